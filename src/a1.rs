@@ -0,0 +1,433 @@
+use anyhow::{anyhow, bail, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Widest grid Google Sheets allows (column ZZZ).
+const MAX_COLUMNS: usize = 18_278;
+
+/// A sheet column, by its 1-based number (`A` = 1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Column(usize);
+
+impl Column {
+    pub fn from_number(number: usize) -> Result<Self> {
+        if number == 0 || number > MAX_COLUMNS {
+            bail!("column number {} is outside 1..={}", number, MAX_COLUMNS);
+        }
+        Ok(Self(number))
+    }
+
+    /// From a 0-based index, as in a header row's `enumerate()`.
+    pub fn from_index(index: usize) -> Result<Self> {
+        Self::from_number(index + 1)
+    }
+
+    pub fn number(self) -> usize {
+        self.0
+    }
+
+    pub fn index(self) -> usize {
+        self.0 - 1
+    }
+
+    /// The column `by` places to the right.
+    pub fn offset(self, by: usize) -> Result<Self> {
+        Self::from_number(self.0 + by)
+    }
+}
+
+impl FromStr for Column {
+    type Err = anyhow::Error;
+
+    /// Column letters, case-insensitive (`C`, `aa`).
+    fn from_str(letters: &str) -> Result<Self> {
+        let letters = letters.trim();
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            bail!("invalid column letters '{}'", letters);
+        }
+        let number = letters.chars().try_fold(0usize, |acc, c| {
+            let number = acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1);
+            (number <= MAX_COLUMNS).then_some(number)
+        });
+        number
+            .ok_or_else(|| anyhow!("column '{}' is past the widest grid (ZZZ)", letters))
+            .and_then(Self::from_number)
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&column_number_to_letter(self.0))
+    }
+}
+
+/// Column letters to their 1-based number (`A` = 1, `AA` = 27).
+pub fn column_letter_to_number(letters: &str) -> Result<usize> {
+    Ok(letters.parse::<Column>()?.number())
+}
+
+/// 1-based column number to its letters; empty for 0.
+pub fn column_number_to_letter(mut num: usize) -> String {
+    let mut result = String::new();
+    while num > 0 {
+        let rem = (num - 1) % 26;
+        result.insert(0, (b'A' + rem as u8) as char);
+        num = (num - 1) / 26;
+    }
+    result
+}
+
+/// One end of a range: a cell (`C2`), a whole column (`C`) or a whole row
+/// (`2`). Rows are 1-based, as in the sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRef {
+    pub column: Option<Column>,
+    pub row: Option<usize>,
+}
+
+impl FromStr for CellRef {
+    type Err = anyhow::Error;
+
+    /// Accepts `$` anchors (`$C$2`) and drops them.
+    fn from_str(cell: &str) -> Result<Self> {
+        let cell = cell.trim().replace('$', "");
+        let split = cell
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(cell.len());
+        let (letters, digits) = cell.split_at(split);
+        if letters.is_empty() && digits.is_empty() {
+            bail!("empty cell reference");
+        }
+        let column = match letters {
+            "" => None,
+            l => Some(l.parse::<Column>()?),
+        };
+        let row = match digits {
+            "" => None,
+            d if d.chars().all(|c| c.is_ascii_digit()) => match d.parse::<usize>()? {
+                0 => bail!("row 0 in cell reference '{}'", cell),
+                r => Some(r),
+            },
+            _ => bail!("invalid cell reference '{}'", cell),
+        };
+        Ok(Self { column, row })
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(column) = self.column {
+            write!(f, "{}", column)?;
+        }
+        if let Some(row) = self.row {
+            write!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// An A1 range, optionally sheet-qualified: a whole sheet (`'Tab'`), a
+/// cell, a column or row (`C`, `1:1`), or a span (`A1:T1`, `C2:C`). Tab
+/// names are always written quoted, with `'` doubled, so names like
+/// "Founder's Camp" survive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct A1Range {
+    pub sheet: Option<String>,
+    /// `None` for the whole sheet.
+    pub start: Option<CellRef>,
+    /// `None` when `start` alone is the range.
+    pub end: Option<CellRef>,
+}
+
+/// `name` quoted for A1 notation: `Founder's Camp` → `'Founder''s Camp'`.
+pub fn quote_sheet_name(name: &str) -> String {
+    format!("'{}'", name.replace('\'', "''"))
+}
+
+impl A1Range {
+    pub fn whole_sheet(sheet: &str) -> Self {
+        Self {
+            sheet: Some(sheet.to_string()),
+            start: None,
+            end: None,
+        }
+    }
+
+    /// A single cell, `'Tab'!C5`.
+    pub fn cell(sheet: &str, column: Column, row: usize) -> Self {
+        Self {
+            start: Some(CellRef {
+                column: Some(column),
+                row: Some(row),
+            }),
+            ..Self::whole_sheet(sheet)
+        }
+    }
+
+    /// `first` to `last` along one row, `'Tab'!A1:T1`.
+    pub fn row_span(sheet: &str, first: Column, last: Column, row: usize) -> Self {
+        Self {
+            end: Some(CellRef {
+                column: Some(last),
+                row: Some(row),
+            }),
+            ..Self::cell(sheet, first, row)
+        }
+    }
+
+    /// A whole row, `'Tab'!1:1`.
+    pub fn row(sheet: &str, row: usize) -> Self {
+        let row = CellRef {
+            column: None,
+            row: Some(row),
+        };
+        Self {
+            start: Some(row),
+            end: Some(row),
+            ..Self::whole_sheet(sheet)
+        }
+    }
+
+    /// A column from `first_row` down, `'Tab'!C2:C`.
+    pub fn column_from(sheet: &str, column: Column, first_row: usize) -> Self {
+        Self {
+            end: Some(CellRef {
+                column: Some(column),
+                row: None,
+            }),
+            ..Self::cell(sheet, column, first_row)
+        }
+    }
+
+    /// A user-supplied range such as `A:T` (blank for the whole sheet),
+    /// qualified with `sheet`. A tab name in `cells` is replaced.
+    pub fn on_sheet(sheet: &str, cells: &str) -> Result<Self> {
+        if cells.trim().is_empty() {
+            return Ok(Self::whole_sheet(sheet));
+        }
+        let range = match cells.parse::<Self>()? {
+            Self { start: None, .. } => bail!("'{}' is not a cell range", cells),
+            range => range,
+        };
+        Ok(Self {
+            sheet: Some(sheet.to_string()),
+            ..range
+        })
+    }
+
+    /// The tab name, for ranges that must have one.
+    pub fn sheet_name(&self) -> Result<&str> {
+        self.sheet
+            .as_deref()
+            .ok_or_else(|| anyhow!("range '{}' has no tab name", self))
+    }
+
+    /// 0-based first row and column; an open start is the top-left.
+    pub fn start_index(&self) -> (usize, usize) {
+        let start = self.start.unwrap_or(CellRef {
+            column: None,
+            row: None,
+        });
+        (
+            start.row.map_or(0, |r| r - 1),
+            start.column.map_or(0, Column::index),
+        )
+    }
+
+    /// 0-based last row and column; `None` where the range is open.
+    pub fn end_index(&self) -> (Option<usize>, Option<usize>) {
+        match self.end.or(self.start) {
+            Some(end) => (end.row.map(|r| r - 1), end.column.map(Column::index)),
+            None => (None, None),
+        }
+    }
+}
+
+impl FromStr for A1Range {
+    type Err = anyhow::Error;
+
+    fn from_str(range: &str) -> Result<Self> {
+        let range = range.trim();
+        let (sheet, cells) = if let Some(quoted) = range.strip_prefix('\'') {
+            // `''` inside a quoted tab name is an escaped quote.
+            let mut sheet = String::new();
+            let mut chars = quoted.char_indices().peekable();
+            let mut rest = None;
+            while let Some((i, c)) = chars.next() {
+                if c == '\'' {
+                    if chars.peek().map(|(_, n)| *n) == Some('\'') {
+                        chars.next();
+                        sheet.push('\'');
+                        continue;
+                    }
+                    rest = Some(&quoted[i + 1..]);
+                    break;
+                }
+                sheet.push(c);
+            }
+            let rest = rest.ok_or_else(|| anyhow!("unterminated tab name in '{}'", range))?;
+            let cells = match rest.strip_prefix('!') {
+                Some(cells) => cells,
+                None if rest.is_empty() => "",
+                None => bail!("expected '!' after tab name in '{}'", range),
+            };
+            (Some(sheet), cells)
+        } else {
+            match range.split_once('!') {
+                Some((sheet, cells)) => (Some(sheet.to_string()), cells),
+                None => (None, range),
+            }
+        };
+        if sheet.as_deref().is_some_and(str::is_empty) {
+            bail!("empty tab name in '{}'", range);
+        }
+        if cells.is_empty() {
+            return match sheet {
+                Some(sheet) => Ok(Self::whole_sheet(&sheet)),
+                None => bail!("empty range"),
+            };
+        }
+
+        let parsed = match cells.split_once(':') {
+            Some((start, end)) => start
+                .parse::<CellRef>()
+                .and_then(|start| Ok((start, Some(end.parse::<CellRef>()?)))),
+            None => cells.parse::<CellRef>().map(|start| (start, None)),
+        };
+        match (parsed, sheet) {
+            (Ok((start, end)), sheet) => Ok(Self {
+                sheet,
+                start: Some(start),
+                end,
+            }),
+            // An unqualified name that isn't a cell reference is a tab,
+            // the way the Sheets API reads it.
+            (Err(_), None) if !cells.contains(':') => Ok(Self::whole_sheet(cells)),
+            (Err(e), _) => Err(e.context(format!("invalid range '{}'", range))),
+        }
+    }
+}
+
+impl fmt::Display for A1Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(sheet) = &self.sheet {
+            f.write_str(&quote_sheet_name(sheet))?;
+            if self.start.is_some() {
+                f.write_str("!")?;
+            }
+        }
+        if let Some(start) = self.start {
+            write!(f, "{}", start)?;
+        }
+        if let Some(end) = self.end {
+            write!(f, ":{}", end)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(letters: &str) -> Column {
+        letters.parse().unwrap()
+    }
+
+    #[test]
+    fn converts_column_letters_both_ways() {
+        for (letters, number) in [("A", 1), ("Z", 26), ("AA", 27), ("AZ", 52), ("ZZZ", 18_278)] {
+            assert_eq!(column_letter_to_number(letters).unwrap(), number);
+            assert_eq!(column_number_to_letter(number), letters);
+        }
+        assert_eq!(column("aa"), column("AA"));
+        assert_eq!(column_number_to_letter(0), "");
+        assert_eq!(column("C").offset(2).unwrap(), column("E"));
+
+        for bad in ["", "A1", "Ä", "AAAA", "-"] {
+            assert!(bad.parse::<Column>().is_err(), "{:?}", bad);
+        }
+        assert!(Column::from_number(0).is_err());
+        assert!(column("ZZZ").offset(1).is_err());
+    }
+
+    #[test]
+    fn quotes_tab_names_with_escaped_apostrophes() {
+        assert_eq!(quote_sheet_name("Founder's Camp"), "'Founder''s Camp'");
+        let range = A1Range::row_span("Founder's Camp", column("A"), column("T"), 1);
+        assert_eq!(range.to_string(), "'Founder''s Camp'!A1:T1");
+        assert_eq!(
+            A1Range::column_from("Search", column("C"), 2).to_string(),
+            "'Search'!C2:C"
+        );
+        assert_eq!(A1Range::row("Search", 1).to_string(), "'Search'!1:1");
+        assert_eq!(A1Range::whole_sheet("It's").to_string(), "'It''s'");
+    }
+
+    #[test]
+    fn parses_quoted_ranges_back() {
+        let range: A1Range = "'Founder''s Camp'!A1:T1".parse().unwrap();
+        assert_eq!(range.sheet_name().unwrap(), "Founder's Camp");
+        assert_eq!(
+            range,
+            A1Range::row_span("Founder's Camp", column("A"), column("T"), 1)
+        );
+        assert_eq!(range.start_index(), (0, 0));
+        assert_eq!(range.end_index(), (Some(0), Some(19)));
+
+        let whole: A1Range = "'Founder''s Camp'".parse().unwrap();
+        assert_eq!(whole, A1Range::whole_sheet("Founder's Camp"));
+        let unquoted: A1Range = "Frontier!C2:C".parse().unwrap();
+        assert_eq!(unquoted, A1Range::column_from("Frontier", column("C"), 2));
+        assert_eq!(unquoted.end_index(), (None, Some(2)));
+        // A bare name that isn't a cell reference is a tab.
+        let tab: A1Range = "Frontier".parse().unwrap();
+        assert_eq!(tab, A1Range::whole_sheet("Frontier"));
+    }
+
+    #[test]
+    fn drops_dollar_anchors() {
+        let cell: CellRef = "$C$2".parse().unwrap();
+        assert_eq!(
+            cell,
+            CellRef {
+                column: Some(column("C")),
+                row: Some(2)
+            }
+        );
+        let range = A1Range::on_sheet("Frontier", "$A$1:$T").unwrap();
+        assert_eq!(range.to_string(), "'Frontier'!A1:T");
+        assert_eq!(
+            A1Range::on_sheet("Frontier", "Old Tab!B:C")
+                .unwrap()
+                .to_string(),
+            "'Frontier'!B:C"
+        );
+        assert_eq!(
+            A1Range::on_sheet("Frontier", " ").unwrap(),
+            A1Range::whole_sheet("Frontier")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_ranges() {
+        for bad in [
+            "",
+            "'Unterminated!A1",
+            "'Tab'A1",
+            "''!A1",
+            "!A1",
+            "Tab!A0",
+            "Tab!1A",
+            "Tab!A1:",
+            "Tab!A1:B2x",
+            "A1:?",
+            "Tab!AAAA1",
+        ] {
+            assert!(bad.parse::<A1Range>().is_err(), "{:?}", bad);
+        }
+        assert!(A1Range::on_sheet("Frontier", "Other!").is_err());
+        assert!(A1Range::cell("x", column("A"), 1).sheet_name().is_ok());
+        assert!("A1:B2".parse::<A1Range>().unwrap().sheet_name().is_err());
+    }
+}
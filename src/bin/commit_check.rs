@@ -21,11 +21,17 @@
 use anyhow::{anyhow, Result};
 use dotenvy::dotenv;
 use futures::{stream, StreamExt};
-use google_sheets4::{api::ValueRange, Sheets};
+use google_sheets4::api::ValueRange;
+use integration_validation::cell_format::{highlights_enabled, ColumnFormat, HighlightWhen, GREEN};
 use integration_validation::github::{classify_github_url, GitHubUrlType};
+use integration_validation::header_rules::{explain_columns_requested, HeaderRules};
+use integration_validation::row_locator::RowLocator;
+use integration_validation::rows::SheetRow;
+use integration_validation::sheet_store::{with_flush_result, SheetStore};
 use integration_validation::sheets::{
-    batch_update_values, init_sheets, read_columns_from_sheet, resolve_or_append_columns,
+    cell_updates, open_sheet_store, read_columns_from_sheet, resolve_or_append_columns,
 };
+use integration_validation::types::SubmissionRow;
 use reqwest::header::USER_AGENT;
 use reqwest::Client;
 use serde_json::{json, Value};
@@ -49,6 +55,7 @@ fn extract_owner(url: &str) -> Option<String> {
     match classify_github_url(url) {
         GitHubUrlType::User(owner) => Some(owner),
         GitHubUrlType::Repo { owner, .. } => Some(owner),
+        GitHubUrlType::Gist { owner, .. } => owner,
         GitHubUrlType::Invalid => None,
     }
 }
@@ -206,8 +213,38 @@ async fn commit_activity(
     None
 }
 
+/// One finished row: the row it was read from, its repo URL, and the
+/// commit count and active days to write.
+type PendingRow = (usize, String, String, String);
+
+/// Writes `pending` to the rows that hold each repo URL now, after one fresh
+/// read of the URL column. Returns how many rows were written.
+async fn flush_rows(
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    sheet_name: &str,
+    locator: &RowLocator,
+    cols: (&str, &str),
+    pending: Vec<PendingRow>,
+) -> Result<usize> {
+    let index = locator.snapshot(sheets, spreadsheet_id).await?;
+    let mut updates: Vec<ValueRange> = Vec::new();
+    for (hint, repo_url, commits_val, days_val) in pending {
+        let Some(row) = index.row_for(&repo_url, hint) else {
+            continue;
+        };
+        let cells = [(cols.0, commits_val), (cols.1, days_val)];
+        updates.extend(cell_updates(sheet_name, row, &cells)?);
+    }
+    let rows = updates.len() / 2;
+    // Entered so the counts land as numbers, not text.
+    sheets.write_entered(spreadsheet_id, updates).await?;
+    Ok(rows)
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn run_check(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     http: &Client,
     github_token: &str,
     spreadsheet_id: &str,
@@ -219,18 +256,17 @@ async fn run_check(
     commits_header: &str,
     active_header: &str,
     concurrency: usize,
+    header_rules: &HeaderRules,
 ) -> Result<()> {
     let columns = read_columns_from_sheet(sheets, spreadsheet_id, sheet_name, read_range).await?;
 
-    // Most-specific keyword first; a tab may name the column "Repo URL",
-    // "GitHub", "Github Link", etc.
-    let repo_key = ["repo url", "github", "repo", "gh"]
-        .iter()
-        .find_map(|kw| columns.keys().find(|k| k.to_lowercase().contains(kw)))
-        .cloned()
+    // The same header rules as the scraper's `snapshot_url`: a tab may name
+    // the column "Repo URL", "GitHub", "Github Link", etc.
+    let repo_key = header_rules
+        .header_for(columns.keys().map(String::as_str), "snapshot_url")
         .ok_or_else(|| {
             anyhow!(
-                "No column containing 'repo url'/'github'/'repo'/'gh' in sheet '{}' range {}",
+                "No repo URL column (header rules field 'snapshot_url') in sheet '{}' range {}",
                 sheet_name,
                 read_range
             )
@@ -260,9 +296,21 @@ async fn run_check(
         "Writing '{}' → col {}, '{}' → col {}.",
         commits_header, commits_col, active_header, active_col
     );
+    let mut active_format = ColumnFormat::count(&active_col)?;
+    if highlights_enabled() {
+        active_format = active_format.highlight(HighlightWhen::NumberGreaterThan(0.0), GREEN);
+    }
+    sheets
+        .format_columns(
+            spreadsheet_id,
+            sheet_name,
+            &[ColumnFormat::count(&commits_col)?, active_format],
+        )
+        .await?;
 
     // Row index in the sheet is +2: row 1 is the header, and `repos` starts at
-    // the first data row.
+    // the first data row. Rows are re-located by URL when written.
+    let locator = RowLocator::for_header(sheets, spreadsheet_id, sheet_name, &repo_key).await?;
     let work: Vec<(usize, String)> = repos
         .into_iter()
         .enumerate()
@@ -281,7 +329,7 @@ async fn run_check(
     // Stream results as each row completes (up to `concurrency` in flight) and
     // flush to the sheet every ~FLUSH_ROWS rows, so a mid-run crash keeps the
     // rows already written instead of losing the whole batch. Rows arrive out
-    // of order, but each write targets an absolute cell range, so order doesn't
+    // of order, but each flush finds every row by its URL, so order doesn't
     // matter. Each row emits 2 ranges (commits + active days).
     const FLUSH_ROWS: usize = 50;
     let total = work.len();
@@ -300,7 +348,7 @@ async fn run_check(
         .buffer_unordered(concurrency);
     futures::pin_mut!(stream);
 
-    let mut pending: Vec<ValueRange> = Vec::new();
+    let mut pending: Vec<PendingRow> = Vec::new();
     let mut processed = 0usize;
     let mut written_rows = 0usize;
 
@@ -331,22 +379,18 @@ async fn run_check(
                 .map(|a| a.active_days.min(window_days).to_string())
                 .unwrap_or_else(|| "—".to_string()),
         );
-        pending.push(ValueRange {
-            range: Some(format!("'{}'!{}{}", sheet_name, commits_col, row)),
-            values: Some(vec![vec![commits_val]]),
-            major_dimension: Some("ROWS".to_string()),
-            ..Default::default()
-        });
-        pending.push(ValueRange {
-            range: Some(format!("'{}'!{}{}", sheet_name, active_col, row)),
-            values: Some(vec![vec![days_val]]),
-            major_dimension: Some("ROWS".to_string()),
-            ..Default::default()
-        });
+        pending.push((row, repo_url, commits_val, days_val));
 
-        if pending.len() >= FLUSH_ROWS * 2 {
-            let rows = pending.len() / 2;
-            batch_update_values(sheets, spreadsheet_id, std::mem::take(&mut pending)).await?;
+        if pending.len() >= FLUSH_ROWS {
+            let rows = flush_rows(
+                sheets,
+                spreadsheet_id,
+                sheet_name,
+                &locator,
+                (&commits_col, &active_col),
+                std::mem::take(&mut pending),
+            )
+            .await?;
             written_rows += rows;
             println!(
                 "  ✅ flushed {} row(s) ({}/{} written so far)",
@@ -357,8 +401,15 @@ async fn run_check(
 
     // Final flush for the remainder.
     if !pending.is_empty() {
-        let rows = pending.len() / 2;
-        batch_update_values(sheets, spreadsheet_id, std::mem::take(&mut pending)).await?;
+        let rows = flush_rows(
+            sheets,
+            spreadsheet_id,
+            sheet_name,
+            &locator,
+            (&commits_col, &active_col),
+            std::mem::take(&mut pending),
+        )
+        .await?;
         written_rows += rows;
         println!(
             "  ✅ flushed final {} row(s) ({}/{} total)",
@@ -405,12 +456,19 @@ async fn main() -> Result<()> {
         sheet_name, read_range, window_days, from, to
     );
 
-    let sheets = init_sheets().await?;
+    let sheets = open_sheet_store().await?;
+    let header_rules = HeaderRules::load()?.with_defaults(SubmissionRow::ALIASES)?;
+    if explain_columns_requested() {
+        let headers = sheets.read_header(&spreadsheet_id, &sheet_name).await?;
+        println!("Columns of '{}':", sheet_name);
+        println!("{}", header_rules.explain(&headers, &["snapshot_url"]));
+        return Ok(());
+    }
     let http = Client::new();
     println!("Initialized Sheets + GitHub clients.");
 
-    run_check(
-        &sheets,
+    let result = run_check(
+        &*sheets,
         &http,
         &github_token,
         &spreadsheet_id,
@@ -422,8 +480,11 @@ async fn main() -> Result<()> {
         &commits_header,
         &active_header,
         concurrency,
+        &header_rules,
     )
-    .await?;
+    .await;
+    // Writes queued behind the quota still go out when the check fails.
+    with_flush_result(result, sheets.flush().await)?;
 
     println!("✅ Commit-activity check complete.");
     Ok(())
@@ -18,10 +18,16 @@ use anyhow::{anyhow, Result};
 use chrono::DateTime;
 use dotenvy::dotenv;
 use futures::{stream, StreamExt};
-use google_sheets4::{api::ValueRange, Sheets};
+use google_sheets4::api::ValueRange;
+use integration_validation::a1::Column;
+use integration_validation::cell_format::{
+    highlights_enabled, hyperlink, ColumnFormat, HighlightWhen, GREEN,
+};
+use integration_validation::header_rules::{explain_columns_requested, HeaderRule, HeaderRules};
+use integration_validation::row_locator::RowLocator;
+use integration_validation::sheet_store::{with_flush_result, SheetStore};
 use integration_validation::sheets::{
-    batch_update_values, column_letter_to_number, column_number_to_letter, init_sheets,
-    read_columns_from_sheet, resolve_or_append_columns,
+    cell_updates, open_sheet_store, read_columns_from_sheet, resolve_or_append_columns, write_row,
 };
 use reqwest::Client;
 use serde_json::{json, Value};
@@ -54,6 +60,12 @@ const DEFAULT_CONCURRENCY: usize = 10;
 const DEFAULT_RPC_BATCH: bool = true;
 /// Max JSON-RPC sub-requests per batched POST.
 const BATCH_SIZE: usize = 100;
+/// Headers read as the program address, under any `SCRAPER_HEADER_RULES`.
+const PROGRAM_ADDRESS_RULES: &[HeaderRule] = &[HeaderRule::contains(
+    "program_address",
+    "program address",
+    30,
+)];
 
 // ─── Signature scan result (sigs + time window) ──────────────────────────────
 struct SigScan {
@@ -226,7 +238,7 @@ impl SolanaClient {
         let mut before: Option<String> = None;
 
         while all.len() < max_total {
-            let limit = (max_total - all.len()).min(1000).max(1);
+            let limit = (max_total - all.len()).clamp(1, 1000);
             let mut cfg = json!({ "limit": limit });
             if let Some(b) = &before {
                 cfg["before"] = Value::String(b.clone());
@@ -278,6 +290,19 @@ struct Network {
     client: Arc<SolanaClient>,
 }
 
+impl Network {
+    /// The Solana Explorer page of `address` on this network.
+    fn explorer_url(&self, address: &str) -> String {
+        match self.name.as_str() {
+            "mainnet" => format!("https://explorer.solana.com/address/{}", address),
+            cluster => format!(
+                "https://explorer.solana.com/address/{}?cluster={}",
+                address, cluster
+            ),
+        }
+    }
+}
+
 // ─── Per-network result for one source program ───────────────────────────────
 struct NetworkResult {
     counts: Vec<usize>,
@@ -347,7 +372,7 @@ async fn inspect_for_network(
 // ─── Check ───────────────────────────────────────────────────────────────────
 #[allow(clippy::too_many_arguments)]
 async fn run_check(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     networks: Arc<Vec<Network>>,
     spreadsheet_id: &str,
     sheet_name: &str,
@@ -357,15 +382,14 @@ async fn run_check(
     tx_limit: usize,
     concurrency: usize,
     use_batch: bool,
+    header_rules: &HeaderRules,
 ) -> Result<()> {
     let columns = read_columns_from_sheet(sheets, spreadsheet_id, sheet_name, read_range).await?;
-    let pa_key = columns
-        .keys()
-        .find(|k| k.to_lowercase().contains("program address"))
-        .cloned()
+    let pa_key = header_rules
+        .header_for(columns.keys().map(String::as_str), "program_address")
         .ok_or_else(|| {
             anyhow!(
-                "No column containing 'program address' in sheet '{}' range {}",
+                "No program address column (header rules field 'program_address') in sheet '{}' range {}",
                 sheet_name,
                 read_range
             )
@@ -396,23 +420,17 @@ async fn run_check(
     let result_cols: Vec<String> = if write_start_col.trim().is_empty() {
         resolve_or_append_columns(sheets, spreadsheet_id, sheet_name, &header_values).await?
     } else {
-        let start = column_letter_to_number(write_start_col);
-        let cols: Vec<String> = (0..header_values.len())
-            .map(|i| column_number_to_letter(start + i))
-            .collect();
-        let end_col = column_number_to_letter(start + header_values.len().saturating_sub(1));
-        batch_update_values(
+        let start: Column = write_start_col.parse()?;
+        let cols = (0..header_values.len())
+            .map(|i| Ok(start.offset(i)?.to_string()))
+            .collect::<Result<Vec<String>>>()?;
+        write_row(
             sheets,
             spreadsheet_id,
-            vec![ValueRange {
-                range: Some(format!(
-                    "'{}'!{}1:{}1",
-                    sheet_name, write_start_col, end_col
-                )),
-                values: Some(vec![header_values.clone()]),
-                major_dimension: Some("ROWS".to_string()),
-                ..Default::default()
-            }],
+            sheet_name,
+            write_start_col,
+            1,
+            header_values.clone(),
         )
         .await?;
         cols
@@ -421,6 +439,22 @@ async fn run_check(
         "Writing integration results to columns {:?} (total {}).",
         result_cols, cols_per_row
     );
+    // Counts as numbers; with highlights on, totals above zero go green.
+    let highlight = highlights_enabled();
+    let formats = result_cols
+        .iter()
+        .zip(&header_values)
+        .map(|(col, header)| {
+            let format = ColumnFormat::count(col)?;
+            Ok(match highlight && header.ends_with(":total") {
+                true => format.highlight(HighlightWhen::NumberGreaterThan(0.0), GREEN),
+                false => format,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    sheets
+        .format_columns(spreadsheet_id, sheet_name, &formats)
+        .await?;
 
     let mut updates: Vec<ValueRange> = Vec::new();
 
@@ -462,29 +496,38 @@ async fn run_check(
     let mut rows = results;
     rows.sort_by_key(|(r, _, _)| *r);
 
+    // The scan can take a while; write each result to the row holding its
+    // program address now, not the row it was read from.
+    let index = RowLocator::for_header(sheets, spreadsheet_id, sheet_name, &pa_key)
+        .await?
+        .snapshot(sheets, spreadsheet_id)
+        .await?;
+
     // Per-row writes + one log line per network.
-    for (row, prog_a, net_results) in &rows {
+    for (read_row, prog_a, net_results) in &rows {
+        let Some(row) = index.row_for(prog_a, *read_row) else {
+            continue;
+        };
         // Values grouped by network: [net1:T1, net1:T2, …, net1:total,
         //                             net2:T1, net2:T2, …, net2:total].
+        // Each total links to the program on that network's explorer.
         let mut row_values: Vec<String> = Vec::with_capacity(cols_per_row);
-        for net_result in net_results {
+        for (net, net_result) in networks.iter().zip(net_results) {
             for count in &net_result.counts {
                 row_values.push(count.to_string());
             }
             let total: usize = net_result.counts.iter().sum();
-            row_values.push(total.to_string());
+            row_values.push(hyperlink(&net.explorer_url(prog_a), &total.to_string()));
         }
         // One ValueRange per cell — columns may be non-contiguous since each
-        // header was resolved independently. batch_update_values still folds
+        // header was resolved independently. write_entered still folds
         // these into a single API call.
-        for (col, val) in result_cols.iter().zip(row_values.iter()) {
-            updates.push(ValueRange {
-                range: Some(format!("'{}'!{}{}", sheet_name, col, row)),
-                values: Some(vec![vec![val.clone()]]),
-                major_dimension: Some("ROWS".to_string()),
-                ..Default::default()
-            });
-        }
+        let cells: Vec<(&str, String)> = result_cols
+            .iter()
+            .map(String::as_str)
+            .zip(row_values)
+            .collect();
+        updates.extend(cell_updates(sheet_name, row, &cells)?);
 
         for (net, result) in networks.iter().zip(net_results.iter()) {
             let sum: usize = result.counts.iter().sum();
@@ -511,13 +554,13 @@ async fn run_check(
     // counts as ONE write against the 60/min/user quota.
     const CHUNK: usize = 200;
     let total_ranges = updates.len();
-    let total_batches = (total_ranges + CHUNK - 1) / CHUNK;
+    let total_batches = total_ranges.div_ceil(CHUNK);
     println!(
         "Flushing {} range(s) across {} batch request(s)...",
         total_ranges, total_batches
     );
     for (i, chunk) in updates.chunks(CHUNK).enumerate() {
-        batch_update_values(sheets, spreadsheet_id, chunk.to_vec()).await?;
+        sheets.write_entered(spreadsheet_id, chunk.to_vec()).await?;
         println!(
             "  ✅ batch {}/{} flushed ({} range(s))",
             i + 1,
@@ -586,7 +629,7 @@ async fn main() -> Result<()> {
                 name: (*name).to_string(),
                 client: Arc::new(SolanaClient::new(&url)),
             });
-            println!("  {} ← {} (from {})", name, "ok", env_vars.join("/"));
+            println!("  {} ← ok (from {})", name, env_vars.join("/"));
         }
     }
     if networks.is_empty() {
@@ -639,14 +682,21 @@ async fn main() -> Result<()> {
     );
     println!("Targets: {:?}", TARGETS);
 
-    let sheets = init_sheets().await?;
+    let sheets = open_sheet_store().await?;
+    let header_rules = HeaderRules::load()?.with_defaults(PROGRAM_ADDRESS_RULES)?;
+    if explain_columns_requested() {
+        let headers = sheets.read_header(&spreadsheet_id, &sheet_name).await?;
+        println!("Columns of '{}':", sheet_name);
+        println!("{}", header_rules.explain(&headers, &["program_address"]));
+        return Ok(());
+    }
     println!(
         "Initialized Sheets client. Networks: {:?}",
         networks.iter().map(|n| n.name.as_str()).collect::<Vec<_>>()
     );
 
-    run_check(
-        &sheets,
+    let result = run_check(
+        &*sheets,
         Arc::clone(&networks),
         &spreadsheet_id,
         &sheet_name,
@@ -656,8 +706,10 @@ async fn main() -> Result<()> {
         tx_limit,
         concurrency,
         use_batch,
+        &header_rules,
     )
-    .await?;
+    .await;
+    with_flush_result(result, sheets.flush().await)?;
 
     println!("✅ Integration check complete.");
     Ok(())
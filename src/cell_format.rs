@@ -0,0 +1,250 @@
+use crate::a1::Column;
+use anyhow::Result;
+
+/// Thousands-separated whole numbers, for count columns.
+pub const COUNT_PATTERN: &str = "#,##0";
+
+pub const GREEN: Rgb = Rgb(0.85, 0.94, 0.83);
+pub const RED: Rgb = Rgb(0.96, 0.8, 0.8);
+
+/// A background colour, each channel 0.0–1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub f32, pub f32, pub f32);
+
+/// When a highlight applies to a cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HighlightWhen {
+    TextStartsWith(String),
+    NumberGreaterThan(f64),
+    /// A custom formula written for the column's first data cell (row 2);
+    /// Sheets shifts it for the rows below.
+    Formula(String),
+}
+
+/// A conditional-format rule on one column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    pub when: HighlightWhen,
+    pub color: Rgb,
+}
+
+/// Display format for the data cells (row 2 down) of one column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnFormat {
+    pub column: Column,
+    /// Sheets number-format pattern such as `#,##0`.
+    pub number_pattern: Option<&'static str>,
+    /// Clip overflowing text instead of wrapping it, so a long cell
+    /// doesn't stretch its row.
+    pub clip: bool,
+    pub highlights: Vec<Highlight>,
+}
+
+impl ColumnFormat {
+    pub fn new(column: &str) -> Result<Self> {
+        Ok(Self {
+            column: column.parse()?,
+            number_pattern: None,
+            clip: false,
+            highlights: Vec::new(),
+        })
+    }
+
+    pub fn count(column: &str) -> Result<Self> {
+        Ok(Self {
+            number_pattern: Some(COUNT_PATTERN),
+            ..Self::new(column)?
+        })
+    }
+
+    pub fn highlight(mut self, when: HighlightWhen, color: Rgb) -> Self {
+        self.highlights.push(Highlight { when, color });
+        self
+    }
+}
+
+/// Whether `SHEETS_CONDITIONAL_FORMATS` asks for highlight rules on result
+/// columns (off by default: they are added to the sheet, not rewritten).
+pub fn highlights_enabled() -> bool {
+    std::env::var("SHEETS_CONDITIONAL_FORMATS")
+        .map(|v| {
+            matches!(
+                v.trim().to_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// `text` for a `USER_ENTERED` write that Sheets keeps exactly as typed:
+/// the leading apostrophe stops it being read as a number, date or
+/// formula, and isn't part of the cell's value.
+pub fn literal(text: &str) -> String {
+    match text {
+        "" => String::new(),
+        text => format!("'{}", text),
+    }
+}
+
+/// A count cell: entered as a number when it is one, so number formats and
+/// `>` highlights apply; otherwise as literal text.
+pub fn number(text: &str) -> String {
+    match is_number(text) {
+        true => text.trim().to_string(),
+        false => literal(text),
+    }
+}
+
+/// Whether Sheets reads `text` as a number. Rust also parses `inf` and
+/// `NaN`, which Sheets takes for text (or, in a formula, a name).
+fn is_number(text: &str) -> bool {
+    text.trim().parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// A clickable `HYPERLINK` cell showing `label` (a number label stays a
+/// number). Anything but a single http(s) URL is entered as `label`.
+pub fn hyperlink(url: &str, label: &str) -> String {
+    let url = url.trim();
+    let single_url = (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.contains(char::is_whitespace);
+    if !single_url {
+        return number(label);
+    }
+    let label = match is_number(label) {
+        true => label.trim().to_string(),
+        false => quote(label),
+    };
+    format!("=HYPERLINK({},{})", quote(url), label)
+}
+
+/// A URL cell that links to itself.
+pub fn url_link(url: &str) -> String {
+    hyperlink(url, url)
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// What Sheets shows for an entered value, for backends that store text
+/// only: literals lose their apostrophe and links show their label.
+pub fn displayed(entered: &str) -> String {
+    if let Some(text) = entered.strip_prefix('\'') {
+        return text.to_string();
+    }
+    let Some(args) = entered
+        .strip_prefix("=HYPERLINK(")
+        .and_then(|a| a.strip_suffix(')'))
+    else {
+        return entered.to_string();
+    };
+    // The URL is always quoted; the label follows the comma after it.
+    let mut in_quotes = false;
+    let mut chars = args.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek().map(|(_, n)| *n) == Some('"') => {
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                let label = args[i + 1..].trim();
+                return match label.strip_prefix('"').and_then(|l| l.strip_suffix('"')) {
+                    Some(text) => text.replace("\"\"", "\""),
+                    None => label.to_string(),
+                };
+            }
+            _ => {}
+        }
+    }
+    entered.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literals_keep_text_as_typed() {
+        assert_eq!(literal("0042"), "'0042");
+        assert_eq!(literal("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(literal(""), "");
+        assert_eq!(displayed(&literal("'quoted")), "'quoted");
+    }
+
+    #[test]
+    fn numbers_are_entered_bare_and_anything_else_as_text() {
+        assert_eq!(number(" 12 "), "12");
+        assert_eq!(number("-0.5"), "-0.5");
+        assert_eq!(number("1e3"), "1e3");
+        assert_eq!(number("12 files"), "'12 files");
+        assert_eq!(number("inf"), "'inf");
+        assert_eq!(number("NaN"), "'NaN");
+        assert_eq!(number(""), "");
+    }
+
+    #[test]
+    fn hyperlinks_quote_the_url_and_text_labels() {
+        assert_eq!(
+            hyperlink(" https://github.com/a/b ", "a/b"),
+            r#"=HYPERLINK("https://github.com/a/b","a/b")"#
+        );
+        assert_eq!(
+            hyperlink("https://example.com/?q=\"x\"", "say \"hi\""),
+            r#"=HYPERLINK("https://example.com/?q=""x""","say ""hi""")"#
+        );
+        assert_eq!(
+            hyperlink("https://example.com", " 7 "),
+            r#"=HYPERLINK("https://example.com",7)"#
+        );
+        assert_eq!(
+            hyperlink("https://example.com", "inf"),
+            r#"=HYPERLINK("https://example.com","inf")"#
+        );
+        // Not a single http(s) URL: just the label.
+        assert_eq!(hyperlink("file:///work/game", "game"), "'game");
+        assert_eq!(hyperlink("ftp://example.com", "3"), "3");
+        assert_eq!(hyperlink("https://a.com https://b.com", "two"), "'two");
+        assert_eq!(hyperlink("", ""), "");
+        assert_eq!(
+            url_link("https://a.com"),
+            r#"=HYPERLINK("https://a.com","https://a.com")"#
+        );
+    }
+
+    #[test]
+    fn displayed_shows_what_was_entered() {
+        let urls = [
+            "https://example.com/a,b?q=\"x\"",
+            "http://example.com",
+            "file:///work/game",
+            "",
+        ];
+        let labels = [
+            "plain",
+            "say \"hi\", then leave",
+            "\"",
+            ",",
+            "a,\"b\",c",
+            " padded ",
+            "42",
+            "-1.5",
+            "inf",
+            "",
+        ];
+        for url in urls {
+            for label in labels {
+                let shown = displayed(&hyperlink(url, label));
+                let expected = if is_number(label) {
+                    label.trim()
+                } else {
+                    label
+                };
+                assert_eq!(shown, expected, "{:?} {:?}", url, label);
+            }
+        }
+        assert_eq!(displayed("plain"), "plain");
+        assert_eq!(displayed("=SUM(A1)"), "=SUM(A1)");
+        assert_eq!(displayed(""), "");
+    }
+}
@@ -0,0 +1,444 @@
+use crate::types::{PathCategory, RepoMap};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Which MagicBlock feature each scanner keyword is evidence of. Keywords not
+/// listed here still count towards `keyword_matches` but don't label the repo.
+const KEYWORD_FEATURES: &[(&str, &str)] = &[
+    ("ephemeral-rollups-sdk", "delegation/er"),
+    ("ephemeral-rollups-kit", "delegation/er"),
+    ("ephemeral-rollups-pinocchio", "delegation/er"),
+    ("#[ephemeral]", "delegation/er"),
+    ("#[commit]", "delegation/er"),
+    ("#[delegate]", "delegation/er"),
+    ("delegate_account", "delegation/er"),
+    ("undelegate_account", "delegation/er"),
+    ("MagicIntentBundleBuilder", "delegation/er"),
+    ("ephemeral-vrf-sdk", "vrf"),
+    (
+        "SPLxh1LVZzEkX99H6rqYizhytLWPZVV296zyYDPagv2",
+        "ephemeral spl",
+    ),
+    ("payments.magicblock.app", "payments"),
+    ("session-keys", "session/auth"),
+    ("await getAuthToken", "session/auth"),
+];
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IntegrationSummary {
+    /// Distinct features found, in stable (alphabetical) order.
+    pub features: Vec<String>,
+    /// Comma-joined `features`, or "none".
+    pub integration_type: String,
+    /// 0–100: see `classify_integration`.
+    pub depth_score: u32,
+    /// Hits per path category, summed over all keywords.
+    pub category_counts: BTreeMap<PathCategory, usize>,
+}
+
+/// Strips `https://github.com/<owner>/<repo>/blob/<ref>/` (or the GitLab,
+/// Bitbucket and Gitea equivalents) off a file link so path rules only look
+/// at the in-repo path (a repo called `examples` must not turn every hit
+/// into an example hit).
+pub fn repo_relative_path(file_url: &str) -> &str {
+    const MARKERS: [&str; 3] = ["/blob/", "/src/branch/", "/src/commit/"];
+    // Bitbucket's bare `/src/<ref>/` would also match any `src` directory,
+    // such as one in a local checkout's `file://` link.
+    let bitbucket_marker = file_url
+        .starts_with("https://bitbucket.org/")
+        .then_some("/src/");
+    match MARKERS
+        .iter()
+        .copied()
+        .chain(bitbucket_marker)
+        .find_map(|m| file_url.find(m).map(|idx| idx + m.len()))
+    {
+        Some(start) => {
+            let rest = &file_url[start..];
+            rest.split_once('/').map(|(_, path)| path).unwrap_or(rest)
+        }
+        None => file_url,
+    }
+}
+
+/// One ordered path rule: a file whose path contains any of `patterns` is
+/// put in `category`. Patterns are matched case-insensitively against the
+/// repo-relative path with a leading `/`, so `/tests/` also matches a
+/// top-level `tests` directory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PathRule {
+    pub category: PathCategory,
+    pub patterns: Vec<String>,
+}
+
+impl PathRule {
+    fn new(category: PathCategory, patterns: &[&str]) -> Self {
+        Self {
+            category,
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+/// Built-in rules; the first matching rule wins and unmatched files count as
+/// client code. Vendored copies come first so an SDK checkout's own tests
+/// and manifests don't leak into the other categories.
+pub fn default_path_rules() -> Vec<PathRule> {
+    vec![
+        PathRule::new(
+            PathCategory::Vendored,
+            &[
+                "/vendor/",
+                "/vendored/",
+                "/third_party/",
+                "/ephemeral-rollups-sdk/",
+                "/ephemeral_rollups_sdk/",
+                "/ephemeral-rollups-kit/",
+                "/ephemeral-vrf-sdk/",
+            ],
+        ),
+        PathRule::new(
+            PathCategory::Test,
+            &[
+                "/test/",
+                "/tests/",
+                "/__tests__/",
+                ".test.",
+                ".spec.",
+                "_test.rs",
+            ],
+        ),
+        PathRule::new(
+            PathCategory::Example,
+            &["/example/", "/examples/", "/script/", "/scripts/"],
+        ),
+        PathRule::new(PathCategory::Config, &[".toml", ".json"]),
+        PathRule::new(
+            PathCategory::Client,
+            &["/app/", "/client/", "/frontend/", "/web/", "/cli/"],
+        ),
+        PathRule::new(PathCategory::Program, &["/programs/", ".rs"]),
+    ]
+}
+
+/// Default rules, preceded by any rules from the JSON file named in
+/// `SCRAPER_PATH_RULES` (`[{"category": "test", "patterns": ["/spec/"]}]`),
+/// so custom rules take priority.
+pub fn load_path_rules() -> Result<Vec<PathRule>> {
+    let mut rules = match std::env::var("SCRAPER_PATH_RULES") {
+        Ok(path) if !path.trim().is_empty() => {
+            let raw = std::fs::read_to_string(path.trim())
+                .with_context(|| format!("reading SCRAPER_PATH_RULES file '{}'", path))?;
+            serde_json::from_str::<Vec<PathRule>>(&raw)
+                .with_context(|| format!("parsing SCRAPER_PATH_RULES file '{}'", path))?
+        }
+        _ => Vec::new(),
+    };
+    rules.extend(default_path_rules());
+    Ok(rules)
+}
+
+pub fn categorize_path(path: &str, rules: &[PathRule]) -> PathCategory {
+    let lower = format!("/{}", path.trim_start_matches('/').to_lowercase());
+    rules
+        .iter()
+        .find(|rule| {
+            rule.patterns
+                .iter()
+                .any(|p| lower.contains(&p.to_lowercase()))
+        })
+        .map(|rule| rule.category)
+        .unwrap_or(PathCategory::Client)
+}
+
+/// Labels a repo's integration from its keyword hits and scores its depth:
+///   - up to 60 points for distinct features (15 each, capped at 4),
+///   - up to 20 points for distinct files with hits (2 each, capped at 10),
+///   - up to 20 points for the share of hits in production (program, client,
+///     config) paths rather than test/example/vendored ones.
+pub fn classify_integration(keyword_counts: &RepoMap) -> IntegrationSummary {
+    let mut features: BTreeSet<String> = BTreeSet::new();
+    let mut files: BTreeSet<&str> = BTreeSet::new();
+    let mut category_counts: BTreeMap<PathCategory, usize> = BTreeMap::new();
+
+    for (keyword, result) in keyword_counts {
+        if result.count == 0 {
+            continue;
+        }
+        if let Some((_, feature)) = KEYWORD_FEATURES
+            .iter()
+            .find(|(kw, _)| kw.eq_ignore_ascii_case(keyword))
+        {
+            features.insert(feature.to_string());
+        }
+        files.extend(result.files.iter().map(|f| repo_relative_path(f)));
+        for (category, count) in &result.categories {
+            *category_counts.entry(*category).or_default() += count;
+        }
+    }
+
+    let total_hits: usize = category_counts.values().sum();
+    let production_hits: usize = category_counts
+        .iter()
+        .filter(|(category, _)| category.is_production())
+        .map(|(_, count)| count)
+        .sum();
+    let feature_points = features.len().min(4) as u32 * 15;
+    let file_points = files.len().min(10) as u32 * 2;
+    let production_points = (production_hits * 20).checked_div(total_hits).unwrap_or(0) as u32;
+
+    let features: Vec<String> = features.into_iter().collect();
+    let integration_type = if features.is_empty() {
+        "none".to_string()
+    } else {
+        features.join(", ")
+    };
+
+    IntegrationSummary {
+        features,
+        integration_type,
+        depth_score: feature_points + file_points + production_points,
+        category_counts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::KeywordResult;
+
+    fn hit(count: usize, files: &[&str], category: PathCategory) -> KeywordResult {
+        KeywordResult {
+            count,
+            files: files.iter().map(|f| f.to_string()).collect(),
+            categories: BTreeMap::from([(category, count)]),
+        }
+    }
+
+    #[test]
+    fn no_hits_score_zero_and_label_none() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "ephemeral-rollups-sdk".to_string(),
+            hit(0, &[], PathCategory::Program),
+        );
+
+        let summary = classify_integration(&counts);
+        assert_eq!(summary.integration_type, "none");
+        assert!(summary.features.is_empty());
+        assert_eq!(summary.depth_score, 0);
+    }
+
+    #[test]
+    fn unknown_keywords_count_for_depth_but_not_type() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "custom".to_string(),
+            hit(1, &["src/lib.rs"], PathCategory::Program),
+        );
+
+        let summary = classify_integration(&counts);
+        assert_eq!(summary.integration_type, "none");
+        // One file (2) and every hit in production code (20).
+        assert_eq!(summary.depth_score, 22);
+    }
+
+    #[test]
+    fn keywords_of_one_feature_label_it_once() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "#[delegate]".to_string(),
+            hit(2, &["a.rs"], PathCategory::Program),
+        );
+        counts.insert(
+            "#[commit]".to_string(),
+            hit(1, &["a.rs"], PathCategory::Program),
+        );
+        counts.insert(
+            "ephemeral-vrf-sdk".to_string(),
+            hit(1, &["b.toml"], PathCategory::Config),
+        );
+
+        let summary = classify_integration(&counts);
+        assert_eq!(summary.features, vec!["delegation/er", "vrf"]);
+        assert_eq!(summary.integration_type, "delegation/er, vrf");
+        // Two features (30), two files (4), all production (20).
+        assert_eq!(summary.depth_score, 54);
+    }
+
+    #[test]
+    fn feature_lookup_ignores_keyword_case() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "magicintentbundlebuilder".to_string(),
+            hit(1, &["a.ts"], PathCategory::Client),
+        );
+
+        assert_eq!(
+            classify_integration(&counts).features,
+            vec!["delegation/er"]
+        );
+    }
+
+    #[test]
+    fn score_is_capped_at_100() {
+        let keywords = [
+            "ephemeral-rollups-sdk",
+            "ephemeral-vrf-sdk",
+            "payments.magicblock.app",
+            "session-keys",
+            "SPLxh1LVZzEkX99H6rqYizhytLWPZVV296zyYDPagv2",
+        ];
+        let files: Vec<String> = (0..20).map(|i| format!("src/f{}.rs", i)).collect();
+        let files: Vec<&str> = files.iter().map(String::as_str).collect();
+        let mut counts = RepoMap::new();
+        for kw in keywords {
+            counts.insert(kw.to_string(), hit(3, &files, PathCategory::Program));
+        }
+
+        let summary = classify_integration(&counts);
+        assert_eq!(summary.features.len(), 5);
+        assert_eq!(summary.depth_score, 100);
+    }
+
+    #[test]
+    fn test_and_example_hits_earn_no_production_points() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "ephemeral-rollups-sdk".to_string(),
+            KeywordResult {
+                count: 4,
+                files: vec!["tests/a.rs".to_string(), "examples/b.rs".to_string()],
+                categories: BTreeMap::from([(PathCategory::Test, 3), (PathCategory::Example, 1)]),
+            },
+        );
+
+        let summary = classify_integration(&counts);
+        // One feature (15) and two files (4); no production hits.
+        assert_eq!(summary.depth_score, 19);
+        assert_eq!(summary.category_counts[&PathCategory::Test], 3);
+    }
+
+    #[test]
+    fn production_points_follow_the_production_share() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "custom".to_string(),
+            KeywordResult {
+                count: 4,
+                files: vec!["src/lib.rs".to_string()],
+                categories: BTreeMap::from([(PathCategory::Program, 1), (PathCategory::Test, 3)]),
+            },
+        );
+
+        // One file (2) and a quarter of the hits in production (5).
+        assert_eq!(classify_integration(&counts).depth_score, 7);
+    }
+
+    #[test]
+    fn files_are_counted_by_repo_relative_path() {
+        let mut counts = RepoMap::new();
+        counts.insert(
+            "custom".to_string(),
+            hit(
+                2,
+                &[
+                    "https://github.com/o/r/blob/main/src/lib.rs",
+                    "https://github.com/o/r/blob/dev/src/lib.rs",
+                ],
+                PathCategory::Program,
+            ),
+        );
+
+        assert_eq!(classify_integration(&counts).depth_score, 22);
+    }
+
+    #[test]
+    fn vendored_sdk_copies_win_over_test_and_program_rules() {
+        let rules = default_path_rules();
+        for path in [
+            "vendor/ephemeral-rollups-sdk/src/lib.rs",
+            "third_party/anything/tests/it.rs",
+            "libs/ephemeral-rollups-sdk/tests/delegate.rs",
+            "packages/ephemeral_rollups_sdk/Cargo.toml",
+        ] {
+            assert_eq!(
+                categorize_path(path, &rules),
+                PathCategory::Vendored,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn paths_fall_into_the_first_matching_category() {
+        let rules = default_path_rules();
+        let cases = [
+            ("tests/delegate.rs", PathCategory::Test),
+            ("app/src/delegate.test.ts", PathCategory::Test),
+            ("programs/counter/src/lib_test.rs", PathCategory::Test),
+            ("examples/counter/src/lib.rs", PathCategory::Example),
+            ("scripts/deploy.ts", PathCategory::Example),
+            ("programs/counter/Cargo.toml", PathCategory::Config),
+            ("app/src/index.ts", PathCategory::Client),
+            ("programs/counter/src/lib.rs", PathCategory::Program),
+            ("src/index.ts", PathCategory::Client),
+        ];
+        for (path, category) in cases {
+            assert_eq!(categorize_path(path, &rules), category, "{}", path);
+        }
+    }
+
+    #[test]
+    fn path_rules_ignore_case_and_a_leading_slash() {
+        let rules = default_path_rules();
+        assert_eq!(categorize_path("/Tests/Foo.rs", &rules), PathCategory::Test);
+        assert_eq!(
+            categorize_path("Vendor/x.ts", &rules),
+            PathCategory::Vendored
+        );
+    }
+
+    #[test]
+    fn custom_rules_take_priority_over_defaults() {
+        let mut rules = vec![PathRule::new(PathCategory::Test, &["/spec/"])];
+        rules.extend(default_path_rules());
+        assert_eq!(
+            categorize_path("programs/spec/lib.rs", &rules),
+            PathCategory::Test
+        );
+    }
+
+    #[test]
+    fn repo_name_does_not_decide_the_category() {
+        let rules = default_path_rules();
+        let path = repo_relative_path("https://github.com/team/examples/blob/main/src/lib.rs");
+        assert_eq!(path, "src/lib.rs");
+        assert_eq!(categorize_path(path, &rules), PathCategory::Program);
+        assert_eq!(
+            repo_relative_path("https://codeberg.org/team/examples/src/branch/main/app/x.ts"),
+            "app/x.ts"
+        );
+        assert_eq!(repo_relative_path("src/lib.rs"), "src/lib.rs");
+    }
+
+    #[test]
+    fn bitbucket_src_marker_leaves_local_links_whole() {
+        assert_eq!(
+            repo_relative_path("https://bitbucket.org/team/game/src/main/src/lib.rs"),
+            "src/lib.rs"
+        );
+        let local = [
+            "file:///work/game/programs/a/src/lib.rs",
+            "file:///work/game/programs/b/src/lib.rs",
+        ];
+        assert_eq!(repo_relative_path(local[0]), local[0]);
+
+        let mut counts = RepoMap::new();
+        counts.insert("custom".to_string(), hit(2, &local, PathCategory::Program));
+        // Two files (4) and every hit in production code (20).
+        assert_eq!(classify_integration(&counts).depth_score, 24);
+    }
+}
@@ -0,0 +1,243 @@
+use crate::cell_format::{hyperlink, literal};
+use crate::row_locator::RowLocator;
+use crate::sheet_store::SheetStore;
+use crate::sheets::{cell_updates, resolve_or_append_columns};
+use crate::types::{GitHubUpdateData, RepoStatus};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Tab holding result JSON too long for its row's cell.
+pub const DETAILS_SHEET: &str = "Scraper Details";
+
+/// Google Sheets rejects cells longer than this.
+pub const MAX_CELL_CHARS: usize = 50_000;
+
+const DETAILS_HEADERS: [&str; 5] = [
+    "Repo",
+    "Commit SHA",
+    "Part",
+    "Updated",
+    "Scraper Result (JSON)",
+];
+
+/// What the result cell holds instead of JSON over `MAX_CELL_CHARS`: the
+/// headline fields, per-keyword hit counts without file lists, and where
+/// the full JSON went.
+#[derive(Serialize)]
+struct ResultSummary<'a> {
+    details_sheet: &'static str,
+    /// Rows of `details_sheet` holding the full JSON, in part order.
+    details_rows: Vec<usize>,
+    details_url: Option<String>,
+    full_json_chars: usize,
+    owner: &'a str,
+    repo_name: &'a str,
+    commit_sha: &'a str,
+    snapshot_url: &'a str,
+    repo_status: Option<RepoStatus>,
+    keyword_matches: &'a str,
+    keyword_counts: BTreeMap<&'a str, usize>,
+    files_processed: &'a str,
+    integration_type: &'a str,
+    integration_features: &'a [String],
+    depth_score: u32,
+    team_size: Option<usize>,
+}
+
+/// `owner/repo` of the scanned repo, or of each repo of a merged row.
+fn repo_key(data: &GitHubUpdateData) -> String {
+    match data.repos.as_slice() {
+        [] => format!("{}/{}", data.owner, data.repo_name),
+        parts => parts
+            .iter()
+            .map(|p| format!("{}/{}", p.owner, p.repo_name))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// `text` in pieces of at most `max` characters.
+fn split_chars(text: &str, max: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.chunks(max).map(|c| c.iter().collect()).collect()
+}
+
+/// The result cell for `data` when its JSON is too long for one cell, or
+/// `None` when it fits. The full JSON is written to `DETAILS_SHEET` in
+/// parts that fit a cell, one row per part, keyed by repo and commit SHA
+/// (a rescan of the same commit reuses its rows); the returned cell is a
+/// compact summary linking to the first part.
+pub async fn overflow_result(
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    data: &GitHubUpdateData,
+) -> Result<Option<String>> {
+    let json = serde_json::to_string(data)?;
+    let json_chars = json.chars().count();
+    // The cell is written as a literal, whose leading apostrophe counts too.
+    if json_chars < MAX_CELL_CHARS {
+        return Ok(None);
+    }
+
+    sheets.ensure_sheet(spreadsheet_id, DETAILS_SHEET).await?;
+    let headers: Vec<String> = DETAILS_HEADERS.iter().map(|h| h.to_string()).collect();
+    let cols = resolve_or_append_columns(sheets, spreadsheet_id, DETAILS_SHEET, &headers).await?;
+
+    // Rows already holding this repo and commit, in sheet order, read
+    // right before writing. Only the Repo and Commit SHA columns are read,
+    // not the JSON parts.
+    let repo = repo_key(data);
+    let key = RowLocator::compound_key(&[&repo, &data.commit_sha]);
+    let index = RowLocator::new(DETAILS_SHEET, cols[..2].to_vec())
+        .with_compound_key()
+        .snapshot(sheets, spreadsheet_id)
+        .await?;
+    let existing = index.rows(&key);
+    let mut next_row = index.next_row();
+
+    // The leading apostrophe of a literal counts toward the cell limit.
+    let parts = split_chars(&json, MAX_CELL_CHARS - 1);
+    let updated = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    let mut updates = Vec::new();
+    let mut part_rows = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let row = existing.get(i).copied().unwrap_or_else(|| {
+            next_row += 1;
+            next_row - 1
+        });
+        part_rows.push(row);
+        let values = [
+            literal(&repo),
+            literal(&data.commit_sha),
+            literal(&format!("{}/{}", i + 1, parts.len())),
+            literal(&updated),
+            literal(part),
+        ];
+        let cells: Vec<(&str, String)> = cols.iter().map(String::as_str).zip(values).collect();
+        updates.extend(cell_updates(DETAILS_SHEET, row, &cells)?);
+    }
+    // A previous scan of the commit that needed more parts.
+    for row in existing.iter().skip(parts.len()) {
+        let cells: Vec<(&str, String)> = cols.iter().map(|c| (c.as_str(), String::new())).collect();
+        updates.extend(cell_updates(DETAILS_SHEET, *row, &cells)?);
+    }
+    sheets.write_entered(spreadsheet_id, updates).await?;
+
+    let details_url = sheets
+        .sheet_link(spreadsheet_id, DETAILS_SHEET, part_rows[0])
+        .await?;
+    println!(
+        "📄 Result JSON for {} is {} chars; full JSON in '{}' row(s) {:?}.",
+        repo, json_chars, DETAILS_SHEET, part_rows
+    );
+    let summary = ResultSummary {
+        details_sheet: DETAILS_SHEET,
+        details_rows: part_rows,
+        details_url: details_url.clone(),
+        full_json_chars: json_chars,
+        owner: &data.owner,
+        repo_name: &data.repo_name,
+        commit_sha: &data.commit_sha,
+        snapshot_url: &data.snapshot_url,
+        repo_status: data.repo_status,
+        keyword_matches: &data.keyword_matches,
+        keyword_counts: data
+            .keyword_counts
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.count))
+            .collect(),
+        files_processed: &data.files_processed,
+        integration_type: &data.integration_type,
+        integration_features: &data.integration_features,
+        depth_score: data.depth_score,
+        team_size: data.team_size,
+    };
+    let summary = serde_json::to_string(&summary)?;
+    Ok(Some(match details_url {
+        Some(url) => hyperlink(&url, &summary),
+        None => literal(&summary),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet_store::MemorySheetStore;
+
+    /// A result whose JSON is exactly `chars` characters long.
+    fn result_of(chars: usize) -> GitHubUpdateData {
+        let mut data = GitHubUpdateData {
+            owner: "team".to_string(),
+            repo_name: "game".to_string(),
+            commit_sha: "abc123".to_string(),
+            ..Default::default()
+        };
+        let base = serde_json::to_string(&data).unwrap().len();
+        data.snapshot_url = "x".repeat(chars - base);
+        data
+    }
+
+    #[tokio::test]
+    async fn keeps_json_that_fits_with_its_apostrophe_in_the_cell() {
+        let sheets = MemorySheetStore::new();
+        let fits = overflow_result(&sheets, "id", &result_of(MAX_CELL_CHARS - 1))
+            .await
+            .unwrap();
+        assert_eq!(fits, None);
+        assert!(sheets.tab(DETAILS_SHEET).is_none());
+
+        let full = overflow_result(&sheets, "id", &result_of(MAX_CELL_CHARS))
+            .await
+            .unwrap();
+        assert!(full.unwrap().contains("\"details_rows\":[2,3]"));
+    }
+
+    #[tokio::test]
+    async fn rescanning_a_commit_reuses_its_rows_and_clears_extra_parts() {
+        let sheets = MemorySheetStore::new();
+        sheets.add_tab(
+            DETAILS_SHEET,
+            vec![
+                vec!["Repo".into(), "Commit SHA".into()],
+                vec!["other/repo".into(), "def456".into()],
+            ],
+        );
+        overflow_result(&sheets, "id", &result_of(2 * MAX_CELL_CHARS))
+            .await
+            .unwrap();
+        let rows = sheets.tab(DETAILS_SHEET).unwrap();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[4][..3], ["team/game", "abc123", "3/3"]);
+
+        let summary = overflow_result(&sheets, "id", &result_of(MAX_CELL_CHARS + 10))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(summary.contains("\"details_rows\":[3,4]"));
+        let rows = sheets.tab(DETAILS_SHEET).unwrap();
+        assert_eq!(rows[1][..2], ["other/repo", "def456"]);
+        assert_eq!(rows[3][..3], ["team/game", "abc123", "2/2"]);
+        assert!(rows[4].iter().all(String::is_empty));
+    }
+
+    #[tokio::test]
+    async fn parts_of_another_repo_at_the_same_commit_are_left_alone() {
+        let sheets = MemorySheetStore::new();
+        sheets.add_tab(
+            DETAILS_SHEET,
+            vec![
+                vec!["Repo".into(), "Commit SHA".into()],
+                vec!["fork/game".into(), "abc123".into()],
+            ],
+        );
+        let summary = overflow_result(&sheets, "id", &result_of(MAX_CELL_CHARS))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(summary.contains("\"details_rows\":[3,4]"));
+        let rows = sheets.tab(DETAILS_SHEET).unwrap();
+        assert_eq!(rows[1][..2], ["fork/game", "abc123"]);
+        assert_eq!(rows[2][..3], ["team/game", "abc123", "1/2"]);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::a1::A1Range;
+use crate::sheet_store::{MemorySheetStore, SheetStore};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use calamine::{open_workbook, Reader, Xlsx};
+use google_sheets4::api::ValueRange;
+use rust_xlsxwriter::Workbook;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FileFormat {
+    /// A directory with one `<tab>.csv` per tab.
+    Csv,
+    /// One workbook, one worksheet per tab.
+    Xlsx,
+}
+
+/// Sheets kept in local CSV or XLSX files, for partners who send a
+/// spreadsheet file instead of sharing a Google Sheet. The whole file is
+/// loaded up front and saved after every write, so the sheet helpers
+/// behave exactly as they do against Google (header resolution, duplicate
+/// header merging). As on Google, only `ensure_sheet` creates tabs.
+pub struct FileSheetStore {
+    path: PathBuf,
+    /// Where writes are saved: `path` for CSV, a sibling workbook for XLSX
+    /// (see `results_path`).
+    output: PathBuf,
+    format: FileFormat,
+    memory: MemorySheetStore,
+    /// Tab order, for writing the workbook back.
+    tabs: Mutex<Vec<String>>,
+}
+
+impl FileSheetStore {
+    /// Opens an `.xlsx` workbook, or a directory of CSV files. A path to a
+    /// single `.csv` file opens its directory, with the file as the tab
+    /// named after its stem. Files that don't exist yet are created on the
+    /// first write.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path: PathBuf = path.into();
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let (format, path) = match extension.as_str() {
+            "xlsx" | "xlsm" => (FileFormat::Xlsx, path),
+            "csv" => (
+                FileFormat::Csv,
+                path.parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."))
+                    .to_path_buf(),
+            ),
+            "" => (FileFormat::Csv, path),
+            other => bail!("unsupported sheet file type '.{}'", other),
+        };
+
+        let output = match format {
+            FileFormat::Csv => path.clone(),
+            FileFormat::Xlsx => results_path(&path),
+        };
+        let store = Self {
+            path,
+            output,
+            format,
+            memory: MemorySheetStore::without_grid_limit(),
+            tabs: Mutex::new(Vec::new()),
+        };
+        let loaded = match format {
+            FileFormat::Csv => store.load_csv(),
+            FileFormat::Xlsx => store.load_xlsx(),
+        };
+        loaded.with_context(|| format!("failed to read {}", store.path.display()))?;
+        Ok(store)
+    }
+
+    fn add_tab(&self, name: &str, rows: Vec<Vec<String>>) {
+        self.memory.add_tab(name, rows);
+        let mut tabs = self.tabs.lock().unwrap();
+        if !tabs.iter().any(|t| t == name) {
+            tabs.push(name.to_string());
+        }
+    }
+
+    /// Creates `name` empty if it isn't loaded yet.
+    fn ensure_tab(&self, name: &str) {
+        if self.memory.tab(name).is_none() {
+            self.add_tab(name, Vec::new());
+        }
+    }
+
+    fn load_csv(&self) -> Result<()> {
+        if !self.path.is_dir() {
+            return Ok(());
+        }
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(&self.path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv")))
+            .collect();
+        entries.sort();
+        for file in entries {
+            let Some(name) = file.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+                continue;
+            };
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_path(&file)?;
+            let rows = reader
+                .records()
+                .map(|r| Ok(r?.iter().map(|c| c.to_string()).collect()))
+                .collect::<Result<Vec<Vec<String>>>>()
+                .with_context(|| format!("failed to parse {}", file.display()))?;
+            self.add_tab(&name, rows);
+        }
+        Ok(())
+    }
+
+    fn load_xlsx(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        let mut workbook: Xlsx<_> = open_workbook(&self.path)?;
+        for name in workbook.sheet_names() {
+            let range = workbook.worksheet_range(&name)?;
+            // The used range may start below or right of A1.
+            let (first_row, first_col) = range.start().unwrap_or((0, 0));
+            let mut rows: Vec<Vec<String>> = vec![Vec::new(); first_row as usize];
+            for row in range.rows() {
+                let mut cells = vec![String::new(); first_col as usize];
+                cells.extend(row.iter().map(|c| c.to_string()));
+                rows.push(cells);
+            }
+            self.add_tab(&name, rows);
+        }
+        Ok(())
+    }
+
+    /// Writes the given tabs (CSV) or the whole workbook (XLSX) to a
+    /// temporary file and moves it into place at `output`.
+    fn save(&self, changed: &[String]) -> Result<()> {
+        match self.format {
+            FileFormat::Csv => {
+                std::fs::create_dir_all(&self.output)?;
+                for name in changed {
+                    let file = self.output.join(format!("{}.csv", name));
+                    let partial = file.with_extension("csv.partial");
+                    let mut writer = csv::WriterBuilder::new()
+                        .flexible(true)
+                        .from_path(&partial)?;
+                    for row in self.memory.tab(name).unwrap_or_default() {
+                        // An empty record would be skipped; keep blank rows.
+                        if row.is_empty() {
+                            writer.write_record([""])?;
+                        } else {
+                            writer.write_record(&row)?;
+                        }
+                    }
+                    writer.flush()?;
+                    drop(writer);
+                    std::fs::rename(&partial, &file)?;
+                }
+            }
+            FileFormat::Xlsx => {
+                let mut workbook = Workbook::new();
+                for name in self.tabs.lock().unwrap().iter() {
+                    let worksheet = workbook.add_worksheet();
+                    worksheet.set_name(name)?;
+                    for (r, row) in self.memory.tab(name).unwrap_or_default().iter().enumerate() {
+                        for (c, value) in row.iter().enumerate() {
+                            let (r, c) = (r as u32, c as u16);
+                            match as_number(value) {
+                                Some(n) => worksheet.write_number(r, c, n)?,
+                                None if value.is_empty() => continue,
+                                None => worksheet.write_string(r, c, value)?,
+                            };
+                        }
+                    }
+                }
+                let partial = self.output.with_extension("xlsx.partial");
+                workbook.save(&partial)?;
+                std::fs::rename(&partial, &self.output)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `teams.results.xlsx` beside `teams.xlsx`: a workbook is read but never
+/// overwritten, since saving drops its formulas and formatting.
+fn results_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.results.xlsx", stem))
+}
+
+/// `value` as a number when it reads back exactly as written, so counts
+/// stay numeric while ids such as `007` or `1e5` stay text.
+fn as_number(value: &str) -> Option<f64> {
+    let n: f64 = value.parse().ok()?;
+    (n.is_finite() && n.to_string() == value).then_some(n)
+}
+
+#[async_trait]
+impl SheetStore for FileSheetStore {
+    async fn read_values(&self, spreadsheet_id: &str, range: &str) -> Result<Vec<Vec<String>>> {
+        self.memory.read_values(spreadsheet_id, range).await
+    }
+
+    async fn write_values(&self, spreadsheet_id: &str, updates: Vec<ValueRange>) -> Result<()> {
+        let mut changed: Vec<String> = Vec::new();
+        for update in &updates {
+            let range: A1Range = update.range.as_deref().unwrap_or_default().parse()?;
+            let sheet = range.sheet_name()?.to_string();
+            if !changed.contains(&sheet) {
+                changed.push(sheet);
+            }
+        }
+        self.memory.write_values(spreadsheet_id, updates).await?;
+        self.save(&changed)
+            .with_context(|| format!("failed to write {}", self.output.display()))
+    }
+
+    async fn expand_grid(
+        &self,
+        _spreadsheet_id: &str,
+        _sheet_name: &str,
+        _needed_cols: usize,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn ensure_sheet(&self, _spreadsheet_id: &str, sheet_name: &str) -> Result<()> {
+        self.ensure_tab(sheet_name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell_format::{literal, number};
+    use crate::sheets::write_entered_cells;
+    use calamine::Data;
+
+    #[tokio::test]
+    async fn saves_xlsx_results_beside_the_workbook_with_numbers_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("teams.xlsx");
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet().set_name("Frontier").unwrap();
+        sheet.write_string(0, 0, "GitHub Repo").unwrap();
+        sheet
+            .write_string(1, 0, "https://github.com/team/game")
+            .unwrap();
+        workbook.save(&input).unwrap();
+        let before = std::fs::read(&input).unwrap();
+
+        let store = FileSheetStore::open(&input).unwrap();
+        write_entered_cells(
+            &store,
+            "",
+            "Frontier",
+            2,
+            &[("B", number("12")), ("C", literal("007"))],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&input).unwrap(), before);
+        let mut results: Xlsx<_> = open_workbook(dir.path().join("teams.results.xlsx")).unwrap();
+        let range = results.worksheet_range("Frontier").unwrap();
+        assert_eq!(range.get_value((1, 1)), Some(&Data::Float(12.0)));
+        assert_eq!(range.get_value((1, 2)), Some(&Data::String("007".into())));
+        assert_eq!(
+            range.get_value((1, 0)),
+            Some(&Data::String("https://github.com/team/game".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn only_ensure_sheet_creates_tabs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSheetStore::open(dir.path()).unwrap();
+
+        assert!(store.read_values("", "'Serach'!A1:C").await.is_err());
+        assert!(
+            write_entered_cells(&store, "", "Serach", 1, &[("A", literal("x"))])
+                .await
+                .is_err()
+        );
+        assert!(!dir.path().join("Serach.csv").exists());
+
+        store.ensure_sheet("", "Search").await.unwrap();
+        assert!(store
+            .read_values("", "'Search'!A1:C")
+            .await
+            .unwrap()
+            .is_empty());
+        write_entered_cells(&store, "", "Search", 1, &[("A", literal("Repo URL"))])
+            .await
+            .unwrap();
+        let saved = std::fs::read_to_string(dir.path().join("Search.csv")).unwrap();
+        assert_eq!(saved.trim(), "Repo URL");
+    }
+}
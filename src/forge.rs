@@ -0,0 +1,1223 @@
+use crate::github::{parse_github_url_detailed, with_scheme, GitHubForge, GitHubUrlType, UrlFix};
+use crate::local::LocalForge;
+use crate::types::{Contributor, RepoStatus};
+use async_trait::async_trait;
+use reqwest::{header::USER_AGENT, Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+/// Which code host a repo lives on. Codeberg and self-hosted Gitea/Forgejo
+/// instances share the Gitea API; `Local` is a checkout or git repo on disk.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gitea,
+    Local,
+}
+
+impl ForgeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "github",
+            ForgeKind::GitLab => "gitlab",
+            ForgeKind::Bitbucket => "bitbucket",
+            ForgeKind::Gitea => "gitea",
+            ForgeKind::Local => "local",
+        }
+    }
+}
+
+/// A repo on some forge. GitLab owners may span several path segments
+/// (`group/subgroup`); a local repo's owner is its parent directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForgeRepo {
+    pub kind: ForgeKind,
+    pub host: String,
+    pub owner: String,
+    pub repo_name: String,
+    /// Ref pinned by a local path spec (`path#v1.0`); otherwise the
+    /// default branch is scanned.
+    pub git_ref: Option<String>,
+}
+
+impl ForgeRepo {
+    pub fn web_url(&self) -> String {
+        let url = match self.kind {
+            ForgeKind::Local => format!("file://{}/{}", self.owner, self.repo_name),
+            _ => format!("https://{}/{}/{}", self.host, self.owner, self.repo_name),
+        };
+        match &self.git_ref {
+            Some(git_ref) => format!("{}#{}", url, git_ref),
+            None => url,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ForgeUrlType {
+    /// A GitHub user or organization. Other forges' account pages are not
+    /// expanded into repos and classify as `Invalid`.
+    User(String),
+    Repo(ForgeRepo),
+    /// A GitHub gist; recognised so it can be reported, but not scanned.
+    Gist {
+        owner: Option<String>,
+        id: String,
+    },
+    Invalid,
+}
+
+/// What a forge reports about a repo under its current name.
+#[derive(Debug, Clone, Default)]
+pub struct ForgeRepoInfo {
+    pub default_branch: String,
+    /// `owner/repo` as the forge names it now; differs from the submitted
+    /// URL after a rename or transfer.
+    pub full_name: String,
+    pub web_url: String,
+    pub archived: bool,
+    /// The forge says the repo has no commits yet.
+    pub empty: bool,
+}
+
+/// Everything the scanner needs from a code host: the repo's current name
+/// and default branch, a recursive file listing, raw file contents, and the
+/// latest commit.
+#[async_trait]
+pub trait Forge: Send + Sync {
+    fn kind(&self) -> ForgeKind;
+
+    /// Browser link to `path` at `git_ref`, used for `KeywordResult::files`.
+    fn file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String;
+
+    /// Browser link to the whole tree at `commit_sha`.
+    fn snapshot_url(&self, owner: &str, repo: &str, commit_sha: &str) -> String;
+
+    /// `git clone` URL, used to keep a mirror in the git cache.
+    fn clone_url(&self, owner: &str, repo: &str) -> String;
+
+    /// HTTP basic-auth user and password for `clone_url`, when a token is set.
+    fn clone_credentials(&self) -> Option<(String, String)> {
+        None
+    }
+
+    /// Fails with the status the forge's answer implies, if any.
+    async fn repo_info(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError>;
+
+    /// Paths of every file (blob) in the tree at `git_ref`.
+    async fn list_tree(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>>;
+
+    /// One file's contents, decoded as (lossy) UTF-8.
+    async fn fetch_file(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String>;
+
+    /// Latest commit on `git_ref` as `(sha, date, email, name)`.
+    async fn last_commit(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)>;
+
+    /// Whether the forge says `git_ref` has no commits because nothing was
+    /// ever pushed. Asked only after `last_commit` came back empty, so a
+    /// failed lookup isn't mistaken for an empty repo. Forges that flag
+    /// empty repos in `repo_info` keep the default.
+    async fn is_empty(&self, _client: &Client, _owner: &str, _repo: &str, _git_ref: &str) -> bool {
+        false
+    }
+
+    /// Contributor roster from the recent history of `git_ref`, where the
+    /// forge offers one.
+    async fn contributors(
+        &self,
+        _client: &Client,
+        _owner: &str,
+        _repo: &str,
+        _git_ref: &str,
+    ) -> Option<Vec<Contributor>> {
+        None
+    }
+
+    /// Adds account logins to a roster built from git history, which only
+    /// knows names and emails, merging authors whose emails share a login.
+    /// Forges without an account lookup return it unchanged.
+    async fn resolve_logins(
+        &self,
+        _client: &Client,
+        _owner: &str,
+        _repo: &str,
+        contributors: Vec<Contributor>,
+    ) -> Vec<Contributor> {
+        contributors
+    }
+}
+
+/// Hosts beyond the public ones that speak the GitLab or Gitea API, from the
+/// comma-separated `SCRAPER_GITLAB_HOSTS` / `SCRAPER_GITEA_HOSTS`.
+fn extra_hosts(var: &str) -> Vec<String> {
+    std::env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .map(|h| h.trim().trim_start_matches("www.").to_lowercase())
+        .filter(|h| !h.is_empty())
+        .collect()
+}
+
+pub fn forge_kind_for_host(host: &str) -> Option<ForgeKind> {
+    let host = host.trim_start_matches("www.").to_lowercase();
+    match host.as_str() {
+        "github.com" => Some(ForgeKind::GitHub),
+        "gitlab.com" => Some(ForgeKind::GitLab),
+        "bitbucket.org" => Some(ForgeKind::Bitbucket),
+        "codeberg.org" | "gitea.com" => Some(ForgeKind::Gitea),
+        _ if extra_hosts("SCRAPER_GITLAB_HOSTS").contains(&host) => Some(ForgeKind::GitLab),
+        _ if extra_hosts("SCRAPER_GITEA_HOSTS").contains(&host) => Some(ForgeKind::Gitea),
+        _ => None,
+    }
+}
+
+/// Parses a non-GitHub forge URL (`https://`, scheme-less or `git@host:`
+/// SSH form) into its repo. Deep links (`/-/tree/main`, `/src/main/...`)
+/// are cut back to owner/repo.
+fn classify_other_forge_url(input: &str) -> Option<(ForgeUrlType, Vec<UrlFix>)> {
+    let mut fixes = Vec::new();
+    let parsed = Url::parse(&with_scheme(input, &mut fixes)).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").to_lowercase();
+    let kind = forge_kind_for_host(&host)?;
+    if kind == ForgeKind::GitHub {
+        return None;
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        fixes.push(UrlFix::DroppedQuery);
+    }
+
+    let all: Vec<&str> = parsed.path_segments()?.filter(|s| !s.is_empty()).collect();
+    // GitLab puts every sub-page behind a `-` segment and allows nested
+    // groups; the others are always `owner/repo`.
+    let mut segments: Vec<String> = match kind {
+        ForgeKind::GitLab => all.iter().take_while(|seg| **seg != "-").collect(),
+        _ => all.iter().take(2).collect::<Vec<_>>(),
+    }
+    .into_iter()
+    .map(|seg| seg.to_string())
+    .collect();
+    if segments.len() < all.len() {
+        fixes.push(UrlFix::DeepPath);
+    }
+    if let Some(repo) = segments.last_mut() {
+        if let Some(trimmed) = repo.strip_suffix(".git") {
+            *repo = trimmed.to_string();
+            fixes.push(UrlFix::StrippedGitSuffix);
+        }
+    }
+
+    let url_type = match segments.as_slice() {
+        [] | [_] => ForgeUrlType::Invalid,
+        [owner @ .., repo_name] => ForgeUrlType::Repo(ForgeRepo {
+            kind,
+            host,
+            owner: owner.join("/"),
+            repo_name: repo_name.clone(),
+            git_ref: None,
+        }),
+    };
+    Some((url_type, fixes))
+}
+
+/// Classifies a repo URL on any supported forge and reports the rewrites
+/// applied to get there. URLs without a recognised forge host fall through
+/// to the GitHub rules, so bare `owner/repo` cells keep meaning GitHub.
+/// Local paths are never a repo here (see `local_repo`).
+pub fn classify_forge_url_detailed(url: &str) -> (ForgeUrlType, Vec<UrlFix>) {
+    if url.trim().starts_with("file:") {
+        return (ForgeUrlType::Invalid, Vec::new());
+    }
+    if let Some(classified) = classify_other_forge_url(url) {
+        return classified;
+    }
+    let (url_type, fixes) = parse_github_url_detailed(url);
+    let url_type = match url_type {
+        GitHubUrlType::User(owner) => ForgeUrlType::User(owner),
+        GitHubUrlType::Repo { owner, repo_name } => ForgeUrlType::Repo(ForgeRepo {
+            kind: ForgeKind::GitHub,
+            host: "github.com".to_string(),
+            owner,
+            repo_name,
+            git_ref: None,
+        }),
+        GitHubUrlType::Gist { owner, id } => ForgeUrlType::Gist { owner, id },
+        GitHubUrlType::Invalid => ForgeUrlType::Invalid,
+    };
+    (url_type, fixes)
+}
+
+pub fn classify_forge_url(url: &str) -> ForgeUrlType {
+    classify_forge_url_detailed(url).0
+}
+
+/// Whether a free-text token names a repo host rather than being a label
+/// (`frontend:`) or prose.
+fn looks_like_repo_url(token: &str) -> bool {
+    if token.contains("://") || token.starts_with("git@") {
+        return true;
+    }
+    let host = token.split('/').next().unwrap_or("").to_lowercase();
+    host.contains('.') && (host.contains("github") || forge_kind_for_host(&host).is_some())
+}
+
+/// Pulls every repo URL out of a free-text cell: `frontend: <url>, program:
+/// <url>`, newline-separated lists, markdown `[text](url)` links. URLs are
+/// de-duplicated by the repo they point at. A cell with no URL-looking token
+/// is returned whole, so bare `owner/repo` and username cells keep working.
+pub fn extract_repo_urls(cell: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    for token in cell.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '|')) {
+        // `[frontend](https://...)` and `<https://...>` wrap the URL itself.
+        let token = match token.find("://") {
+            Some(idx) => {
+                let start = token[..idx]
+                    .rfind(|c: char| !c.is_ascii_alphabetic())
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                &token[start..]
+            }
+            None => token,
+        };
+        let token = token
+            .trim_start_matches(['(', '<', '[', '"', '\''])
+            .trim_end_matches([')', '>', ']', '"', '\'', '.', ':']);
+        if token.is_empty() || !looks_like_repo_url(token) {
+            continue;
+        }
+        let key = match classify_forge_url(token) {
+            ForgeUrlType::Repo(repo) => repo.web_url().to_lowercase(),
+            ForgeUrlType::User(owner) => format!("user:{}", owner.to_lowercase()),
+            _ => token.to_lowercase(),
+        };
+        if !seen.contains(&key) {
+            seen.push(key);
+            urls.push(token.to_string());
+        }
+    }
+    if urls.is_empty() && !cell.trim().is_empty() {
+        urls.push(cell.trim().to_string());
+    }
+    urls
+}
+
+/// The API client for `repo`'s forge. Non-GitHub tokens are optional and read
+/// from `GITLAB_TOKEN`, `BITBUCKET_TOKEN` and `GITEA_TOKEN`.
+pub fn forge_for(repo: &ForgeRepo, github_token: &str) -> Box<dyn Forge> {
+    let token = |var: &str| std::env::var(var).ok().filter(|t| !t.trim().is_empty());
+    match repo.kind {
+        ForgeKind::GitHub => Box::new(GitHubForge::new(github_token)),
+        ForgeKind::GitLab => Box::new(GitLabForge {
+            host: repo.host.clone(),
+            token: token("GITLAB_TOKEN"),
+        }),
+        ForgeKind::Bitbucket => Box::new(BitbucketForge {
+            token: token("BITBUCKET_TOKEN"),
+        }),
+        ForgeKind::Gitea => Box::new(GiteaForge {
+            host: repo.host.clone(),
+            token: token("GITEA_TOKEN"),
+        }),
+        ForgeKind::Local => Box::new(LocalForge::open(
+            std::path::Path::new(&repo.owner).join(&repo.repo_name),
+        )),
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, for path values
+/// that go into a single URL segment (GitLab project ids and file paths).
+fn encode_segment(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Encodes each `/`-separated part of a repo path but keeps the slashes.
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(encode_segment)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn get_json(request: RequestBuilder) -> Option<Value> {
+    let resp = request
+        .header(USER_AGENT, "rust-scraper")
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    resp.json().await.ok()
+}
+
+/// Why a repo lookup failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupError {
+    /// The forge's answer implies this status for the repo.
+    Status(RepoStatus),
+    /// The forge rejected the token (401): a configuration problem that
+    /// says nothing about the repo.
+    Unauthorized,
+    /// The failure says nothing about the repo (network error, 5xx, rate
+    /// limit).
+    Unknown,
+}
+
+/// Whether the forge says the request was throttled: GitHub answers a
+/// spent rate limit with 403, `x-ratelimit-remaining: 0` and a message
+/// saying so, and a secondary limit with `retry-after`.
+fn is_rate_limited(resp: &Response) -> bool {
+    let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok());
+    header("x-ratelimit-remaining").is_some_and(|r| r.trim() == "0")
+        || header("retry-after").is_some()
+}
+
+/// Maps a failed repo lookup to the status it implies, if any.
+pub(crate) async fn failed_repo_status(resp: Response) -> LookupError {
+    match resp.status().as_u16() {
+        404 | 410 => LookupError::Status(RepoStatus::NotFound),
+        409 => LookupError::Status(RepoStatus::Empty),
+        451 => LookupError::Status(RepoStatus::Blocked),
+        401 => LookupError::Unauthorized,
+        403 if is_rate_limited(&resp) => LookupError::Unknown,
+        403 => {
+            // GitHub answers a repo disabled by a takedown with 403 and a
+            // `block` object rather than 451.
+            let body: Value = resp.json().await.unwrap_or(Value::Null);
+            let message = body["message"].as_str().unwrap_or_default().to_lowercase();
+            if body.get("block").is_some() {
+                LookupError::Status(RepoStatus::Blocked)
+            } else if message.contains("rate limit") {
+                LookupError::Unknown
+            } else {
+                LookupError::Status(RepoStatus::Inaccessible)
+            }
+        }
+        _ => LookupError::Unknown,
+    }
+}
+
+/// Like `get_json`, but reports why a repo lookup failed.
+async fn get_repo_json(request: RequestBuilder) -> Result<Value, LookupError> {
+    let resp = request
+        .header(USER_AGENT, "rust-scraper")
+        .send()
+        .await
+        .map_err(|_| LookupError::Unknown)?;
+    if !resp.status().is_success() {
+        return Err(failed_repo_status(resp).await);
+    }
+    resp.json().await.map_err(|_| LookupError::Unknown)
+}
+
+async fn get_text(request: RequestBuilder) -> Option<String> {
+    let resp = request
+        .header(USER_AGENT, "rust-scraper")
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    let bytes = resp.bytes().await.ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Pages fetched when listing a tree, so a huge monorepo can't stall a run.
+const MAX_TREE_PAGES: usize = 50;
+
+pub struct GitLabForge {
+    host: String,
+    token: Option<String>,
+}
+
+impl GitLabForge {
+    fn api(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!(
+            "https://{}/api/v4/projects/{}{}",
+            self.host,
+            encode_segment(&format!("{}/{}", owner, repo)),
+            rest
+        )
+    }
+
+    fn get(&self, client: &Client, url: &str) -> RequestBuilder {
+        let request = client.get(url);
+        match &self.token {
+            Some(token) => request.header("PRIVATE-TOKEN", token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl Forge for GitLabForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitLab
+    }
+
+    fn clone_url(&self, owner: &str, repo: &str) -> String {
+        format!("https://{}/{}/{}.git", self.host, owner, repo)
+    }
+
+    fn clone_credentials(&self) -> Option<(String, String)> {
+        self.token.clone().map(|t| ("oauth2".to_string(), t))
+    }
+
+    fn file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String {
+        format!(
+            "https://{}/{}/{}/-/blob/{}/{}",
+            self.host, owner, repo, git_ref, path
+        )
+    }
+
+    fn snapshot_url(&self, owner: &str, repo: &str, commit_sha: &str) -> String {
+        format!(
+            "https://{}/{}/{}/-/tree/{}",
+            self.host, owner, repo, commit_sha
+        )
+    }
+
+    async fn repo_info(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError> {
+        let project = get_repo_json(self.get(client, &self.api(owner, repo, ""))).await?;
+        Ok(ForgeRepoInfo {
+            default_branch: project["default_branch"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            full_name: project["path_with_namespace"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            web_url: project["web_url"].as_str().unwrap_or_default().to_string(),
+            archived: project["archived"].as_bool().unwrap_or(false),
+            empty: project["empty_repo"].as_bool().unwrap_or(false),
+        })
+    }
+
+    async fn list_tree(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>> {
+        let mut paths = Vec::new();
+        for page in 1..=MAX_TREE_PAGES {
+            let url = self.api(
+                owner,
+                repo,
+                &format!(
+                    "/repository/tree?recursive=true&per_page=100&page={}&ref={}",
+                    page,
+                    encode_segment(git_ref)
+                ),
+            );
+            let items = get_json(self.get(client, &url)).await?;
+            let items = items.as_array()?;
+            paths.extend(
+                items
+                    .iter()
+                    .filter(|i| i["type"] == "blob")
+                    .filter_map(|i| i["path"].as_str().map(|p| p.to_string())),
+            );
+            if items.len() < 100 {
+                break;
+            }
+        }
+        Some(paths)
+    }
+
+    async fn fetch_file(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String> {
+        let url = self.api(
+            owner,
+            repo,
+            &format!(
+                "/repository/files/{}/raw?ref={}",
+                encode_segment(path),
+                encode_segment(git_ref)
+            ),
+        );
+        get_text(self.get(client, &url)).await
+    }
+
+    async fn last_commit(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)> {
+        let url = self.api(
+            owner,
+            repo,
+            &format!("/repository/commits/{}", encode_segment(git_ref)),
+        );
+        let commit = get_json(self.get(client, &url)).await?;
+        Some((
+            commit["id"].as_str()?.to_string(),
+            commit["authored_date"].as_str()?.to_string(),
+            commit["author_email"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            commit["author_name"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+        ))
+    }
+}
+
+pub struct BitbucketForge {
+    token: Option<String>,
+}
+
+impl BitbucketForge {
+    fn api(owner: &str, repo: &str, rest: &str) -> String {
+        format!(
+            "https://api.bitbucket.org/2.0/repositories/{}/{}{}",
+            owner, repo, rest
+        )
+    }
+
+    fn get(&self, client: &Client, url: &str) -> RequestBuilder {
+        let request = client.get(url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// Splits a Bitbucket `author.raw` value (`Jane Doe <jane@example.com>`).
+fn split_raw_author(raw: &str) -> (String, String) {
+    match raw.split_once('<') {
+        Some((name, email)) => (
+            name.trim().to_string(),
+            email.trim_end_matches('>').trim().to_string(),
+        ),
+        None => (raw.trim().to_string(), "unknown".to_string()),
+    }
+}
+
+#[async_trait]
+impl Forge for BitbucketForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Bitbucket
+    }
+
+    fn clone_url(&self, owner: &str, repo: &str) -> String {
+        format!("https://bitbucket.org/{}/{}.git", owner, repo)
+    }
+
+    fn clone_credentials(&self) -> Option<(String, String)> {
+        self.token.clone().map(|t| ("x-token-auth".to_string(), t))
+    }
+
+    fn file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String {
+        format!(
+            "https://bitbucket.org/{}/{}/src/{}/{}",
+            owner, repo, git_ref, path
+        )
+    }
+
+    fn snapshot_url(&self, owner: &str, repo: &str, commit_sha: &str) -> String {
+        format!(
+            "https://bitbucket.org/{}/{}/src/{}",
+            owner, repo, commit_sha
+        )
+    }
+
+    async fn repo_info(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError> {
+        let repository = get_repo_json(self.get(client, &Self::api(owner, repo, ""))).await?;
+        // Bitbucket has no archiving, and leaves `mainbranch` unset until
+        // the first push.
+        let default_branch = repository["mainbranch"]["name"].as_str();
+        Ok(ForgeRepoInfo {
+            default_branch: default_branch.unwrap_or_default().to_string(),
+            full_name: repository["full_name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            web_url: repository["links"]["html"]["href"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            archived: false,
+            empty: default_branch.is_none(),
+        })
+    }
+
+    async fn list_tree(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>> {
+        let mut paths = Vec::new();
+        let mut next = Some(Self::api(
+            owner,
+            repo,
+            &format!("/src/{}/?max_depth=50&pagelen=100", encode_segment(git_ref)),
+        ));
+        let mut pages = 0;
+        while let Some(url) = next.take() {
+            let page = get_json(self.get(client, &url)).await?;
+            paths.extend(
+                page["values"]
+                    .as_array()?
+                    .iter()
+                    .filter(|i| i["type"] == "commit_file")
+                    .filter_map(|i| i["path"].as_str().map(|p| p.to_string())),
+            );
+            pages += 1;
+            if pages < MAX_TREE_PAGES {
+                next = page["next"].as_str().map(|n| n.to_string());
+            }
+        }
+        Some(paths)
+    }
+
+    async fn fetch_file(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String> {
+        let url = Self::api(
+            owner,
+            repo,
+            &format!("/src/{}/{}", encode_segment(git_ref), encode_path(path)),
+        );
+        get_text(self.get(client, &url)).await
+    }
+
+    async fn last_commit(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)> {
+        let url = Self::api(owner, repo, &format!("/commit/{}", encode_segment(git_ref)));
+        let commit = get_json(self.get(client, &url)).await?;
+        let (name, email) = split_raw_author(commit["author"]["raw"].as_str().unwrap_or(""));
+        Some((
+            commit["hash"].as_str()?.to_string(),
+            commit["date"].as_str()?.to_string(),
+            email,
+            name,
+        ))
+    }
+}
+
+pub struct GiteaForge {
+    host: String,
+    token: Option<String>,
+}
+
+impl GiteaForge {
+    fn api(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!(
+            "https://{}/api/v1/repos/{}/{}{}",
+            self.host, owner, repo, rest
+        )
+    }
+
+    fn get(&self, client: &Client, url: &str) -> RequestBuilder {
+        let request = client.get(url);
+        match &self.token {
+            Some(token) => request.header("Authorization", format!("token {}", token)),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl Forge for GiteaForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Gitea
+    }
+
+    fn clone_url(&self, owner: &str, repo: &str) -> String {
+        format!("https://{}/{}/{}.git", self.host, owner, repo)
+    }
+
+    fn file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String {
+        format!(
+            "https://{}/{}/{}/src/branch/{}/{}",
+            self.host, owner, repo, git_ref, path
+        )
+    }
+
+    fn snapshot_url(&self, owner: &str, repo: &str, commit_sha: &str) -> String {
+        format!(
+            "https://{}/{}/{}/src/commit/{}",
+            self.host, owner, repo, commit_sha
+        )
+    }
+
+    async fn repo_info(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError> {
+        let repository = get_repo_json(self.get(client, &self.api(owner, repo, ""))).await?;
+        Ok(ForgeRepoInfo {
+            default_branch: repository["default_branch"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            full_name: repository["full_name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            web_url: repository["html_url"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            archived: repository["archived"].as_bool().unwrap_or(false),
+            empty: repository["empty"].as_bool().unwrap_or(false),
+        })
+    }
+
+    async fn list_tree(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>> {
+        let mut paths = Vec::new();
+        for page in 1..=MAX_TREE_PAGES {
+            let url = self.api(
+                owner,
+                repo,
+                &format!(
+                    "/git/trees/{}?recursive=true&per_page=1000&page={}",
+                    encode_segment(git_ref),
+                    page
+                ),
+            );
+            let tree = get_json(self.get(client, &url)).await?;
+            paths.extend(
+                tree["tree"]
+                    .as_array()?
+                    .iter()
+                    .filter(|i| i["type"] == "blob")
+                    .filter_map(|i| i["path"].as_str().map(|p| p.to_string())),
+            );
+            if tree["truncated"].as_bool() != Some(true) {
+                break;
+            }
+        }
+        Some(paths)
+    }
+
+    async fn fetch_file(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String> {
+        let url = self.api(
+            owner,
+            repo,
+            &format!("/raw/{}?ref={}", encode_path(path), encode_segment(git_ref)),
+        );
+        get_text(self.get(client, &url)).await
+    }
+
+    async fn last_commit(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)> {
+        let url = self.api(
+            owner,
+            repo,
+            &format!(
+                "/commits?sha={}&limit=1&stat=false",
+                encode_segment(git_ref)
+            ),
+        );
+        let commits = get_json(self.get(client, &url)).await?;
+        let commit = commits.as_array()?.first()?;
+        let author = &commit["commit"]["author"];
+        Some((
+            commit["sha"].as_str()?.to_string(),
+            author["date"].as_str()?.to_string(),
+            author["email"].as_str().unwrap_or("unknown").to_string(),
+            author["name"].as_str().unwrap_or("unknown").to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(kind: ForgeKind, host: &str, owner: &str, repo_name: &str) -> ForgeUrlType {
+        ForgeUrlType::Repo(ForgeRepo {
+            kind,
+            host: host.to_string(),
+            owner: owner.to_string(),
+            repo_name: repo_name.to_string(),
+            git_ref: None,
+        })
+    }
+
+    #[test]
+    fn classifies_urls_on_every_forge() {
+        use ForgeKind::*;
+        use UrlFix::*;
+        let cases = [
+            (
+                "https://gitlab.com/group/sub/game",
+                repo(GitLab, "gitlab.com", "group/sub", "game"),
+                vec![],
+            ),
+            (
+                "https://gitlab.com/group/sub/game/-/blob/main/src/lib.rs",
+                repo(GitLab, "gitlab.com", "group/sub", "game"),
+                vec![DeepPath],
+            ),
+            (
+                "gitlab.com/team/game/-/tree/dev",
+                repo(GitLab, "gitlab.com", "team", "game"),
+                vec![AddedScheme, DeepPath],
+            ),
+            (
+                "git@gitlab.com:team/game.git",
+                repo(GitLab, "gitlab.com", "team", "game"),
+                vec![ConvertedSsh, StrippedGitSuffix],
+            ),
+            (
+                "https://bitbucket.org/team/game/src/main/README.md?at=main",
+                repo(Bitbucket, "bitbucket.org", "team", "game"),
+                vec![DroppedQuery, DeepPath],
+            ),
+            (
+                "https://www.codeberg.org/team/game.git",
+                repo(Gitea, "codeberg.org", "team", "game"),
+                vec![StrippedGitSuffix],
+            ),
+            (
+                "http://gitea.com/team/game/src/branch/main",
+                repo(Gitea, "gitea.com", "team", "game"),
+                vec![UpgradedHttp, DeepPath],
+            ),
+            (
+                "https://github.com/team/game.git",
+                repo(GitHub, "github.com", "team", "game"),
+                vec![StrippedGitSuffix],
+            ),
+            (
+                "https://github.com/team",
+                ForgeUrlType::User("team".to_string()),
+                vec![],
+            ),
+            // Other forges' account pages aren't expanded into repos.
+            ("https://gitlab.com/team", ForgeUrlType::Invalid, vec![]),
+            ("file:///work/team/game", ForgeUrlType::Invalid, vec![]),
+            (
+                "https://example.com/team/game",
+                ForgeUrlType::Invalid,
+                vec![],
+            ),
+        ];
+        for (url, expected, fixes) in cases {
+            assert_eq!(
+                classify_forge_url_detailed(url),
+                (expected, fixes),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn custom_hosts_speak_their_configured_api() {
+        std::env::set_var(
+            "SCRAPER_GITLAB_HOSTS",
+            "git.example.org, www.Lab.Example.com",
+        );
+        std::env::set_var("SCRAPER_GITEA_HOSTS", "forge.example.net");
+        assert_eq!(
+            classify_forge_url("https://git.example.org/a/b/c"),
+            repo(ForgeKind::GitLab, "git.example.org", "a/b", "c")
+        );
+        assert_eq!(
+            forge_kind_for_host("lab.example.com"),
+            Some(ForgeKind::GitLab)
+        );
+        assert_eq!(
+            classify_forge_url("forge.example.net/team/game/src/branch/main"),
+            repo(ForgeKind::Gitea, "forge.example.net", "team", "game")
+        );
+        assert_eq!(forge_kind_for_host("unknown.example.net"), None);
+    }
+
+    #[test]
+    fn splits_bitbucket_raw_authors() {
+        assert_eq!(
+            split_raw_author("Jane Doe <jane@example.com>"),
+            ("Jane Doe".to_string(), "jane@example.com".to_string())
+        );
+        assert_eq!(
+            split_raw_author(" jane "),
+            ("jane".to_string(), "unknown".to_string())
+        );
+        assert_eq!(
+            split_raw_author("<jane@example.com>"),
+            (String::new(), "jane@example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn encodes_api_path_values() {
+        assert_eq!(encode_segment("group/sub game"), "group%2Fsub%20game");
+        assert_eq!(encode_path("src/my file.rs"), "src/my%20file.rs");
+        assert_eq!(
+            GitLabForge {
+                host: "gitlab.com".to_string(),
+                token: None,
+            }
+            .api("group/sub", "game", "/repository/tree"),
+            "https://gitlab.com/api/v4/projects/group%2Fsub%2Fgame/repository/tree"
+        );
+    }
+
+    #[test]
+    fn gitlab_links() {
+        let forge = GitLabForge {
+            host: "gitlab.com".to_string(),
+            token: Some("t".to_string()),
+        };
+        assert_eq!(
+            forge.file_url("group/sub", "game", "main", "src/lib.rs"),
+            "https://gitlab.com/group/sub/game/-/blob/main/src/lib.rs"
+        );
+        assert_eq!(
+            forge.snapshot_url("group/sub", "game", "abc123"),
+            "https://gitlab.com/group/sub/game/-/tree/abc123"
+        );
+        assert_eq!(
+            forge.clone_url("group/sub", "game"),
+            "https://gitlab.com/group/sub/game.git"
+        );
+        assert_eq!(
+            forge.clone_credentials(),
+            Some(("oauth2".to_string(), "t".to_string()))
+        );
+    }
+
+    #[test]
+    fn bitbucket_links() {
+        let forge = BitbucketForge { token: None };
+        assert_eq!(
+            forge.file_url("team", "game", "main", "src/lib.rs"),
+            "https://bitbucket.org/team/game/src/main/src/lib.rs"
+        );
+        assert_eq!(
+            forge.snapshot_url("team", "game", "abc123"),
+            "https://bitbucket.org/team/game/src/abc123"
+        );
+        assert_eq!(
+            forge.clone_url("team", "game"),
+            "https://bitbucket.org/team/game.git"
+        );
+        assert_eq!(forge.clone_credentials(), None);
+    }
+
+    #[test]
+    fn gitea_links() {
+        let forge = GiteaForge {
+            host: "codeberg.org".to_string(),
+            token: None,
+        };
+        assert_eq!(
+            forge.file_url("team", "game", "main", "src/lib.rs"),
+            "https://codeberg.org/team/game/src/branch/main/src/lib.rs"
+        );
+        assert_eq!(
+            forge.snapshot_url("team", "game", "abc123"),
+            "https://codeberg.org/team/game/src/commit/abc123"
+        );
+        assert_eq!(
+            forge.clone_url("team", "game"),
+            "https://codeberg.org/team/game.git"
+        );
+    }
+
+    #[test]
+    fn splits_cells_on_commas_spaces_and_newlines() {
+        assert_eq!(
+            extract_repo_urls(
+                "frontend: https://github.com/team/web, program: github.com/team/program\n\
+                 https://gitlab.com/team/sdk; https://codeberg.org/team/docs"
+            ),
+            [
+                "https://github.com/team/web",
+                "github.com/team/program",
+                "https://gitlab.com/team/sdk",
+                "https://codeberg.org/team/docs",
+            ]
+        );
+    }
+
+    #[test]
+    fn unwraps_links_and_trailing_punctuation() {
+        assert_eq!(
+            extract_repo_urls(
+                "[web](https://github.com/team/web) and <https://github.com/team/api>."
+            ),
+            ["https://github.com/team/web", "https://github.com/team/api"]
+        );
+    }
+
+    #[test]
+    fn keeps_one_url_per_repo() {
+        assert_eq!(
+            extract_repo_urls(
+                "https://github.com/Team/Game git@github.com:team/game.git \
+                 https://github.com/team/game/tree/main"
+            ),
+            ["https://github.com/Team/Game"]
+        );
+    }
+
+    #[test]
+    fn skips_prose_and_keeps_every_url_looking_token() {
+        // Invalid URLs are still returned so the scan can report them.
+        assert_eq!(
+            extract_repo_urls("see https://github.com/team/game or https://example.com/x"),
+            ["https://github.com/team/game", "https://example.com/x"]
+        );
+        assert_eq!(extract_repo_urls("team/game"), ["team/game"]);
+        assert_eq!(extract_repo_urls("  ann  "), ["ann"]);
+        assert!(extract_repo_urls("   ").is_empty());
+    }
+
+    type Headers<'a> = &'a [(&'a str, &'a str)];
+
+    fn response(status: u16, headers: Headers, body: &str) -> Response {
+        let mut builder = hyper::Response::builder().status(status);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        Response::from(builder.body(body.to_string()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn maps_each_failed_lookup_to_what_it_says_about_the_repo() {
+        let cases: &[(u16, Headers, &str, LookupError)] = &[
+            (404, &[], "", LookupError::Status(RepoStatus::NotFound)),
+            (410, &[], "", LookupError::Status(RepoStatus::NotFound)),
+            (409, &[], "", LookupError::Status(RepoStatus::Empty)),
+            (451, &[], "", LookupError::Status(RepoStatus::Blocked)),
+            (401, &[], "", LookupError::Unauthorized),
+            (
+                403,
+                &[("x-ratelimit-remaining", "0")],
+                r#"{"message":"API rate limit exceeded"}"#,
+                LookupError::Unknown,
+            ),
+            (403, &[("retry-after", "60")], "", LookupError::Unknown),
+            (
+                403,
+                &[],
+                r#"{"message":"You have exceeded a secondary rate limit"}"#,
+                LookupError::Unknown,
+            ),
+            (
+                403,
+                &[],
+                r#"{"message":"Repository access blocked","block":{"reason":"dmca"}}"#,
+                LookupError::Status(RepoStatus::Blocked),
+            ),
+            (
+                403,
+                &[],
+                r#"{"message":"Resource not accessible by integration"}"#,
+                LookupError::Status(RepoStatus::Inaccessible),
+            ),
+            (
+                403,
+                &[],
+                "not json",
+                LookupError::Status(RepoStatus::Inaccessible),
+            ),
+            (500, &[], "", LookupError::Unknown),
+        ];
+        for (status, headers, body, expected) in cases {
+            assert_eq!(
+                failed_repo_status(response(*status, headers, body)).await,
+                *expected,
+                "{} {:?} {}",
+                status,
+                headers,
+                body
+            );
+        }
+    }
+}
@@ -1,8 +1,19 @@
 use crate::{
-    helper::{check_api_request_limit, format_for_mapping},
-    types::{self, GitHubUpdateData},
+    classify::{categorize_path, PathRule},
+    forge::{
+        classify_forge_url, failed_repo_status, forge_for, Forge, ForgeKind, ForgeRepo,
+        ForgeRepoInfo, ForgeUrlType, LookupError,
+    },
+    helper::{check_api_request_limit, format_for_mapping, merge_contributor},
+    mirror::{with_mirror, GitCache},
+    types::{
+        self, Contributor, GitHubUpdateData, PathCategory, RepoScan, RepoStatus, ScanOverrides,
+        WorkspaceMemberResult,
+    },
+    workspace::{detect_members, member_for_path, workspace_manifest_paths, WorkspaceMember},
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
 use reqwest::{
     header::{AUTHORIZATION, USER_AGENT},
@@ -10,7 +21,8 @@ use reqwest::{
 };
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
 use types::KeywordResult;
 use url::Url;
 
@@ -29,12 +41,20 @@ struct TreeResponse {
 #[derive(Debug, Deserialize)]
 struct ContentResponse {
     content: String,
-    path: String,
 }
 
+#[derive(Debug, PartialEq)]
 pub enum GitHubUrlType {
     User(String),
-    Repo { owner: String, repo_name: String },
+    Repo {
+        owner: String,
+        repo_name: String,
+    },
+    /// `gist.github.com/[owner/]id`; the owner is missing from bare links.
+    Gist {
+        owner: Option<String>,
+        id: String,
+    },
     Invalid,
 }
 
@@ -56,191 +76,580 @@ pub struct Repository {
     pub html_url: String,
 }
 
-/// Clean up dirty GitHub URLs into a canonical `https://github.com/owner[/repo]` form.
-/// Handles:
-///   - missing scheme         (`github.com/foo/bar`, `foo/bar`)
-///   - `www.` host prefix
-///   - SSH style              (`git@github.com:foo/bar.git`)
-///   - `.git` suffix on the repo segment
-///   - trailing slashes, query strings, fragments
-///   - deep paths             (`.../tree/main/...` → keeps only owner/repo)
-///
-/// Returns `None` if the input can't be coerced into a GitHub URL.
-pub fn normalize_github_url(input: &str) -> Option<String> {
-    let mut s = input.trim().to_string();
-    if s.is_empty() {
-        return None;
-    }
+/// One rewrite applied while normalizing a submitted URL. Reported back to
+/// the sheet so data-entry problems can be fixed at the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlFix {
+    AddedScheme,
+    UpgradedHttp,
+    ConvertedSsh,
+    StrippedGitSuffix,
+    DroppedQuery,
+    OrgPage,
+    RawFileLink,
+    PagesSite,
+    PullRequestLink,
+    IssueLink,
+    CommitLink,
+    DeepPath,
+}
 
-    // SSH form: git@github.com:owner/repo(.git)
-    if let Some(rest) = s.strip_prefix("git@github.com:") {
-        s = format!("https://github.com/{}", rest);
+impl UrlFix {
+    pub fn label(self) -> &'static str {
+        match self {
+            UrlFix::AddedScheme => "added https://",
+            UrlFix::UpgradedHttp => "http → https",
+            UrlFix::ConvertedSsh => "ssh → https",
+            UrlFix::StrippedGitSuffix => "dropped .git",
+            UrlFix::DroppedQuery => "dropped query/fragment",
+            UrlFix::OrgPage => "org page → org",
+            UrlFix::RawFileLink => "raw file → repo",
+            UrlFix::PagesSite => "pages site → repo",
+            UrlFix::PullRequestLink => "pull request → repo",
+            UrlFix::IssueLink => "issue → repo",
+            UrlFix::CommitLink => "commit → repo",
+            UrlFix::DeepPath => "deep path → repo",
+        }
     }
+}
 
-    // Add scheme if missing.
-    if !s.starts_with("http://") && !s.starts_with("https://") {
-        let stripped = s
-            .trim_start_matches("www.")
-            .trim_start_matches("github.com/")
-            .trim_start_matches('/');
-        s = format!("https://github.com/{}", stripped);
-    } else if s.starts_with("http://github.com") {
-        s = s.replacen("http://", "https://", 1);
+/// Labels of `fixes` for the sheet, or "none" when the URL was already clean.
+pub fn describe_url_fixes(fixes: &[UrlFix]) -> String {
+    if fixes.is_empty() {
+        return "none".to_string();
     }
+    fixes
+        .iter()
+        .map(|f| f.label())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
 
-    let parsed = Url::parse(&s).ok()?;
-    let host = parsed.host_str()?;
-    if host != "github.com" && host != "www.github.com" {
-        return None;
+/// Adds `https://` to scheme-less input and rewrites `git@host:path` SSH
+/// remotes, recording what was done. Input without a host (`foo/bar`) is
+/// taken to be on github.com.
+pub(crate) fn with_scheme(input: &str, fixes: &mut Vec<UrlFix>) -> String {
+    let mut s = input.trim().to_string();
+    if let Some(rest) = s.strip_prefix("ssh://") {
+        s = rest.to_string();
+        fixes.push(UrlFix::ConvertedSsh);
+        if let Some((host, path)) = s.strip_prefix("git@").and_then(|r| r.split_once('/')) {
+            return format!("https://{}/{}", host, path);
+        }
+    }
+    if let Some((host, path)) = s.strip_prefix("git@").and_then(|r| r.split_once(':')) {
+        fixes.push(UrlFix::ConvertedSsh);
+        return format!("https://{}/{}", host, path);
+    }
+    if let Some(rest) = s.strip_prefix("http://") {
+        fixes.push(UrlFix::UpgradedHttp);
+        return format!("https://{}", rest);
+    }
+    if s.starts_with("https://") {
+        return s;
     }
 
-    let segments: Vec<String> = parsed
-        .path_segments()?
-        .filter(|seg| !seg.is_empty())
-        .map(|seg| seg.trim_end_matches(".git").to_string())
-        .take(2) // owner [, repo] — drop /tree/main/... etc.
-        .collect();
+    fixes.push(UrlFix::AddedScheme);
+    let stripped = s.trim_start_matches('/');
+    // GitHub logins can't contain dots, so a dotted first segment is a host
+    // (`gitlab.com/x/y`, `raw.githubusercontent.com/...`), not a user.
+    if stripped.split('/').next().unwrap_or("").contains('.') {
+        format!("https://{}", stripped)
+    } else {
+        format!("https://github.com/{}", stripped)
+    }
+}
 
-    if segments.is_empty() {
-        return None;
+/// What the rest of a `github.com/<owner>/<repo>/...` path pointed at.
+fn deep_path_fix(kind: &str) -> UrlFix {
+    match kind {
+        "pull" | "pulls" => UrlFix::PullRequestLink,
+        "issues" => UrlFix::IssueLink,
+        "commit" | "commits" => UrlFix::CommitLink,
+        _ => UrlFix::DeepPath,
     }
-    Some(format!("https://github.com/{}", segments.join("/")))
 }
 
-pub fn classify_github_url(url: &str) -> GitHubUrlType {
-    let Some(normalized) = normalize_github_url(url) else {
-        return GitHubUrlType::Invalid;
+/// Parses any GitHub-hosted URL a submission form is likely to contain and
+/// returns its classification plus the rewrites needed to get there:
+///   - missing scheme / `www.` / SSH (`git@github.com:foo/bar.git`)
+///   - `.git` suffix, trailing slashes, query strings (`?tab=repositories`)
+///   - `github.com/orgs/<org>/...` org pages
+///   - `raw.githubusercontent.com/<owner>/<repo>/<ref>/<path>` raw files
+///   - `<owner>.github.io[/<repo>]` Pages sites
+///   - `gist.github.com/[<owner>/]<id>` gists
+///   - pull request, issue, commit and `tree`/`blob` deep links
+pub fn parse_github_url_detailed(input: &str) -> (GitHubUrlType, Vec<UrlFix>) {
+    let mut fixes = Vec::new();
+    if input.trim().is_empty() {
+        return (GitHubUrlType::Invalid, fixes);
+    }
+    let s = with_scheme(input, &mut fixes);
+    let Ok(parsed) = Url::parse(&s) else {
+        return (GitHubUrlType::Invalid, fixes);
     };
-    let Ok(parsed_url) = Url::parse(&normalized) else {
-        return GitHubUrlType::Invalid;
+    let Some(host) = parsed.host_str() else {
+        return (GitHubUrlType::Invalid, fixes);
     };
-    let segments: Vec<_> = parsed_url
+    let host = host.trim_start_matches("www.").to_lowercase();
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        fixes.push(UrlFix::DroppedQuery);
+    }
+
+    let mut segments: Vec<String> = parsed
         .path_segments()
-        .map(|c| c.collect::<Vec<_>>())
+        .map(|segs| {
+            segs.filter(|seg| !seg.is_empty())
+                .map(|seg| seg.to_string())
+                .collect()
+        })
         .unwrap_or_default();
+    // `.git` only ever belongs on the repo segment.
+    if let Some(repo) = segments.get_mut(1) {
+        if let Some(trimmed) = repo.strip_suffix(".git") {
+            *repo = trimmed.to_string();
+            fixes.push(UrlFix::StrippedGitSuffix);
+        }
+    }
 
-    match segments.as_slice() {
-        [owner] => GitHubUrlType::User(owner.to_string()),
-        [owner, repo_name] => GitHubUrlType::Repo {
-            owner: owner.to_string(),
-            repo_name: repo_name.to_string(),
+    let repo = |owner: &str, repo_name: &str| GitHubUrlType::Repo {
+        owner: owner.to_string(),
+        repo_name: repo_name.to_string(),
+    };
+
+    let url_type = match host.as_str() {
+        "github.com" => match segments.as_slice() {
+            [org_page, org, ..] if org_page == "orgs" => {
+                fixes.push(UrlFix::OrgPage);
+                GitHubUrlType::User(org.clone())
+            }
+            [owner] => GitHubUrlType::User(owner.clone()),
+            [owner, repo_name] => repo(owner, repo_name),
+            [owner, repo_name, kind, ..] => {
+                fixes.push(deep_path_fix(kind));
+                repo(owner, repo_name)
+            }
+            [] => GitHubUrlType::Invalid,
+        },
+        "raw.githubusercontent.com" => match segments.as_slice() {
+            [owner, repo_name, ..] => {
+                fixes.push(UrlFix::RawFileLink);
+                repo(owner, repo_name)
+            }
+            _ => GitHubUrlType::Invalid,
         },
-        _ => GitHubUrlType::Invalid,
+        "gist.github.com" | "gist.githubusercontent.com" => match segments.as_slice() {
+            [id] => GitHubUrlType::Gist {
+                owner: None,
+                id: id.clone(),
+            },
+            [owner, id, ..] => GitHubUrlType::Gist {
+                owner: Some(owner.clone()),
+                id: id.clone(),
+            },
+            [] => GitHubUrlType::Invalid,
+        },
+        _ => match host.strip_suffix(".github.io") {
+            // A user's root Pages site is served from `<owner>.github.io`.
+            Some(owner) => {
+                fixes.push(UrlFix::PagesSite);
+                match segments.first() {
+                    Some(repo_name) => repo(owner, repo_name),
+                    None => repo(owner, &host),
+                }
+            }
+            None => GitHubUrlType::Invalid,
+        },
+    };
+    (url_type, fixes)
+}
+
+/// Clean up dirty GitHub URLs into a canonical `https://github.com/owner[/repo]`
+/// form, using the shapes `parse_github_url_detailed` understands.
+///
+/// Returns `None` if the input can't be coerced into a GitHub user or repo URL
+/// (gists included).
+pub fn normalize_github_url(input: &str) -> Option<String> {
+    match parse_github_url_detailed(input).0 {
+        GitHubUrlType::User(owner) => Some(format!("https://github.com/{}", owner)),
+        GitHubUrlType::Repo { owner, repo_name } => {
+            Some(format!("https://github.com/{}/{}", owner, repo_name))
+        }
+        GitHubUrlType::Gist { .. } | GitHubUrlType::Invalid => None,
     }
 }
 
+pub fn classify_github_url(url: &str) -> GitHubUrlType {
+    parse_github_url_detailed(url).0
+}
+
 pub fn get_github_repo(url: &str) -> Option<String> {
     parse_github_url(url).map(|(owner, repo)| format!("https://github.com/{}/{}", owner, repo))
 }
 
 pub fn parse_github_url(url: &str) -> Option<(String, String)> {
-    let normalized = normalize_github_url(url)?;
-    Url::parse(&normalized)
-        .ok()?
-        .path_segments()?
-        .collect::<Vec<_>>()
-        .get(0..2)
-        .map(|s| (s[0].to_string(), s[1].to_string()))
+    match classify_github_url(url) {
+        GitHubUrlType::Repo { owner, repo_name } => Some((owner, repo_name)),
+        _ => None,
+    }
+}
+
+/// GitHub through the REST API; the token is sent with every request.
+pub struct GitHubForge {
+    token: String,
+}
+
+impl GitHubForge {
+    pub fn new(github_token: &str) -> Self {
+        Self {
+            token: github_token.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Forge for GitHubForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitHub
+    }
+
+    fn clone_url(&self, owner: &str, repo: &str) -> String {
+        format!("https://github.com/{}/{}.git", owner, repo)
+    }
+
+    fn clone_credentials(&self) -> Option<(String, String)> {
+        (!self.token.is_empty()).then(|| ("x-access-token".to_string(), self.token.clone()))
+    }
+
+    fn file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String {
+        format!(
+            "https://github.com/{}/{}/blob/{}/{}",
+            owner, repo, git_ref, path
+        )
+    }
+
+    fn snapshot_url(&self, owner: &str, repo: &str, commit_sha: &str) -> String {
+        format!("https://github.com/{}/{}/tree/{}", owner, repo, commit_sha)
+    }
+
+    async fn repo_info(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError> {
+        fetch_repo_metadata(client, owner, repo, &self.token)
+            .await
+            .map(|m| m.info())
+    }
+
+    async fn list_tree(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>> {
+        let tree_url = format!(
+            "https://api.github.com/repos/{}/{}/git/trees/{}?recursive=1",
+            owner, repo, git_ref
+        );
+
+        let tree_resp = client
+            .get(&tree_url)
+            .header(USER_AGENT, "rusty")
+            .header(AUTHORIZATION, format!("token {}", self.token))
+            .send()
+            .await
+            .ok()?;
+        check_api_request_limit(&tree_resp).await;
+
+        // Read the response body as text
+        let body = tree_resp.text().await.ok()?;
+
+        // Parse into your struct
+        let tree: TreeResponse = serde_json::from_str(&body).ok()?;
+        Some(
+            tree.tree
+                .into_iter()
+                .filter(|i| i.item_type == "blob")
+                .map(|i| i.path)
+                .collect(),
+        )
+    }
+
+    async fn fetch_file(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String> {
+        fetch_file_text(client, owner, repo, git_ref, &self.token, path).await
+    }
+
+    async fn last_commit(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)> {
+        get_commit_info(client, owner, repo, git_ref, &self.token).await
+    }
+
+    /// GitHub answers the commit lookup of a repo nothing was pushed to
+    /// with 409 "Git Repository is empty".
+    async fn is_empty(&self, client: &Client, owner: &str, repo: &str, git_ref: &str) -> bool {
+        let commit_url = format!(
+            "https://api.github.com/repos/{}/{}/commits/{}",
+            owner, repo, git_ref
+        );
+        let Ok(resp) = client
+            .get(&commit_url)
+            .header("User-Agent", "rust-app")
+            .bearer_auth(&self.token)
+            .send()
+            .await
+        else {
+            return false;
+        };
+        check_api_request_limit(&resp).await;
+        !resp.status().is_success()
+            && failed_repo_status(resp).await == LookupError::Status(RepoStatus::Empty)
+    }
+
+    async fn contributors(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<Contributor>> {
+        fetch_contributors(client, owner, repo, git_ref, &self.token).await
+    }
+
+    async fn resolve_logins(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        contributors: Vec<Contributor>,
+    ) -> Vec<Contributor> {
+        resolve_contributor_logins(client, owner, repo, &self.token, contributors).await
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn process_repo(
     client: &Client,
+    forge: &dyn Forge,
     owner: &str,
     repo: &str,
-    github_token: &str,
+    git_ref: &str,
+    scan_path: Option<&str>,
     keywords: &[&str],
     allowed_extensions: &[&str],
     files_limit: usize,
-) -> Option<(HashMap<String, KeywordResult>, String, usize)> {
-    let tree_url = format!(
-        "https://api.github.com/repos/{}/{}/git/trees/HEAD?recursive=1",
-        owner, repo
-    );
-
-    let tree_resp = client
-        .get(&tree_url)
-        .header(USER_AGENT, "rusty")
-        .header(AUTHORIZATION, format!("token {}", github_token))
-        .send()
-        .await
-        .ok()?;
-    check_api_request_limit(&tree_resp).await;
-
-    // Read the response body as text
-    let body = tree_resp.text().await.ok()?;
+    path_rules: &[PathRule],
+    include_vendored: bool,
+) -> Option<RepoScan> {
+    let mut tree = forge.list_tree(client, owner, repo, git_ref).await?;
+    if let Some(dir) = scan_path {
+        let prefix = format!("{}/", dir);
+        tree.retain(|p| p.starts_with(&prefix));
+    }
 
-    // Parse into your struct
-    let tree: TreeResponse = serde_json::from_str(&body).ok()?;
+    // Workspace members are resolved from the root (or one-level-down)
+    // manifests before the tree is consumed by the file filter.
+    let tree_paths: Vec<&str> = tree.iter().map(|p| p.as_str()).collect();
+    let mut members: Vec<WorkspaceMember> = Vec::new();
+    for manifest in workspace_manifest_paths(tree_paths.iter().copied()) {
+        if let Some(content) = forge
+            .fetch_file(client, owner, repo, git_ref, &manifest)
+            .await
+        {
+            members.extend(detect_members(&manifest, &content, &tree_paths));
+        }
+    }
+    if !members.is_empty() {
+        println!(
+            "🧩 Workspace members for {}/{}: {:?}",
+            owner,
+            repo,
+            members.iter().map(|m| m.path.as_str()).collect::<Vec<_>>()
+        );
+    }
 
+    // Vendored copies of the SDK are dropped before the limit so they can't
+    // crowd out the team's own files.
     let files: Vec<_> = tree
-        .tree
-        .into_iter()
-        .filter(|i| {
-            i.item_type == "blob"
-                && allowed_extensions.iter().any(|e| i.path.ends_with(e))
-                && !i.path.contains("node_modules")
-                && !i.path.contains("target")
+        .iter()
+        .filter(|path| {
+            allowed_extensions.iter().any(|e| path.ends_with(e))
+                && !path.contains("node_modules")
+                && !path.contains("target")
+        })
+        .map(|path| {
+            let category = categorize_path(path, path_rules);
+            (path, category)
         })
+        .filter(|(_, category)| include_vendored || *category != PathCategory::Vendored)
         .take(files_limit)
         .collect(); // Debug limit
 
     let files_processed = files.len();
 
     println!(
-        "📁 Number of matching files: {} for {}",
+        "📁 Number of matching files: {} for {}/{} ({})",
         files.len(),
-        tree_url
+        owner,
+        repo,
+        forge.kind().as_str()
     );
 
     let mut results = HashMap::new();
+    let mut workspace_breakdown: BTreeMap<String, WorkspaceMemberResult> = BTreeMap::new();
 
-    for item in files {
-        let file_url = format!(
-            "https://api.github.com/repos/{}/{}/contents/{}",
-            owner, repo, item.path
-        );
-
-        let file_resp = client
-            .get(&file_url)
-            .header(USER_AGENT, "rust-scraper")
-            .header(AUTHORIZATION, format!("token {}", github_token))
-            .send()
-            .await
-            .ok()?;
-        check_api_request_limit(&file_resp).await;
-
-        let file: ContentResponse = file_resp.json().await.ok()?;
-        let decoded = general_purpose::STANDARD
-            .decode(file.content.replace('\n', ""))
-            .ok()?;
-        let text = String::from_utf8_lossy(&decoded).to_lowercase();
+    for (path, category) in files {
+        let text = forge
+            .fetch_file(client, owner, repo, git_ref, path)
+            .await?
+            .to_lowercase();
+        let member = member_for_path(path, &members);
 
         for &kw in keywords {
-            let count = text.matches(kw).count();
+            // `text` is lowercased, so the keyword must be too or mixed-case
+            // keywords (`MagicIntentBundleBuilder`) never match.
+            let count = text.matches(&kw.to_lowercase()).count();
             if count > 0 {
                 let entry = results.entry(kw.to_string()).or_insert(KeywordResult {
                     count: 0,
                     files: vec![],
+                    categories: Default::default(),
                 });
                 entry.count += count;
-                entry.files.push(format!(
-                    "https://github.com/{}/{}/blob/HEAD/{}",
-                    owner, repo, file.path
-                ));
+                *entry.categories.entry(category).or_default() += count;
+                entry.files.push(forge.file_url(owner, repo, git_ref, path));
+
+                if !members.is_empty() {
+                    let (name, manifest) = match member {
+                        Some(m) => (m.path.clone(), m.manifest),
+                        None => ("(root)".to_string(), ""),
+                    };
+                    let member_entry = workspace_breakdown.entry(name).or_default();
+                    member_entry.manifest = manifest.to_string();
+                    member_entry.count += count;
+                    *member_entry.keywords.entry(kw.to_string()).or_default() += count;
+                }
             }
         }
     }
 
-    Some((results, allowed_extensions[..].join(", "), files_processed))
+    Some(RepoScan {
+        keyword_counts: results,
+        file_types: allowed_extensions[..].join(", "),
+        files_processed,
+        workspace_breakdown,
+    })
 }
 
-pub async fn get_last_commit_info(
-    client: &reqwest::Client,
+/// Fetches one file through the contents API and decodes it as (lossy) UTF-8.
+async fn fetch_file_text(
+    client: &Client,
     owner: &str,
     repo: &str,
+    git_ref: &str,
     github_token: &str,
-) -> Option<(String, String, String, String)> {
-    // Step 1: Get repo metadata to find default branch
+    path: &str,
+) -> Option<String> {
+    let file_url = format!(
+        "https://api.github.com/repos/{}/{}/contents/{}?ref={}",
+        owner, repo, path, git_ref
+    );
+
+    let file_resp = client
+        .get(&file_url)
+        .header(USER_AGENT, "rust-scraper")
+        .header(AUTHORIZATION, format!("token {}", github_token))
+        .send()
+        .await
+        .ok()?;
+    check_api_request_limit(&file_resp).await;
+
+    let file: ContentResponse = file_resp.json().await.ok()?;
+    let decoded = general_purpose::STANDARD
+        .decode(file.content.replace('\n', ""))
+        .ok()?;
+    Some(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+struct RepoLicense {
+    spdx_id: Option<String>,
+    name: Option<String>,
+}
+
+/// The repo fields judges otherwise look up by hand, from
+/// `GET /repos/{owner}/{repo}` (plus `/languages`, filled in separately).
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct RepoMetadata {
+    pub default_branch: String,
+    /// `owner/repo` under the current name; the API follows renames and
+    /// transfers of the requested repo.
+    #[serde(default)]
+    pub full_name: String,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(rename = "stargazers_count", default)]
+    pub stars: u64,
+    #[serde(rename = "forks_count", default)]
+    pub forks: u64,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    license: Option<RepoLicense>,
+    pub created_at: Option<String>,
+    pub pushed_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    pub homepage: Option<String>,
+    /// Bytes of code per language, from `/languages`.
+    #[serde(skip)]
+    pub languages: BTreeMap<String, u64>,
+}
+
+impl RepoMetadata {
+    pub fn info(&self) -> ForgeRepoInfo {
+        ForgeRepoInfo {
+            default_branch: self.default_branch.clone(),
+            full_name: self.full_name.clone(),
+            web_url: self.html_url.clone(),
+            archived: self.archived,
+            // GitHub has no flag for this; an empty repo shows up as a 409
+            // on the commit lookup instead.
+            empty: false,
+        }
+    }
+
+    /// SPDX id when GitHub recognised the license ("MIT"), else its name.
+    pub fn license(&self) -> Option<String> {
+        let license = self.license.as_ref()?;
+        license
+            .spdx_id
+            .clone()
+            .filter(|id| id != "NOASSERTION")
+            .or_else(|| license.name.clone())
+    }
+}
+
+pub async fn fetch_repo_metadata(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    github_token: &str,
+) -> Result<RepoMetadata, LookupError> {
     let repo_url = format!("https://api.github.com/repos/{}/{}", owner, repo);
     let repo_resp = client
         .get(&repo_url)
@@ -248,16 +657,45 @@ pub async fn get_last_commit_info(
         .bearer_auth(github_token)
         .send()
         .await
-        .ok()?;
+        .map_err(|_| LookupError::Unknown)?;
     check_api_request_limit(&repo_resp).await;
+    if !repo_resp.status().is_success() {
+        return Err(failed_repo_status(repo_resp).await);
+    }
+
+    repo_resp.json().await.map_err(|_| LookupError::Unknown)
+}
+
+pub async fn fetch_repo_languages(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    github_token: &str,
+) -> Option<BTreeMap<String, u64>> {
+    let url = format!("https://api.github.com/repos/{}/{}/languages", owner, repo);
+    let resp = client
+        .get(&url)
+        .header("User-Agent", "rust-app")
+        .bearer_auth(github_token)
+        .send()
+        .await
+        .ok()?;
+    check_api_request_limit(&resp).await;
 
-    let repo_json: serde_json::Value = repo_resp.json().await.ok()?;
-    let default_branch = repo_json["default_branch"].as_str()?.to_string();
+    resp.json().await.ok()
+}
 
-    // Step 2: Get the latest commit on the default branch
+/// Latest commit on `git_ref` as `(sha, date, email, name)`.
+pub async fn get_commit_info(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+    github_token: &str,
+) -> Option<(String, String, String, String)> {
     let commit_url = format!(
         "https://api.github.com/repos/{}/{}/commits/{}",
-        owner, repo, default_branch
+        owner, repo, git_ref
     );
 
     let commit_resp = client
@@ -287,55 +725,277 @@ pub async fn get_last_commit_info(
     Some((sha, date, email, name))
 }
 
-pub async fn fetch_user_repos(
+/// Commits scanned per repo when building the contributor roster
+/// (pages of 100, newest first).
+pub(crate) const CONTRIBUTOR_COMMIT_PAGES: usize = 5;
+
+pub(crate) fn is_bot(
+    login: Option<&str>,
+    account_type: Option<&str>,
+    name: &str,
+    email: &str,
+) -> bool {
+    account_type == Some("Bot")
+        || login.is_some_and(|l| l.ends_with("[bot]"))
+        || name.ends_with("[bot]")
+        || email.contains("[bot]@")
+}
+
+async fn fetch_commit_page(
     client: &Client,
-    username: &str,
+    url: &str,
     github_token: &str,
-    repos_limit: usize,
-) -> (Vec<String>, usize) {
-    let mut repo_urls = Vec::new();
-    let page = 1;
+) -> reqwest::Result<Vec<serde_json::Value>> {
+    let resp = client
+        .get(url)
+        .header("User-Agent", "rust-app")
+        .bearer_auth(github_token)
+        .send()
+        .await?;
+    check_api_request_limit(&resp).await;
+    resp.error_for_status()?.json().await
+}
 
-    loop {
+/// Builds the contributor roster from the commit history of `git_ref`. Commits
+/// are grouped by GitHub login when the author email is linked to an account,
+/// otherwise by lowercased email. Dates cover the scanned window only
+/// (`CONTRIBUTOR_COMMIT_PAGES` × 100 most recent commits). A page that fails
+/// after the first ends the scan with the roster read so far.
+pub async fn fetch_contributors(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+    github_token: &str,
+) -> Option<Vec<Contributor>> {
+    let what = format!("{}/{}", owner, repo);
+    roster_from_pages(&what, |page| {
         let url = format!(
-            "https://api.github.com/users/{}/repos?per_page={}&page={}&sort=pushed&direction=desc",
-            username, repos_limit, page
+            "https://api.github.com/repos/{}/{}/commits?sha={}&per_page=100&page={}",
+            owner, repo, git_ref, page
         );
+        async move { fetch_commit_page(client, &url, github_token).await }
+    })
+    .await
+}
 
-        let resp = match client
-            .get(&url)
-            .header(USER_AGENT, "rust-scraper")
-            .header(AUTHORIZATION, format!("token {}", github_token))
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(_) => break,
-        };
-        check_api_request_limit(&resp).await;
+/// Reads commit pages 1, 2, … from `fetch_page` into a roster until a page
+/// comes back short or `CONTRIBUTOR_COMMIT_PAGES` have been read. `None`
+/// when the first page fails; a later failure keeps the pages read so far.
+async fn roster_from_pages<F, Fut, E>(what: &str, mut fetch_page: F) -> Option<Vec<Contributor>>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Vec<serde_json::Value>, E>>,
+    E: std::fmt::Display,
+{
+    let mut by_key: HashMap<String, Contributor> = HashMap::new();
 
-        let repos: Vec<serde_json::Value> = match resp.json().await {
-            Ok(json) => json,
-            Err(_) => break,
+    for page in 1..=CONTRIBUTOR_COMMIT_PAGES {
+        let commits = match fetch_page(page).await {
+            Ok(commits) => commits,
+            // Nothing to build a roster from.
+            Err(_) if page == 1 => return None,
+            Err(e) => {
+                println!(
+                    "⚠️  Contributor page {} of {} failed ({}); keeping the {} page(s) read",
+                    page,
+                    what,
+                    e,
+                    page - 1
+                );
+                break;
+            }
         };
-
-        if repos.is_empty() {
+        add_commit_page(&mut by_key, &commits);
+        if commits.len() < 100 {
             break;
         }
+    }
+
+    let mut contributors: Vec<Contributor> = by_key.into_values().collect();
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
+    Some(contributors)
+}
 
-        for repo in repos {
-            if let Some(html_url) = repo.get("html_url").and_then(|u| u.as_str()) {
-                repo_urls.push(html_url.to_string());
+/// Counts one page of `/commits` results into `by_key` (lowercased login,
+/// else lowercased email), skipping bots and widening each author's first
+/// and last commit dates.
+fn add_commit_page(by_key: &mut HashMap<String, Contributor>, commits: &[serde_json::Value]) {
+    for commit in commits {
+        let login = commit["author"]["login"].as_str();
+        let account_type = commit["author"]["type"].as_str();
+        let name = commit["commit"]["author"]["name"].as_str().unwrap_or("");
+        let email = commit["commit"]["author"]["email"].as_str().unwrap_or("");
+        let date = commit["commit"]["author"]["date"].as_str().unwrap_or("");
+        if is_bot(login, account_type, name, email) {
+            continue;
+        }
+
+        let key = login
+            .map(|l| l.to_lowercase())
+            .unwrap_or_else(|| email.to_lowercase());
+        let entry = by_key.entry(key).or_insert_with(|| Contributor {
+            login: login.map(|l| l.to_string()),
+            name: name.to_string(),
+            email: email.to_string(),
+            first_commit: date.to_string(),
+            last_commit: date.to_string(),
+            ..Default::default()
+        });
+        entry.commits += 1;
+        // RFC 3339 UTC timestamps compare correctly as strings.
+        if !date.is_empty() && (entry.first_commit.is_empty() || *date < *entry.first_commit) {
+            entry.first_commit = date.to_string();
+        }
+        if *date > *entry.last_commit {
+            entry.last_commit = date.to_string();
+        }
+    }
+}
+
+/// The login in a GitHub noreply address (`123+login@users.noreply.github.com`).
+fn noreply_login(email: &str) -> Option<&str> {
+    let (local, domain) = email.rsplit_once('@')?;
+    if !domain.eq_ignore_ascii_case("users.noreply.github.com") {
+        return None;
+    }
+    Some(local.split_once('+').map_or(local, |(_, login)| login))
+}
+
+/// Lowercased login → account type of the repo's top 100 contributors, from
+/// one `/contributors` call. The API lists no emails, so callers match on
+/// what an author's name or email suggests the login is.
+async fn repo_contributor_logins(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    github_token: &str,
+) -> Option<HashMap<String, (String, Option<String>)>> {
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/contributors?per_page=100",
+        owner, repo
+    );
+    let contributors = fetch_commit_page(client, &url, github_token).await.ok()?;
+    Some(
+        contributors
+            .iter()
+            .filter_map(|c| {
+                let login = c["login"].as_str()?;
+                Some((
+                    login.to_lowercase(),
+                    (login.to_string(), c["type"].as_str().map(str::to_string)),
+                ))
+            })
+            .collect(),
+    )
+}
+
+/// Links a roster built from git history (a mirror's) to GitHub logins
+/// without a request per author: noreply addresses name their login, and
+/// the rest are matched against one `/contributors` page by email local
+/// part or by name. Authors matching neither keep no login. Authors under
+/// several emails of one account are merged, and authors that turn out to
+/// be bots dropped.
+pub async fn resolve_contributor_logins(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    github_token: &str,
+    mut contributors: Vec<Contributor>,
+) -> Vec<Contributor> {
+    for c in contributors.iter_mut().filter(|c| c.login.is_none()) {
+        c.login = noreply_login(&c.email).map(str::to_string);
+    }
+    let known = if contributors.iter().any(|c| c.login.is_none()) {
+        repo_contributor_logins(client, owner, repo, github_token)
+            .await
+            .unwrap_or_default()
+    } else {
+        HashMap::new()
+    };
+
+    link_logins(contributors, &known)
+}
+
+/// Gives each author without a login the one `known` (lowercased login →
+/// login and account type) lists under their email's local part or their
+/// name without spaces, drops authors that turn out to be bots and merges
+/// authors sharing a login.
+fn link_logins(
+    contributors: Vec<Contributor>,
+    known: &HashMap<String, (String, Option<String>)>,
+) -> Vec<Contributor> {
+    let mut resolved: Vec<Contributor> = Vec::new();
+    for mut c in contributors {
+        if c.login.is_none() {
+            let local = c.email.split('@').next().unwrap_or("");
+            let matched = [local, &c.name.replace(' ', "")]
+                .iter()
+                .find_map(|candidate| known.get(&candidate.to_lowercase()));
+            if let Some((login, account_type)) = matched {
+                if is_bot(Some(login), account_type.as_deref(), &c.name, &c.email) {
+                    continue;
+                }
+                c.login = Some(login.clone());
             }
         }
+        merge_contributor(&mut resolved, &c);
+    }
+    resolved.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
+    resolved
+}
 
-        break; // break after 1st page
+pub async fn get_last_commit_info(
+    client: &reqwest::Client,
+    owner: &str,
+    repo: &str,
+    github_token: &str,
+) -> Option<(String, String, String, String)> {
+    let metadata = fetch_repo_metadata(client, owner, repo, github_token)
+        .await
+        .ok()?;
+    get_commit_info(client, owner, repo, &metadata.default_branch, github_token).await
+}
+
+pub async fn fetch_user_repos(
+    client: &Client,
+    username: &str,
+    github_token: &str,
+    repos_limit: usize,
+) -> (Vec<String>, usize) {
+    let mut repo_urls = Vec::new();
+    let page = 1;
+
+    // Only the first page is fetched; `repos_limit` caps the page size.
+    let url = format!(
+        "https://api.github.com/users/{}/repos?per_page={}&page={}&sort=pushed&direction=desc",
+        username, repos_limit, page
+    );
+
+    if let Ok(resp) = client
+        .get(&url)
+        .header(USER_AGENT, "rust-scraper")
+        .header(AUTHORIZATION, format!("token {}", github_token))
+        .send()
+        .await
+    {
+        check_api_request_limit(&resp).await;
+
+        if let Ok(repos) = resp.json::<Vec<serde_json::Value>>().await {
+            for repo in repos {
+                if let Some(html_url) = repo.get("html_url").and_then(|u| u.as_str()) {
+                    repo_urls.push(html_url.to_string());
+                }
+            }
+        }
     }
 
     let total = repo_urls.len();
     (repo_urls, total)
 }
 
+/// Scans the repo at `repo_url` on any supported forge (`scan_forge_repo`).
+#[allow(clippy::too_many_arguments)]
 pub async fn handle_github_repo_url(
     client: &Client,
     repo_url: &str,
@@ -344,55 +1004,243 @@ pub async fn handle_github_repo_url(
     allowed_extensions: &[&str],
     limit: usize,
     origin: &str,
-) -> Result<(GitHubUpdateData, Option<String>)> {
-    if let Some((owner, repo)) = parse_github_url(repo_url) {
-        let (commit_sha, commit_date, email, name) =
-            match get_last_commit_info(client, &owner, &repo, github_token).await {
-                Some(info) => info,
-                None => (
-                    "".to_string(),
-                    chrono::Utc::now().to_rfc3339(),
-                    "".to_string(),
-                    "".to_string(),
-                ),
-            };
+    path_rules: &[PathRule],
+    include_vendored: bool,
+    overrides: &ScanOverrides,
+) -> Result<(GitHubUpdateData, Option<RepoStatus>, Option<String>)> {
+    let ForgeUrlType::Repo(target) = classify_forge_url(repo_url) else {
+        return Ok((
+            GitHubUpdateData::default(),
+            None,
+            Some("Invalid repository URL".to_string()),
+        ));
+    };
+    scan_forge_repo(
+        client,
+        &target,
+        github_token,
+        keywords,
+        allowed_extensions,
+        limit,
+        origin,
+        path_rules,
+        include_vendored,
+        overrides,
+    )
+    .await
+}
 
-        match process_repo(
-            &client,
-            &owner,
-            &repo,
-            &github_token,
-            keywords,
-            allowed_extensions,
-            limit,
-        )
-        .await
-        {
-            Some((repo_map, file_types, files_processed)) => {
-                let formatted_summary = format_for_mapping(
-                    &owner,
-                    &repo,
-                    &commit_sha,
-                    &commit_date,
-                    &repo_map,
-                    &email,
-                    &name,
-                    Some(origin),
-                    &file_types,
-                    &files_processed,
-                );
-                Ok((formatted_summary, None))
-            }
-            None => Ok((
+/// Scans one repo on any supported forge. GitHub repos additionally get
+/// repository metadata, which only the GitHub API provides here. The repo's
+/// status comes back alongside the result, also for repos that couldn't be
+/// scanned; it is `None` when the forge couldn't be reached.
+#[allow(clippy::too_many_arguments)]
+pub async fn scan_forge_repo(
+    client: &Client,
+    target: &ForgeRepo,
+    github_token: &str,
+    keywords: &[&str],
+    allowed_extensions: &[&str],
+    limit: usize,
+    origin: &str,
+    path_rules: &[PathRule],
+    include_vendored: bool,
+    overrides: &ScanOverrides,
+) -> Result<(GitHubUpdateData, Option<RepoStatus>, Option<String>)> {
+    let forge = forge_for(target, github_token);
+
+    let mut metadata = None;
+    let info = if target.kind == ForgeKind::GitHub {
+        fetch_repo_metadata(client, &target.owner, &target.repo_name, github_token)
+            .await
+            .map(|m| {
+                let info = m.info();
+                metadata = Some(m);
+                info
+            })
+    } else {
+        forge
+            .repo_info(client, &target.owner, &target.repo_name)
+            .await
+    };
+    // A lookup that failed for no repo-specific reason still gets a scan
+    // attempt at HEAD, as before.
+    let info = match info {
+        Ok(info) if info.empty => {
+            return Ok((
                 GitHubUpdateData::default(),
-                Some("Failed to process repo data".to_string()),
-            )),
+                Some(RepoStatus::Empty),
+                Some(RepoStatus::Empty.error().to_string()),
+            ));
+        }
+        Ok(info) => Some(info),
+        Err(LookupError::Status(status)) => {
+            return Ok((
+                GitHubUpdateData::default(),
+                Some(status),
+                Some(status.error().to_string()),
+            ));
+        }
+        Err(LookupError::Unauthorized) => bail!(
+            "{} rejected the API token for {}/{} (401 Unauthorized); check the token configuration",
+            target.host,
+            target.owner,
+            target.repo_name
+        ),
+        Err(LookupError::Unknown) => None,
+    };
+    let status = info.as_ref().map(|i| {
+        if i.archived {
+            RepoStatus::Archived
+        } else {
+            RepoStatus::Active
+        }
+    });
+
+    // A renamed or transferred repo answers under its new name; scan and
+    // record it under that identity so results don't split across two.
+    let (owner, repo) = match info.as_ref().and_then(|i| i.full_name.rsplit_once('/')) {
+        Some((owner, repo)) => (owner.to_string(), repo.to_string()),
+        None => (target.owner.clone(), target.repo_name.clone()),
+    };
+    if !owner.eq_ignore_ascii_case(&target.owner) || !repo.eq_ignore_ascii_case(&target.repo_name) {
+        println!(
+            "↪️  {}/{} has moved to {}/{}",
+            target.owner, target.repo_name, owner, repo
+        );
+    }
+    let (owner, repo) = (owner.as_str(), repo.as_str());
+
+    if let Some(metadata) = metadata.as_mut() {
+        metadata.languages = fetch_repo_languages(client, owner, repo, github_token)
+            .await
+            .unwrap_or_default();
+    }
+    let default_branch = overrides
+        .branch
+        .clone()
+        .or_else(|| target.git_ref.clone())
+        .or_else(|| info.as_ref().map(|i| i.default_branch.clone()));
+
+    // With a git cache configured, files and history come from a local
+    // mirror; the forge API still answers for metadata and links.
+    let forge = match GitCache::from_env() {
+        Some(cache) if target.kind != ForgeKind::Local && info.is_some() => {
+            with_mirror(forge, &cache, &target.host, owner, repo).await
+        }
+        _ => forge,
+    };
+
+    let (commit_info, contributors) = match default_branch.as_deref() {
+        Some(branch) => (
+            forge.last_commit(client, owner, repo, branch).await,
+            forge.contributors(client, owner, repo, branch).await,
+        ),
+        None => (None, None),
+    };
+    let has_commit = commit_info.is_some();
+    let (commit_sha, commit_date, email, name) = match commit_info {
+        Some(info) => info,
+        None => (
+            "".to_string(),
+            chrono::Utc::now().to_rfc3339(),
+            "".to_string(),
+            "".to_string(),
+        ),
+    };
+
+    let git_ref = default_branch.as_deref().unwrap_or("HEAD");
+    let mut keywords = keywords.to_vec();
+    for extra in &overrides.extra_keywords {
+        if !keywords.iter().any(|k| k.eq_ignore_ascii_case(extra)) {
+            keywords.push(extra);
+        }
+    }
+    match process_repo(
+        client,
+        forge.as_ref(),
+        owner,
+        repo,
+        git_ref,
+        overrides.path.as_deref(),
+        &keywords,
+        allowed_extensions,
+        limit,
+        path_rules,
+        include_vendored,
+    )
+    .await
+    {
+        Some(scan) => {
+            let mut formatted_summary = format_for_mapping(
+                owner,
+                repo,
+                &commit_sha,
+                &commit_date,
+                &scan.keyword_counts,
+                &email,
+                &name,
+                Some(origin),
+                &scan.file_types,
+                &scan.files_processed,
+                scan.workspace_breakdown,
+                &forge.snapshot_url(owner, repo, &commit_sha),
+            );
+            formatted_summary.forge = Some(target.kind);
+            formatted_summary.repo_status = status;
+            formatted_summary.scan_overrides = (!overrides.is_empty()).then(|| overrides.clone());
+            formatted_summary.canonical_url = info
+                .map(|i| i.web_url)
+                .filter(|u| !u.is_empty())
+                .or_else(|| Some(target.web_url()));
+            if let Some(metadata) = metadata {
+                formatted_summary.stars = Some(metadata.stars);
+                formatted_summary.forks = Some(metadata.forks);
+                formatted_summary.license = metadata.license();
+                formatted_summary.topics = metadata.topics;
+                formatted_summary.repo_created_at = metadata.created_at;
+                formatted_summary.repo_pushed_at = metadata.pushed_at;
+                formatted_summary.archived = Some(metadata.archived);
+                formatted_summary.languages = metadata.languages;
+                formatted_summary.homepage = metadata.homepage.filter(|h| !h.is_empty());
+            }
+            if let Some(contributors) = contributors {
+                formatted_summary.team_size = Some(contributors.len());
+                formatted_summary.contributors = contributors;
+            }
+            Ok((formatted_summary, status, None))
+        }
+        None => {
+            // A Scan Branch override naming a branch the repo doesn't have;
+            // the repo itself is fine.
+            let missing_branch = overrides.branch.as_deref().filter(|_| !has_commit);
+            let forge_says_empty = match default_branch.as_deref() {
+                Some(branch) if !has_commit && missing_branch.is_none() && status.is_some() => {
+                    forge.is_empty(client, owner, repo, branch).await
+                }
+                _ => false,
+            };
+            let (status, error) = unscanned_status(status, missing_branch, forge_says_empty);
+            Ok((GitHubUpdateData::default(), status, Some(error)))
         }
-    } else {
-        Ok((
-            GitHubUpdateData::default(),
-            Some("Invalid GitHub URL".to_string()),
-        ))
+    }
+}
+
+/// Status and error for a repo that yielded no scan. Empty only when the
+/// forge said so; anything else (a timeout, a 5xx, a rate limit, a failed
+/// tree listing) keeps the looked-up status and reports the failure.
+fn unscanned_status(
+    status: Option<RepoStatus>,
+    missing_branch: Option<&str>,
+    forge_says_empty: bool,
+) -> (Option<RepoStatus>, String) {
+    match missing_branch {
+        Some(branch) => (status, format!("branch '{}' not found", branch)),
+        None if forge_says_empty => (
+            Some(RepoStatus::Empty),
+            RepoStatus::Empty.error().to_string(),
+        ),
+        None => (status, "Failed to process repo data".to_string()),
     }
 }
 
@@ -418,6 +1266,15 @@ pub async fn search_code(
     Ok(res.items)
 }
 
+/// MagicBlock's own repos, which discovery leaves out.
+pub fn is_first_party_repo(repo_url: &str) -> bool {
+    let exclude_keywords = ["magicblock-labs"];
+    let url_lower = repo_url.to_lowercase();
+    exclude_keywords
+        .iter()
+        .any(|kw| url_lower.contains(&kw.to_lowercase()))
+}
+
 pub async fn search_github_repos(queries: &[&str], github_token: &str) -> Result<Vec<String>> {
     let mut seen_repos: HashSet<String> = HashSet::new();
     for &query in queries {
@@ -439,19 +1296,349 @@ pub async fn search_github_repos(queries: &[&str], github_token: &str) -> Result
     }
 
     // Filter repos
-    let exclude_keywords = vec!["magicblock-labs"];
     let filtered_repo_urls: Vec<String> = seen_repos
         .into_iter()
-        .filter(|repo_url| {
-            let url_lower = repo_url.to_lowercase();
-            !exclude_keywords
-                .iter()
-                .any(|kw| url_lower.contains(&kw.to_lowercase()))
-        })
+        .filter(|repo_url| !is_first_party_repo(repo_url))
         .collect();
     println!(
         "Found {} unique repos from queries",
         filtered_repo_urls.len()
     );
-    return Ok(filtered_repo_urls);
+    Ok(filtered_repo_urls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    fn commit(login: Option<&str>, name: &str, email: &str, date: &str) -> serde_json::Value {
+        json!({
+            "author": login.map(|l| json!({"login": l, "type": "User"})),
+            "commit": {"author": {"name": name, "email": email, "date": date}},
+        })
+    }
+
+    /// `len` commits by Ann.
+    fn page(len: usize) -> Vec<serde_json::Value> {
+        (0..len)
+            .map(|_| {
+                commit(
+                    Some("ann"),
+                    "Ann",
+                    "ann@example.com",
+                    "2024-01-01T00:00:00Z",
+                )
+            })
+            .collect()
+    }
+
+    fn contributor(login: Option<&str>, name: &str, email: &str, commits: usize) -> Contributor {
+        Contributor {
+            login: login.map(str::to_string),
+            name: name.to_string(),
+            email: email.to_string(),
+            commits,
+            first_commit: "2024-01-01T00:00:00Z".to_string(),
+            last_commit: "2024-02-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn groups_commits_by_login_then_by_email() {
+        let mut by_key = HashMap::new();
+        add_commit_page(
+            &mut by_key,
+            &[
+                commit(Some("Ann"), "Ann", "ann@work.com", "2024-03-01T00:00:00Z"),
+                commit(
+                    Some("ann"),
+                    "Ann L.",
+                    "ann@home.com",
+                    "2024-01-15T00:00:00Z",
+                ),
+                commit(None, "Bob", "Bob@Example.com", "2024-02-01T00:00:00Z"),
+                commit(None, "Bob", "bob@example.com", "2024-02-02T00:00:00Z"),
+                commit(
+                    Some("dependabot[bot]"),
+                    "dependabot",
+                    "x@y.z",
+                    "2024-02-03T00:00:00Z",
+                ),
+                commit(None, "ci", "ci[bot]@users.noreply.github.com", ""),
+            ],
+        );
+        assert_eq!(by_key.len(), 2);
+
+        let ann = &by_key["ann"];
+        assert_eq!(ann.login.as_deref(), Some("Ann"));
+        assert_eq!(ann.email, "ann@work.com");
+        assert_eq!(ann.commits, 2);
+        assert_eq!(ann.first_commit, "2024-01-15T00:00:00Z");
+        assert_eq!(ann.last_commit, "2024-03-01T00:00:00Z");
+
+        let bob = &by_key["bob@example.com"];
+        assert_eq!((bob.login.as_deref(), bob.commits), (None, 2));
+    }
+
+    #[tokio::test]
+    async fn reads_at_most_the_page_cap() {
+        let fetched = Cell::new(0);
+        let roster = roster_from_pages("team/game", |p| {
+            fetched.set(p);
+            async { Ok::<_, String>(page(100)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(fetched.get(), CONTRIBUTOR_COMMIT_PAGES);
+        assert_eq!(roster[0].commits, CONTRIBUTOR_COMMIT_PAGES * 100);
+    }
+
+    #[tokio::test]
+    async fn stops_after_a_short_page() {
+        let fetched = Cell::new(0);
+        let roster = roster_from_pages("team/game", |p| {
+            fetched.set(p);
+            async move { Ok::<_, String>(page(if p == 1 { 100 } else { 7 })) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(fetched.get(), 2);
+        assert_eq!(roster[0].commits, 107);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_pages_read_when_a_later_page_fails() {
+        let roster = roster_from_pages("team/game", |p| async move {
+            match p {
+                1 | 2 => Ok(page(100)),
+                _ => Err("HTTP 502"),
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(roster[0].commits, 200);
+
+        let none = roster_from_pages("team/game", |_| async { Err::<Vec<_>, _>("HTTP 502") }).await;
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn reads_logins_from_noreply_addresses() {
+        assert_eq!(
+            noreply_login("123+Ann@users.noreply.github.com"),
+            Some("Ann")
+        );
+        assert_eq!(noreply_login("ann@users.noreply.github.com"), Some("ann"));
+        assert_eq!(noreply_login("ann@example.com"), None);
+        assert_eq!(noreply_login("not an email"), None);
+    }
+
+    #[test]
+    fn links_logins_by_email_or_name_and_merges_accounts() {
+        let known = HashMap::from([
+            (
+                "ann".to_string(),
+                ("Ann".to_string(), Some("User".to_string())),
+            ),
+            ("bobsmith".to_string(), ("BobSmith".to_string(), None)),
+            (
+                "ci".to_string(),
+                ("ci".to_string(), Some("Bot".to_string())),
+            ),
+        ]);
+        let roster = link_logins(
+            vec![
+                contributor(Some("ann"), "Ann", "1+ann@users.noreply.github.com", 3),
+                contributor(None, "Ann", "ann@work.com", 2),
+                contributor(None, "Bob Smith", "bob@home.com", 4),
+                contributor(None, "CI", "ci@example.com", 9),
+                contributor(None, "Cy", "cy@example.com", 1),
+            ],
+            &known,
+        );
+        let summary: Vec<(Option<&str>, usize)> = roster
+            .iter()
+            .map(|c| (c.login.as_deref(), c.commits))
+            .collect();
+        assert_eq!(
+            summary,
+            [(Some("ann"), 5), (Some("BobSmith"), 4), (None, 1)]
+        );
+    }
+
+    #[test]
+    fn parses_messy_github_urls_and_reports_the_fixes() {
+        use UrlFix::*;
+        let repo = |owner: &str, repo_name: &str| GitHubUrlType::Repo {
+            owner: owner.to_string(),
+            repo_name: repo_name.to_string(),
+        };
+        let user = |owner: &str| GitHubUrlType::User(owner.to_string());
+        let cases = [
+            ("https://github.com/team/game", repo("team", "game"), vec![]),
+            (
+                "github.com/team/game",
+                repo("team", "game"),
+                vec![AddedScheme],
+            ),
+            ("team/game", repo("team", "game"), vec![AddedScheme]),
+            (
+                "https://www.github.com/team/game/",
+                repo("team", "game"),
+                vec![],
+            ),
+            (
+                "http://github.com/team/game",
+                repo("team", "game"),
+                vec![UpgradedHttp],
+            ),
+            (
+                "  https://github.com/team/game.git  ",
+                repo("team", "game"),
+                vec![StrippedGitSuffix],
+            ),
+            (
+                "git@github.com:team/game.git",
+                repo("team", "game"),
+                vec![ConvertedSsh, StrippedGitSuffix],
+            ),
+            (
+                "ssh://git@github.com/team/game",
+                repo("team", "game"),
+                vec![ConvertedSsh],
+            ),
+            (
+                "https://github.com/team/game?tab=readme#setup",
+                repo("team", "game"),
+                vec![DroppedQuery],
+            ),
+            (
+                "https://github.com/team/game/tree/main/programs",
+                repo("team", "game"),
+                vec![DeepPath],
+            ),
+            (
+                "https://github.com/team/game/blob/main/src/lib.rs",
+                repo("team", "game"),
+                vec![DeepPath],
+            ),
+            (
+                "https://github.com/team/game/pull/7/files",
+                repo("team", "game"),
+                vec![PullRequestLink],
+            ),
+            (
+                "https://github.com/team/game/issues/3",
+                repo("team", "game"),
+                vec![IssueLink],
+            ),
+            (
+                "https://github.com/team/game/commit/abc123",
+                repo("team", "game"),
+                vec![CommitLink],
+            ),
+            (
+                "https://raw.githubusercontent.com/team/game/main/Cargo.toml",
+                repo("team", "game"),
+                vec![RawFileLink],
+            ),
+            (
+                "https://team.github.io/game/",
+                repo("team", "game"),
+                vec![PagesSite],
+            ),
+            (
+                "https://team.github.io",
+                repo("team", "team.github.io"),
+                vec![PagesSite],
+            ),
+            (
+                "https://github.com/team?tab=repositories",
+                user("team"),
+                vec![DroppedQuery],
+            ),
+            (
+                "https://github.com/orgs/team/repositories",
+                user("team"),
+                vec![OrgPage],
+            ),
+            (
+                "https://gist.github.com/ann/abc123",
+                GitHubUrlType::Gist {
+                    owner: Some("ann".to_string()),
+                    id: "abc123".to_string(),
+                },
+                vec![],
+            ),
+            ("", GitHubUrlType::Invalid, vec![]),
+            ("https://github.com/", GitHubUrlType::Invalid, vec![]),
+            (
+                "https://example.com/team/game",
+                GitHubUrlType::Invalid,
+                vec![],
+            ),
+        ];
+        for (input, expected, fixes) in cases {
+            assert_eq!(
+                parse_github_url_detailed(input),
+                (expected, fixes),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn adds_a_scheme_only_where_one_is_missing() {
+        let mut fixes = Vec::new();
+        assert_eq!(
+            with_scheme("gitlab.com/x/y", &mut fixes),
+            "https://gitlab.com/x/y"
+        );
+        assert_eq!(
+            with_scheme("/team/game", &mut fixes),
+            "https://github.com/team/game"
+        );
+        assert_eq!(fixes, [UrlFix::AddedScheme, UrlFix::AddedScheme]);
+
+        fixes.clear();
+        assert_eq!(
+            with_scheme("https://gitlab.com/x/y", &mut fixes),
+            "https://gitlab.com/x/y"
+        );
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn describes_fixes_for_the_sheet() {
+        assert_eq!(describe_url_fixes(&[]), "none");
+        assert_eq!(
+            describe_url_fixes(&[UrlFix::AddedScheme, UrlFix::StrippedGitSuffix]),
+            "added https://; dropped .git"
+        );
+    }
+
+    #[test]
+    fn marks_a_repo_empty_only_when_the_forge_says_so() {
+        let active = Some(RepoStatus::Active);
+        assert_eq!(
+            unscanned_status(active, Some("dev"), false),
+            (active, "branch 'dev' not found".to_string())
+        );
+        assert_eq!(
+            unscanned_status(active, None, true),
+            (Some(RepoStatus::Empty), "Repository is empty".to_string())
+        );
+        // A timeout, 5xx or failed tree listing on a repo with commits.
+        assert_eq!(
+            unscanned_status(active, None, false),
+            (active, "Failed to process repo data".to_string())
+        );
+        assert_eq!(
+            unscanned_status(None, None, false),
+            (None, "Failed to process repo data".to_string())
+        );
+    }
 }
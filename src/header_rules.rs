@@ -0,0 +1,376 @@
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Field of rules that claim a header for no field, so the header keeps its
+/// own name (the scraper's result columns, which would otherwise look like
+/// repo links).
+pub const IGNORE_FIELD: &str = "-";
+
+/// How a rule matches a header: case-insensitively, against the trimmed
+/// header text.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderPattern {
+    Exact(Cow<'static, str>),
+    Contains(Cow<'static, str>),
+    Regex(Cow<'static, str>),
+}
+
+impl fmt::Display for HeaderPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeaderPattern::Exact(p) => write!(f, "exact {:?}", p),
+            HeaderPattern::Contains(p) => write!(f, "contains {:?}", p),
+            HeaderPattern::Regex(p) => write!(f, "regex {:?}", p),
+        }
+    }
+}
+
+/// Maps headers matching `pattern` to `field`. A header matching several
+/// rules takes the highest `priority`, then the earliest rule; a field
+/// matching several headers takes the one whose rule has the highest
+/// priority, then the earliest rule, then the header that sorts first.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct HeaderRule {
+    pub field: Cow<'static, str>,
+    #[serde(flatten)]
+    pub pattern: HeaderPattern,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl HeaderRule {
+    pub const fn exact(field: &'static str, pattern: &'static str, priority: i32) -> Self {
+        Self::new(
+            field,
+            HeaderPattern::Exact(Cow::Borrowed(pattern)),
+            priority,
+        )
+    }
+
+    pub const fn contains(field: &'static str, pattern: &'static str, priority: i32) -> Self {
+        Self::new(
+            field,
+            HeaderPattern::Contains(Cow::Borrowed(pattern)),
+            priority,
+        )
+    }
+
+    pub const fn regex(field: &'static str, pattern: &'static str, priority: i32) -> Self {
+        Self::new(
+            field,
+            HeaderPattern::Regex(Cow::Borrowed(pattern)),
+            priority,
+        )
+    }
+
+    const fn new(field: &'static str, pattern: HeaderPattern, priority: i32) -> Self {
+        Self {
+            field: Cow::Borrowed(field),
+            pattern,
+            priority,
+        }
+    }
+}
+
+/// Rules every sheet read applies, below a row struct's own aliases
+/// (`SheetRow::ALIASES`): the scraper's result columns belong to no field,
+/// though they would otherwise look like repo links.
+pub const SHARED_RULES: &[HeaderRule] = &[
+    HeaderRule::regex(IGNORE_FIELD, r"^scraper\b", 100),
+    HeaderRule::exact(IGNORE_FIELD, "canonical repo url", 100),
+    HeaderRule::regex(
+        IGNORE_FIELD,
+        r"^repo (status|stars|forks|topics|license|created at|pushed at|archived|languages|homepage)$",
+        100,
+    ),
+];
+
+#[derive(Clone)]
+enum Matcher {
+    Exact(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+fn matcher(pattern: &HeaderPattern) -> Result<Matcher> {
+    Ok(match pattern {
+        HeaderPattern::Exact(p) => Matcher::Exact(p.trim().to_lowercase()),
+        HeaderPattern::Contains(p) => Matcher::Contains(p.to_lowercase()),
+        HeaderPattern::Regex(p) => Matcher::Regex(
+            RegexBuilder::new(p)
+                .case_insensitive(true)
+                .build()
+                .with_context(|| format!("invalid header regex {:?}", p))?,
+        ),
+    })
+}
+
+impl Matcher {
+    fn matches(&self, lower: &str) -> bool {
+        match self {
+            Matcher::Exact(p) => lower == p,
+            Matcher::Contains(p) => lower.contains(p.as_str()),
+            Matcher::Regex(re) => re.is_match(lower),
+        }
+    }
+}
+
+/// Which sheet header feeds which field, shared by all three binaries:
+/// configured rules first, then the defaults of the row being read, then
+/// `SHARED_RULES`.
+pub struct HeaderRules {
+    rules: Vec<(HeaderRule, Matcher)>,
+    /// How many of `rules` come from `SCRAPER_HEADER_RULES`.
+    configured: usize,
+}
+
+/// The header a field resolved to, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedField {
+    pub field: String,
+    pub header: String,
+    pub rule: HeaderRule,
+    /// Other headers that matched the field and lost.
+    pub passed_over: Vec<String>,
+}
+
+impl HeaderRules {
+    /// `configured` rules over `SHARED_RULES`.
+    pub fn new(configured: Vec<HeaderRule>) -> Result<Self> {
+        let count = configured.len();
+        let rules = configured
+            .into_iter()
+            .chain(SHARED_RULES.iter().cloned())
+            .map(|rule| Ok((rule.clone(), matcher(&rule.pattern)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            rules,
+            configured: count,
+        })
+    }
+
+    /// Rules from the JSON file named in `SCRAPER_HEADER_RULES`
+    /// (`[{"field": "contact", "contains": "discord", "priority": 25}]`)
+    /// over `SHARED_RULES`. Readers add their row's defaults with
+    /// `with_defaults`; at equal priority the file's rules win.
+    pub fn load() -> Result<Self> {
+        let rules = match std::env::var("SCRAPER_HEADER_RULES") {
+            Ok(path) if !path.trim().is_empty() => {
+                let raw = std::fs::read_to_string(path.trim())
+                    .with_context(|| format!("reading SCRAPER_HEADER_RULES file '{}'", path))?;
+                serde_json::from_str::<Vec<HeaderRule>>(&raw)
+                    .with_context(|| format!("parsing SCRAPER_HEADER_RULES file '{}'", path))?
+            }
+            _ => Vec::new(),
+        };
+        Self::new(rules)
+    }
+
+    /// These rules with `defaults` (a row struct's `SheetRow::ALIASES`)
+    /// between the configured rules and `SHARED_RULES`. Rules already
+    /// present aren't added twice.
+    pub fn with_defaults(&self, defaults: &[HeaderRule]) -> Result<Self> {
+        let mut rules: Vec<(HeaderRule, Matcher)> = Vec::with_capacity(self.rules.len());
+        rules.extend(self.rules[..self.configured].iter().cloned());
+        for rule in defaults {
+            if !self.rules.iter().any(|(r, _)| r == rule) {
+                rules.push((rule.clone(), matcher(&rule.pattern)?));
+            }
+        }
+        rules.extend(self.rules[self.configured..].iter().cloned());
+        Ok(Self {
+            rules,
+            configured: self.configured,
+        })
+    }
+
+    /// The winning rule for `header` and its position, ignore rules
+    /// included.
+    fn best_rule(&self, header: &str) -> Option<(usize, &HeaderRule)> {
+        let lower = header.trim().to_lowercase();
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, matcher))| matcher.matches(&lower))
+            .max_by_key(|(i, (rule, _))| (rule.priority, std::cmp::Reverse(*i)))
+            .map(|(i, (rule, _))| (i, rule))
+    }
+
+    /// The field `header` feeds, if any.
+    pub fn field_for(&self, header: &str) -> Option<&str> {
+        self.best_rule(header)
+            .map(|(_, rule)| rule.field.as_ref())
+            .filter(|f| *f != IGNORE_FIELD)
+    }
+
+    /// `header` renamed to its field, or unchanged.
+    pub fn rename(&self, header: &str) -> String {
+        self.field_for(header).unwrap_or(header).to_string()
+    }
+
+    /// Resolves every field some header matches to exactly one header,
+    /// in field order of first appearance among `headers`.
+    pub fn resolve<'a>(&self, headers: impl IntoIterator<Item = &'a str>) -> Vec<ResolvedField> {
+        // Per field: (rule position, rule, header) for every matching header.
+        type Candidates<'r, 'h> = Vec<(usize, &'r HeaderRule, &'h str)>;
+        let mut candidates: Vec<(String, Candidates)> = Vec::new();
+        for header in headers {
+            let Some((rank, rule)) = self.best_rule(header) else {
+                continue;
+            };
+            if rule.field == IGNORE_FIELD {
+                continue;
+            }
+            match candidates.iter_mut().find(|(f, _)| *f == rule.field) {
+                Some((_, found)) => found.push((rank, rule, header)),
+                None => candidates.push((rule.field.to_string(), vec![(rank, rule, header)])),
+            }
+        }
+        candidates
+            .into_iter()
+            .map(|(field, mut found)| {
+                found.sort_by_key(|(rank, rule, header)| {
+                    (std::cmp::Reverse(rule.priority), *rank, header.to_string())
+                });
+                let (_, rule, header) = found[0];
+                ResolvedField {
+                    field,
+                    header: header.to_string(),
+                    rule: rule.clone(),
+                    passed_over: found[1..].iter().map(|(_, _, h)| h.to_string()).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// The header `field` resolves to among `headers`.
+    pub fn header_for<'a>(
+        &self,
+        headers: impl IntoIterator<Item = &'a str>,
+        field: &str,
+    ) -> Option<String> {
+        self.resolve(headers)
+            .into_iter()
+            .find(|r| r.field == field)
+            .map(|r| r.header)
+    }
+
+    /// One line per field in `fields`: the header (and column) it reads
+    /// from, the rule that picked it and the headers it beat; for
+    /// `--explain-columns`.
+    pub fn explain(&self, headers: &[String], fields: &[&str]) -> String {
+        let resolved: HashMap<String, ResolvedField> = self
+            .resolve(headers.iter().map(String::as_str))
+            .into_iter()
+            .map(|r| (r.field.clone(), r))
+            .collect();
+        let mut lines: Vec<String> = Vec::new();
+        for field in fields {
+            let Some(r) = resolved.get(*field) else {
+                lines.push(format!("  {:<18} ← (no matching header)", field));
+                continue;
+            };
+            let column = headers
+                .iter()
+                .position(|h| *h == r.header)
+                .map(|i| crate::a1::column_number_to_letter(i + 1))
+                .unwrap_or_default();
+            let mut line = format!(
+                "  {:<18} ← '{}' (col {}; {}, priority {})",
+                field, r.header, column, r.rule.pattern, r.rule.priority
+            );
+            if !r.passed_over.is_empty() {
+                line.push_str(&format!("; passed over {:?}", r.passed_over));
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+/// Whether the binary was run with `--explain-columns`: print the column
+/// resolution and exit without scanning or writing.
+pub fn explain_columns_requested() -> bool {
+    std::env::args().skip(1).any(|a| a == "--explain-columns")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rows::{rows_from_columns, SheetRow};
+    use crate::types::SubmissionRow;
+
+    fn columns(cells: &[(&str, &str)]) -> HashMap<String, Vec<String>> {
+        cells
+            .iter()
+            .map(|(header, value)| (header.to_string(), vec![value.to_string()]))
+            .collect()
+    }
+
+    #[test]
+    fn reads_submission_rows_through_the_struct_aliases() {
+        let sheet = columns(&[
+            ("GitHub Repo", "https://github.com/team/game"),
+            ("Scan Branch", "dev"),
+            ("Scraper Result (JSON)", "{}"),
+            ("Highlights", "fast"),
+            ("Telegram", "@team"),
+        ]);
+        let rules = HeaderRules::new(Vec::new()).unwrap();
+        let rows: Vec<SubmissionRow> = rows_from_columns(&sheet, &rules).unwrap();
+        assert_eq!(rows[0].snapshot_url, "https://github.com/team/game");
+        assert_eq!(rows[0].scan_branch, "dev");
+        assert_eq!(rows[0].contact.as_deref(), Some("@team"));
+    }
+
+    #[test]
+    fn unrelated_branch_headers_are_not_scan_branches() {
+        let sheet = columns(&[
+            ("GitHub Repo", "https://github.com/team/game"),
+            ("Default Branch", "main"),
+            ("Branch Protection", "on"),
+        ]);
+        let rules = HeaderRules::new(Vec::new()).unwrap();
+        let rows: Vec<SubmissionRow> = rows_from_columns(&sheet, &rules).unwrap();
+        assert_eq!(rows[0].scan_branch, "");
+
+        let submission = rules.with_defaults(SubmissionRow::ALIASES).unwrap();
+        let headers = ["Default Branch", "Branch to scan"];
+        assert_eq!(
+            submission.header_for(headers, "scan_branch").as_deref(),
+            Some("Branch to scan")
+        );
+    }
+
+    #[test]
+    fn configured_rules_win_ties_against_the_struct_aliases() {
+        let configured: Vec<HeaderRule> = serde_json::from_str(
+            r#"[{"field": "snapshot_url", "exact": "code", "priority": 30},
+                {"field": "contact", "contains": "discord", "priority": 25}]"#,
+        )
+        .unwrap();
+        let rules = HeaderRules::new(configured).unwrap();
+        let sheet = columns(&[
+            ("GitHub Repo", "https://github.com/team/old"),
+            ("Code", "https://github.com/team/game"),
+            ("Telegram", "@team"),
+            ("Discord", "team#1"),
+        ]);
+        let rows: Vec<SubmissionRow> = rows_from_columns(&sheet, &rules).unwrap();
+        assert_eq!(rows[0].snapshot_url, "https://github.com/team/game");
+        assert_eq!(rows[0].contact.as_deref(), Some("team#1"));
+
+        let submission = rules.with_defaults(SubmissionRow::ALIASES).unwrap();
+        let headers = ["GitHub Repo", "Code", "Repo Status"];
+        assert_eq!(
+            submission.header_for(headers, "snapshot_url").as_deref(),
+            Some("Code")
+        );
+        assert_eq!(submission.field_for("Repo Status"), None);
+    }
+}
@@ -1,12 +1,15 @@
+use crate::classify::classify_integration;
 use crate::types;
 
 use chrono::Utc;
 use reqwest::Response;
 use serde_json::json;
+use std::collections::BTreeMap;
 use tokio::time::{sleep, Duration};
-use types::GitHubUpdateData;
 use types::RepoMap;
+use types::{Contributor, GitHubUpdateData, RepoSummary, WorkspaceMemberResult};
 
+#[allow(clippy::too_many_arguments)]
 pub fn format_for_mapping(
     owner: &str,
     repo_name: &str,
@@ -18,6 +21,8 @@ pub fn format_for_mapping(
     origin: Option<&str>,
     file_types: &str,
     files_processed: &usize,
+    workspace_breakdown: BTreeMap<String, WorkspaceMemberResult>,
+    snapshot_url: &str,
 ) -> GitHubUpdateData {
     let mut keyword_counts_json = serde_json::Map::new();
 
@@ -33,14 +38,10 @@ pub fn format_for_mapping(
 
     let keyword_matches = &keyword_counts_json.len();
 
-    let snapshot_url = format!(
-        "https://github.com/{}/{}/tree/{}",
-        owner, repo_name, commit_sha
-    );
-
     let origin = origin.unwrap_or("unknown");
+    let integration = classify_integration(keyword_counts);
 
-    let formatted_result = GitHubUpdateData {
+    GitHubUpdateData {
         repo_name: repo_name.to_string(),
         owner: owner.to_string(),
         commit_sha: commit_sha.to_string(),
@@ -53,12 +54,108 @@ pub fn format_for_mapping(
         origin: origin.to_string(),
         file_types: file_types.to_string(),
         files_processed: files_processed.to_string(),
+        integration_type: integration.integration_type,
+        integration_features: integration.features,
+        depth_score: integration.depth_score,
+        category_counts: integration.category_counts,
+        workspace_breakdown,
 
         // Optional fields can be added here if needed
         ..Default::default()
+    }
+}
+
+/// Adds `c` to `roster`, or folds its commits and dates into the entry with
+/// the same login (the same email when either has no login).
+pub fn merge_contributor(roster: &mut Vec<Contributor>, c: &Contributor) {
+    let same = |o: &&mut Contributor| match (&o.login, &c.login) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => o.email.eq_ignore_ascii_case(&c.email),
     };
+    match roster.iter_mut().find(|o| same(o)) {
+        Some(existing) => {
+            existing.commits += c.commits;
+            if c.first_commit < existing.first_commit {
+                existing.first_commit = c.first_commit.clone();
+            }
+            if c.last_commit > existing.last_commit {
+                existing.last_commit = c.last_commit.clone();
+            }
+        }
+        None => roster.push(c.clone()),
+    }
+}
+
+/// Folds the results of every repo listed in one sheet cell into a single
+/// row result. Keyword hits, files and contributors are combined and the
+/// integration is re-classified over the union; the latest commit wins. Each
+/// repo keeps a summary under `repos`. A single result is returned
+/// unchanged.
+pub fn merge_row_results(mut parts: Vec<GitHubUpdateData>) -> GitHubUpdateData {
+    if parts.len() == 1 {
+        return parts.remove(0);
+    }
 
-    formatted_result
+    let mut keyword_counts: RepoMap = RepoMap::new();
+    let mut contributors: Vec<Contributor> = Vec::new();
+    let mut files_processed = 0usize;
+    for part in &parts {
+        for (keyword, kc) in &part.keyword_counts {
+            let entry = keyword_counts.entry(keyword.clone()).or_default();
+            entry.count += kc.count;
+            entry.files.extend(kc.files.iter().cloned());
+            for (category, count) in &kc.categories {
+                *entry.categories.entry(*category).or_default() += count;
+            }
+        }
+        files_processed += part.files_processed.parse::<usize>().unwrap_or(0);
+
+        for c in &part.contributors {
+            merge_contributor(&mut contributors, c);
+        }
+    }
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
+
+    // RFC 3339 UTC timestamps compare correctly as strings.
+    let latest = parts
+        .iter()
+        .max_by(|a, b| a.commit_date.cmp(&b.commit_date))
+        .cloned()
+        .unwrap_or_default();
+    let join = |f: fn(&GitHubUpdateData) -> &str, sep: &str| {
+        let mut values: Vec<&str> = parts.iter().map(f).filter(|v| !v.is_empty()).collect();
+        values.dedup();
+        values.join(sep)
+    };
+    let integration = classify_integration(&keyword_counts);
+    let has_roster = parts.iter().any(|p| p.team_size.is_some());
+
+    GitHubUpdateData {
+        commit_sha: latest.commit_sha,
+        email: latest.email,
+        name: latest.name,
+        commit_date: latest.commit_date,
+        owner: join(|p| &p.owner, ", "),
+        repo_name: join(|p| &p.repo_name, ", "),
+        snapshot_url: join(|p| &p.snapshot_url, "\n"),
+        canonical_url: Some(join(|p| p.canonical_url.as_deref().unwrap_or(""), "\n"))
+            .filter(|u| !u.is_empty()),
+        origin: latest.origin,
+        file_types: latest.file_types,
+        files_processed: files_processed.to_string(),
+        keyword_matches: keyword_counts.len().to_string(),
+        keyword_counts,
+        integration_type: integration.integration_type,
+        integration_features: integration.features,
+        depth_score: integration.depth_score,
+        category_counts: integration.category_counts,
+        team_size: has_roster.then_some(contributors.len()),
+        contributors,
+        // Overrides are per row, so every part carries the same ones.
+        scan_overrides: parts.first().and_then(|p| p.scan_overrides.clone()),
+        repos: parts.iter().map(RepoSummary::new).collect(),
+        ..Default::default()
+    }
 }
 
 pub async fn check_api_request_limit(resp: &Response) {
@@ -93,3 +190,110 @@ pub async fn check_api_request_limit(resp: &Response) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{KeywordResult, PathCategory};
+
+    fn part(repo_name: &str, commit_date: &str, hits: &[(&str, usize)]) -> GitHubUpdateData {
+        GitHubUpdateData {
+            owner: "team".to_string(),
+            repo_name: repo_name.to_string(),
+            commit_sha: format!("{}-sha", repo_name),
+            commit_date: commit_date.to_string(),
+            snapshot_url: format!("https://github.com/team/{}/tree/x", repo_name),
+            files_processed: "10".to_string(),
+            keyword_matches: hits.len().to_string(),
+            keyword_counts: hits
+                .iter()
+                .map(|(kw, count)| {
+                    let result = KeywordResult {
+                        count: *count,
+                        files: vec![format!("{}/src/lib.rs", repo_name)],
+                        categories: BTreeMap::from([(PathCategory::Program, *count)]),
+                    };
+                    (kw.to_string(), result)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn contributor(login: &str, commits: usize) -> Contributor {
+        Contributor {
+            login: Some(login.to_string()),
+            name: login.to_string(),
+            email: format!("{}@example.com", login),
+            commits,
+            first_commit: "2024-01-01T00:00:00Z".to_string(),
+            last_commit: "2024-02-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn a_single_result_is_returned_unchanged() {
+        let one = part("web", "2024-01-01T00:00:00Z", &[("vrf", 1)]);
+        assert_eq!(merge_row_results(vec![one.clone()]), one);
+    }
+
+    #[test]
+    fn merges_keyword_hits_and_keeps_a_summary_per_repo() {
+        let web = part(
+            "web",
+            "2024-01-01T00:00:00Z",
+            &[("vrf", 2), ("session-keys", 1)],
+        );
+        let program = part("program", "2024-03-01T00:00:00Z", &[("vrf", 3)]);
+        let merged = merge_row_results(vec![web, program]);
+
+        assert_eq!(merged.repo_name, "web, program");
+        assert_eq!(merged.owner, "team");
+        assert_eq!(merged.commit_sha, "program-sha");
+        assert_eq!(merged.files_processed, "20");
+        assert_eq!(merged.keyword_matches, "2");
+
+        let vrf = &merged.keyword_counts["vrf"];
+        assert_eq!(vrf.count, 5);
+        assert_eq!(vrf.files, ["web/src/lib.rs", "program/src/lib.rs"]);
+        assert_eq!(vrf.categories[&PathCategory::Program], 5);
+        assert_eq!(merged.keyword_counts["session-keys"].count, 1);
+
+        let repos: Vec<(&str, &str, Option<&usize>)> = merged
+            .repos
+            .iter()
+            .map(|r| {
+                (
+                    r.repo_name.as_str(),
+                    r.keyword_matches.as_str(),
+                    r.keyword_counts.get("vrf"),
+                )
+            })
+            .collect();
+        assert_eq!(repos, [("web", "2", Some(&2)), ("program", "1", Some(&3))]);
+    }
+
+    #[test]
+    fn merges_rosters_by_login() {
+        let mut web = part("web", "2024-01-01T00:00:00Z", &[]);
+        web.contributors = vec![contributor("ann", 3), contributor("bob", 1)];
+        web.team_size = Some(2);
+        let mut program = part("program", "2024-01-02T00:00:00Z", &[]);
+        program.contributors = vec![contributor("Ann", 4)];
+        program.team_size = Some(1);
+
+        let merged = merge_row_results(vec![web, program.clone()]);
+        let roster: Vec<(Option<&str>, usize)> = merged
+            .contributors
+            .iter()
+            .map(|c| (c.login.as_deref(), c.commits))
+            .collect();
+        assert_eq!(roster, [(Some("ann"), 7), (Some("bob"), 1)]);
+        assert_eq!(merged.team_size, Some(2));
+
+        // No part read a roster: the merged row has no team size either.
+        program.team_size = None;
+        let rosterless = merge_row_results(vec![part("web", "", &[]), program]);
+        assert_eq!(rosterless.team_size, None);
+    }
+}
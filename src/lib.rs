@@ -1,5 +1,21 @@
+pub mod a1;
+pub mod cell_format;
+pub mod classify;
+pub mod details;
 pub mod elk;
+pub mod file_store;
+pub mod forge;
 pub mod github;
+pub mod header_rules;
 pub mod helper;
+pub mod local;
+pub mod mirror;
+pub mod quota;
+pub mod registry;
+pub mod row_locator;
+pub mod rows;
+pub mod search_tab;
+pub mod sheet_store;
 pub mod sheets;
 pub mod types;
+pub mod workspace;
@@ -0,0 +1,301 @@
+use crate::forge::{Forge, ForgeKind, ForgeRepo, ForgeRepoInfo, LookupError};
+use crate::github::{is_bot, CONTRIBUTOR_COMMIT_PAGES};
+use crate::types::{Contributor, RepoStatus};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Directories skipped when walking a plain checkout on disk: VCS metadata
+/// and dependency/build output that a hosted tree wouldn't contain.
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target"];
+
+/// Scans repos already on disk with no API at all. A git repo (bare or
+/// with a worktree) is read from its objects at the requested ref; any other
+/// directory is walked as-is. Disk and git reads run on the blocking pool.
+pub struct LocalForge {
+    root: PathBuf,
+    /// `None` for a plain directory.
+    repo: Option<gix::ThreadSafeRepository>,
+}
+
+impl LocalForge {
+    pub fn open(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let repo = gix::open(&root).ok().map(|r| r.into_sync());
+        Self { root, repo }
+    }
+
+    /// Runs `read` on the blocking pool with the root and, for a git repo,
+    /// a thread-local handle; `None` if the task panicked.
+    async fn blocking<T, F>(&self, read: F) -> Option<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Path, Option<gix::Repository>) -> T + Send + 'static,
+    {
+        let root = self.root.clone();
+        let repo = self.repo.clone();
+        tokio::task::spawn_blocking(move || read(&root, repo.map(|r| r.to_thread_local())))
+            .await
+            .ok()
+    }
+}
+
+/// The repo at a path on disk (`path` or `path#ref`, the ref naming the
+/// branch, tag or commit to scan), resolved against the current directory.
+/// `None` when the path doesn't exist. Only `SCRAPER_LOCAL_PATHS` names
+/// local repos: `classify_forge_url` never yields one, so sheet cells and
+/// registry data can't point the scanner at the runner's disk.
+pub fn local_repo(spec: &str) -> Option<ForgeRepo> {
+    let (path, git_ref) = match spec.trim().rsplit_once('#') {
+        Some((path, git_ref)) => (path, Some(git_ref)),
+        None => (spec.trim(), None),
+    };
+    let path = std::fs::canonicalize(path).ok()?;
+    let owner = path.parent()?.to_string_lossy().into_owned();
+    let repo_name = path.file_name()?.to_string_lossy().into_owned();
+    Some(ForgeRepo {
+        kind: ForgeKind::Local,
+        host: String::new(),
+        owner,
+        repo_name,
+        git_ref: git_ref.filter(|r| !r.is_empty()).map(str::to_string),
+    })
+}
+
+/// `HEAD` for an empty ref, so callers can pass the repo's default through.
+fn rev(git_ref: &str) -> &str {
+    if git_ref.is_empty() {
+        "HEAD"
+    } else {
+        git_ref
+    }
+}
+
+fn commit_at<'r>(repo: &'r gix::Repository, git_ref: &str) -> Option<gix::Commit<'r>> {
+    repo.rev_parse_single(rev(git_ref))
+        .ok()?
+        .object()
+        .ok()?
+        .peel_to_commit()
+        .ok()
+}
+
+fn rfc3339(seconds: i64) -> Option<String> {
+    Some(
+        chrono::DateTime::from_timestamp(seconds, 0)?
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    )
+}
+
+/// Contributor roster from the same window of history the GitHub roster
+/// covers. Without an API there are no logins, so authors are grouped by
+/// lowercased email.
+fn history_contributors(repo: &gix::Repository, git_ref: &str) -> Option<Vec<Contributor>> {
+    let tip = commit_at(repo, git_ref)?;
+    let mut by_email: HashMap<String, Contributor> = HashMap::new();
+    let walk = tip
+        .ancestors()
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(
+            Default::default(),
+        ))
+        .all()
+        .ok()?;
+    for info in walk.take(CONTRIBUTOR_COMMIT_PAGES * 100) {
+        let Ok(info) = info else {
+            break;
+        };
+        let Ok(commit) = info.object() else {
+            continue;
+        };
+        let Ok(author) = commit.author() else {
+            continue;
+        };
+        let (name, email) = (author.name.to_string(), author.email.to_string());
+        if is_bot(None, None, &name, &email) {
+            continue;
+        }
+        let date = author
+            .time()
+            .ok()
+            .and_then(|t| rfc3339(t.seconds))
+            .unwrap_or_default();
+        let entry = by_email
+            .entry(email.to_lowercase())
+            .or_insert_with(|| Contributor {
+                name,
+                email,
+                first_commit: date.clone(),
+                last_commit: date.clone(),
+                ..Default::default()
+            });
+        entry.commits += 1;
+        if !date.is_empty() && (entry.first_commit.is_empty() || date < entry.first_commit) {
+            entry.first_commit = date.clone();
+        }
+        if date > entry.last_commit {
+            entry.last_commit = date;
+        }
+    }
+    let mut contributors: Vec<Contributor> = by_email.into_values().collect();
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
+    Some(contributors)
+}
+
+/// Repo-relative paths of every file under `dir`, skipping `SKIPPED_DIRS`.
+fn walk(root: &Path, dir: &Path, paths: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                walk(root, &path, paths);
+            }
+        } else if file_type.is_file() {
+            if let Ok(relative) = path.strip_prefix(root) {
+                paths.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Forge for LocalForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Local
+    }
+
+    fn file_url(&self, _owner: &str, _repo: &str, _git_ref: &str, path: &str) -> String {
+        format!("file://{}/{}", self.root.display(), path)
+    }
+
+    fn clone_url(&self, _owner: &str, _repo: &str) -> String {
+        format!("file://{}", self.root.display())
+    }
+
+    fn snapshot_url(&self, _owner: &str, _repo: &str, commit_sha: &str) -> String {
+        if commit_sha.is_empty() {
+            format!("file://{}", self.root.display())
+        } else {
+            format!("file://{}#{}", self.root.display(), commit_sha)
+        }
+    }
+
+    async fn repo_info(
+        &self,
+        _client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError> {
+        let full_name = format!("{}/{}", owner, repo);
+        self.blocking(move |root, repo| {
+            if !root.is_dir() {
+                return Err(LookupError::Status(RepoStatus::NotFound));
+            }
+            let mut info = ForgeRepoInfo {
+                full_name,
+                web_url: format!("file://{}", root.display()),
+                ..Default::default()
+            };
+            if let Some(repo) = repo {
+                info.default_branch = match repo.head_name() {
+                    Ok(Some(name)) => name.shorten().to_string(),
+                    _ => "HEAD".to_string(),
+                };
+                info.empty = commit_at(&repo, "HEAD").is_none();
+            }
+            Ok(info)
+        })
+        .await
+        .unwrap_or(Err(LookupError::Unknown))
+    }
+
+    async fn list_tree(
+        &self,
+        _client: &Client,
+        _owner: &str,
+        _repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>> {
+        let git_ref = git_ref.to_string();
+        self.blocking(move |root, repo| {
+            let Some(repo) = repo else {
+                let mut paths = Vec::new();
+                walk(root, root, &mut paths);
+                paths.sort();
+                return Some(paths);
+            };
+            let tree = commit_at(&repo, &git_ref)?.tree().ok()?;
+            let entries = tree.traverse().breadthfirst.files().ok()?;
+            Some(
+                entries
+                    .into_iter()
+                    .filter(|e| e.mode.is_blob())
+                    .map(|e| e.filepath.to_string())
+                    .collect(),
+            )
+        })
+        .await?
+    }
+
+    async fn fetch_file(
+        &self,
+        _client: &Client,
+        _owner: &str,
+        _repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String> {
+        let spec = format!("{}:{}", rev(git_ref), path);
+        let path = path.to_string();
+        self.blocking(move |root, repo| {
+            let Some(repo) = repo else {
+                let bytes = std::fs::read(root.join(&path)).ok()?;
+                return Some(String::from_utf8_lossy(&bytes).into_owned());
+            };
+            let blob = repo.rev_parse_single(spec.as_str()).ok()?.object().ok()?;
+            Some(String::from_utf8_lossy(&blob.data).into_owned())
+        })
+        .await?
+    }
+
+    async fn last_commit(
+        &self,
+        _client: &Client,
+        _owner: &str,
+        _repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)> {
+        let git_ref = git_ref.to_string();
+        self.blocking(move |_, repo| {
+            let repo = repo?;
+            let commit = commit_at(&repo, &git_ref)?;
+            let author = commit.author().ok()?;
+            let date = rfc3339(commit.time().ok()?.seconds)?;
+            Some((
+                commit.id.to_string(),
+                date,
+                author.email.to_string(),
+                author.name.to_string(),
+            ))
+        })
+        .await?
+    }
+
+    async fn contributors(
+        &self,
+        _client: &Client,
+        _owner: &str,
+        _repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<Contributor>> {
+        let git_ref = git_ref.to_string();
+        self.blocking(move |_, repo| history_contributors(&repo?, &git_ref))
+            .await?
+    }
+}
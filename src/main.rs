@@ -1,19 +1,60 @@
 use anyhow::Result;
 use dotenvy::dotenv;
+use integration_validation::a1::{column_letter_to_number, column_number_to_letter};
+use integration_validation::cell_format::{highlights_enabled, literal};
+use integration_validation::classify::{load_path_rules, PathRule};
+use integration_validation::details::overflow_result;
 use integration_validation::elk::{es_document_exists, ingest_via_logstash};
+use integration_validation::forge::{
+    classify_forge_url_detailed, extract_repo_urls, ForgeRepo, ForgeUrlType,
+};
 use integration_validation::github::{
-    classify_github_url, fetch_user_repos, handle_github_repo_url, search_github_repos,
-    GitHubUrlType,
+    describe_url_fixes, fetch_user_repos, handle_github_repo_url, scan_forge_repo,
+    search_github_repos,
+};
+use integration_validation::header_rules::{explain_columns_requested, HeaderRules};
+use integration_validation::helper::merge_row_results;
+use integration_validation::local::local_repo;
+use integration_validation::registry::discover_registry_repos;
+use integration_validation::row_locator::RowLocator;
+use integration_validation::rows::{named_cells, rows_from_columns, struct_fields, SheetRow};
+use integration_validation::search_tab::{
+    read_search_rows, SearchTab, CANONICAL_URL_HEADER, FIRST_SEEN_HEADER, LAST_SEEN_HEADER,
+    SEARCH_STATUS_HEADER,
 };
+use integration_validation::sheet_store::{with_flush_result, SheetStore};
 use integration_validation::sheets::{
-    clean_column_names, column_letter_to_number, column_number_to_letter, find_rule_columns,
-    init_sheets, read_columns_from_sheet, resolve_or_append_columns, row_cell_still_matches,
+    apply_cell_links, clean_column_names, find_rule_columns, open_sheet_store, read_column_links,
+    read_columns_from_sheet, rename_column, resolve_or_append_columns, write_entered_cells,
     write_named_cells, write_row, write_to_cell,
 };
-use integration_validation::types::{Config, GitHubUpdateData};
+use integration_validation::types::{
+    result_column_formats, Config, GitHubUpdateData, ScanOverrides, ScraperResultRow,
+    SubmissionRow, REPO_METADATA_HEADERS,
+};
 use reqwest::Client;
 use std::{collections::HashMap, env, fs::File, io::Write, vec};
 
+const ALLOWED_EXTENSIONS: [&str; 4] = [".toml", ".json", ".rs", ".ts"];
+const KEYWORDS: [&str; 13] = [
+    "ephemeral-rollups-sdk",
+    "ephemeral-rollups-kit",
+    "ephemeral-vrf-sdk",
+    "#[ephemeral]",
+    "#[commit]",
+    "#[delegate]",
+    "delegate_account",
+    "undelegate_account",
+    "MagicIntentBundleBuilder",
+    "payments.magicblock.app",
+    "await getAuthToken",
+    "session-keys",
+    "SPLxh1LVZzEkX99H6rqYizhytLWPZVV296zyYDPagv2",
+];
+
+const URL_NORMALIZATION_HEADER: &str = "Scraper URL Normalization";
+const REPO_STATUS_HEADER: &str = "Repo Status";
+
 fn env_bool(name: &str) -> Option<bool> {
     env::var(name)
         .ok()
@@ -28,16 +69,54 @@ fn env_nonempty(name: &str) -> Option<String> {
     env::var(name).ok().filter(|v| !v.trim().is_empty())
 }
 
-fn row_has_value(
-    columns: &HashMap<String, Vec<String>>,
+fn row_value<'a>(
+    columns: &'a HashMap<String, Vec<String>>,
     column_name: &str,
     row_index: usize,
-) -> bool {
+) -> &'a str {
     columns
         .get(column_name)
         .and_then(|values| values.get(row_index))
-        .map(|value| !value.trim().is_empty())
-        .unwrap_or(false)
+        .map(|value| value.trim())
+        .unwrap_or("")
+}
+
+fn row_has_value(
+    columns: &HashMap<String, Vec<String>>,
+    column_name: &str,
+    row_index: usize,
+) -> bool {
+    !row_value(columns, column_name, row_index).is_empty()
+}
+
+/// Pairs the result columns (resolved from `headers`, in order) with the
+/// values written for one scanned repo, entered for `write_entered_cells`.
+/// Optional blocks such as `REPO_METADATA_HEADERS` only get a value when
+/// their header was requested. JSON too long for one cell goes to the
+/// details tab, leaving a linked summary in the row.
+async fn result_cells<'a>(
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    cols: &'a [String],
+    headers: &[String],
+    update_data: &GitHubUpdateData,
+) -> Result<Vec<(&'a str, String)>> {
+    let mut row = ScraperResultRow::for_sheet(update_data)?;
+    if let Some(summary) = overflow_result(sheets, spreadsheet_id, update_data).await? {
+        row.result_json = summary;
+    }
+    named_cells(&row, cols, headers)
+}
+
+/// The column `header` was resolved to (`cols` and `headers` in the same
+/// order).
+fn column_for<'a>(cols: &'a [String], headers: &[String], header: &str) -> Result<&'a str> {
+    headers
+        .iter()
+        .position(|h| h == header)
+        .and_then(|i| cols.get(i))
+        .map(String::as_str)
+        .ok_or_else(|| anyhow::anyhow!("no column resolved for '{}'", header))
 }
 
 fn scraper_modes() -> Result<(bool, bool)> {
@@ -58,10 +137,15 @@ fn scraper_modes() -> Result<(bool, bool)> {
                 run_search = true;
                 run_sheets = true;
             }
+            // Only the paths in SCRAPER_LOCAL_PATHS.
+            "local" | "local-only" => {
+                run_search = false;
+                run_sheets = false;
+            }
             "" => {}
             other => {
                 anyhow::bail!(
-                    "Invalid SCRAPER_MODE '{}'. Use search, sheet, or all.",
+                    "Invalid SCRAPER_MODE '{}'. Use search, sheet, local, or all.",
                     other
                 );
             }
@@ -79,11 +163,39 @@ async fn main() -> Result<()> {
     let (run_search, run_sheets) = scraper_modes()?;
     println!("Scraper mode: search={}, sheets={}", run_search, run_sheets);
 
-    let github_token = env::var("PRIVATE_GITHUB_TOKEN")?;
+    // Local scans never touch the GitHub API, so a local-only run needs no token.
+    let github_token = if run_search || run_sheets {
+        env::var("PRIVATE_GITHUB_TOKEN")?
+    } else {
+        env::var("PRIVATE_GITHUB_TOKEN").unwrap_or_default()
+    };
+    // Checkouts or git repos on disk (`path` or `path#ref`) to scan offline.
+    let local_paths: Vec<String> = env_nonempty("SCRAPER_LOCAL_PATHS")
+        .map(|v| {
+            v.split(',')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
     let default_sheet_name = env_nonempty("SCRAPER_SHEET_NAME")
         .or_else(|| env_nonempty("SHEET_NAME"))
         .unwrap_or_else(|| "Founders Camp (BUILD)".to_string());
     let continue_from_results = env_bool("SCRAPER_CONTINUE").unwrap_or(false);
+    let include_vendored = env_bool("SCRAPER_INCLUDE_VENDORED").unwrap_or(false);
+    let write_repo_metadata = env_bool("SCRAPER_REPO_METADATA_COLUMNS").unwrap_or(false);
+    let write_canonical_url = env_bool("SCRAPER_CANONICAL_URL_COLUMN").unwrap_or(false);
+    let path_rules = load_path_rules()?;
+    // Headers (or field names such as `snapshot_url`) whose cells should be
+    // read as their hyperlink target when the display text isn't a URL.
+    let link_columns: Vec<String> = env_nonempty("SCRAPER_LINK_COLUMNS")
+        .map(|v| {
+            v.split(',')
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
 
     let config = Config {
         spreadsheet_id: env_nonempty("SPREADSHEET_ID")
@@ -121,51 +233,46 @@ async fn main() -> Result<()> {
         search_write_sheet_name: "Search".to_string(),
     };
 
-    const ALLOWED_EXTENSIONS: [&str; 4] = [".toml", ".json", ".rs", ".ts"];
-    const KEYWORDS: [&str; 11] = [
-        "ephemeral-rollups-sdk",
-        "ephemeral-rollups-kit",
-        "ephemeral-vrf-sdk",
-        "#[ephemeral]",
-        "#[commit]",
-        "#[delegate]",
-        "delegate_account",
-        "undelegate_account",
-        "MagicIntentBundleBuilder",
-        "payments.magicblock.app",
-        "await getAuthToken",
-    ];
-
     // Resolve write locations by header name. If the matching config field is
     // empty, the resolver finds the existing anchor header or appends the
     // block at the next empty column. If the config field is set explicitly,
     // we honor that position and (re-)write the headers there.
-    let update_data_headers: Vec<String> = vec![
+    let mut update_data_headers: Vec<String> = vec![
         "Scraper Result (JSON)".to_string(),
         "Scraper Keyword Matches".to_string(),
         "Scraper Snapshot URL".to_string(),
+        "Scraper Integration Type".to_string(),
+        "Scraper Depth Score".to_string(),
+        "Scraper Team Size".to_string(),
+        URL_NORMALIZATION_HEADER.to_string(),
+        REPO_STATUS_HEADER.to_string(),
     ];
     let user_headers: Vec<String> = vec!["Owner".to_string(), "Source Sheet".to_string()];
-    let search_headers: Vec<String> = vec![
+    let mut search_headers: Vec<String> = vec![
         "Repo URL".to_string(),
         "Scraper Result (JSON)".to_string(),
         "Scraper Keyword Matches".to_string(),
         "Scraper Snapshot URL".to_string(),
+        "Scraper Integration Type".to_string(),
+        "Scraper Depth Score".to_string(),
+        "Scraper Team Size".to_string(),
+        URL_NORMALIZATION_HEADER.to_string(),
+        REPO_STATUS_HEADER.to_string(),
     ];
-
-    // ES fields
-    let fields = vec![
-        "snapshot_url",
-        "presentation_link",
-        "technical_link",
-        "files_processed",
-        "location",
-        "tracks",
-        "contact",
-    ];
+    if write_repo_metadata {
+        update_data_headers.extend(REPO_METADATA_HEADERS.iter().map(|h| h.to_string()));
+        search_headers.extend(REPO_METADATA_HEADERS.iter().map(|h| h.to_string()));
+    }
+    if write_canonical_url {
+        update_data_headers.push(CANONICAL_URL_HEADER.to_string());
+        search_headers.push(CANONICAL_URL_HEADER.to_string());
+    }
+    // Last, so fixed-column Search tabs keep their existing layout.
+    search_headers
+        .extend([FIRST_SEEN_HEADER, LAST_SEEN_HEADER, SEARCH_STATUS_HEADER].map(String::from));
 
     let sheets = if run_sheets {
-        let sheets = init_sheets().await?;
+        let sheets = open_sheet_store().await?;
         println!("Initialized Google Sheets API client.");
         Some(sheets)
     } else {
@@ -173,27 +280,34 @@ async fn main() -> Result<()> {
         None
     };
 
-    let rename_rules: [(Vec<&str>, &str); 10] = [
-        (vec!["github", "repo", "gh"], "snapshot_url"),
-        (vec!["presentation"], "presentation_link"),
-        (vec!["website"], "website_link"),
-        (vec!["technical", "demo"], "technical_link"),
-        (vec!["files_processed"], "files_processed"),
-        (vec!["location", "country", "residence"], "location"),
-        (vec!["track"], "tracks"),
-        (vec!["contact", "telegram", "team", "twitter"], "contact"),
-        (vec!["wallet", "solana"], "wallet"),
-        (vec!["twitter", "social link"], "social_link"),
-    ];
+    let header_rules = HeaderRules::load()?;
+    // The submission sheet's columns, for lookups outside `read_rows`.
+    let submission_rules = header_rules.with_defaults(SubmissionRow::ALIASES)?;
+    if explain_columns_requested() {
+        let Some(sheets) = sheets.as_deref() else {
+            anyhow::bail!("--explain-columns reads the sheet's headers; enable RUN_SHEETS");
+        };
+        let headers = sheets
+            .read_header(&config.spreadsheet_id, &config.read_sheet_name)
+            .await?;
+        println!("Columns of '{}':", config.read_sheet_name);
+        println!(
+            "{}",
+            submission_rules.explain(&headers, struct_fields::<SubmissionRow>())
+        );
+        return Ok(());
+    }
 
     let mut update_data_cols: Vec<String> = Vec::new();
     let mut user_write_cols: Vec<String> = Vec::new();
     let mut search_cols: Vec<String> = Vec::new();
+    let mut search_rows: HashMap<String, (usize, String)> = HashMap::new();
     let mut sheet_columns: HashMap<String, Vec<String>> = HashMap::new();
-    let mut cleaned_columns: HashMap<String, Vec<String>> = HashMap::new();
+    let mut submissions: Vec<SubmissionRow> = Vec::new();
     let mut repos: Vec<String> = Vec::new();
+    let mut repo_cells: Vec<String> = Vec::new();
 
-    if let Some(sheets) = sheets.as_ref() {
+    if let Some(sheets) = sheets.as_deref() {
         // For each header, the resolver returns either the column letter where
         // it already lives, or a freshly appended column. When the matching
         // config field is set explicitly, we keep the old behavior: lay out the
@@ -216,7 +330,7 @@ async fn main() -> Result<()> {
                 update_data_headers.clone(),
             )
             .await?;
-            let start = column_letter_to_number(&config.update_data_col);
+            let start = column_letter_to_number(&config.update_data_col)?;
             (0..update_data_headers.len())
                 .map(|i| column_number_to_letter(start + i))
                 .collect()
@@ -240,13 +354,16 @@ async fn main() -> Result<()> {
                 user_headers.clone(),
             )
             .await?;
-            let start = column_letter_to_number(&config.user_write_col);
+            let start = column_letter_to_number(&config.user_write_col)?;
             (0..user_headers.len())
                 .map(|i| column_number_to_letter(start + i))
                 .collect()
         };
 
         if run_search {
+            sheets
+                .ensure_sheet(&config.spreadsheet_id, &config.search_write_sheet_name)
+                .await?;
             search_cols = if config.search_update_data_col.trim().is_empty() {
                 resolve_or_append_columns(
                     sheets,
@@ -265,11 +382,18 @@ async fn main() -> Result<()> {
                     search_headers.clone(),
                 )
                 .await?;
-                let start = column_letter_to_number(&config.search_update_data_col);
+                let start = column_letter_to_number(&config.search_update_data_col)?;
                 (0..search_headers.len())
                     .map(|i| column_number_to_letter(start + i))
                     .collect()
             };
+            search_rows = read_search_rows(
+                sheets,
+                &config.spreadsheet_id,
+                &config.search_write_sheet_name,
+                &header_rules,
+            )
+            .await?;
         }
 
         println!(
@@ -277,6 +401,24 @@ async fn main() -> Result<()> {
             update_data_cols, user_write_cols, search_cols
         );
 
+        let highlight = highlights_enabled();
+        sheets
+            .format_columns(
+                &config.spreadsheet_id,
+                &config.write_sheet_name,
+                &result_column_formats(&update_data_cols, &update_data_headers, highlight)?,
+            )
+            .await?;
+        if run_search {
+            sheets
+                .format_columns(
+                    &config.spreadsheet_id,
+                    &config.search_write_sheet_name,
+                    &result_column_formats(&search_cols, &search_headers, highlight)?,
+                )
+                .await?;
+        }
+
         sheet_columns = read_columns_from_sheet(
             sheets,
             &config.spreadsheet_id,
@@ -285,17 +427,85 @@ async fn main() -> Result<()> {
         )
         .await?;
 
-        // Extract sheet columns and normalize.
-        cleaned_columns = clean_column_names(sheet_columns.clone(), &rename_rules);
-        repos = cleaned_columns
-            .get("snapshot_url")
-            .cloned()
+        // The display values are what the row-drift check re-reads later,
+        // so keep them before link targets are swapped in.
+        repo_cells = clean_column_names(sheet_columns.clone(), &submission_rules)
+            .remove("snapshot_url")
             .unwrap_or_default();
+
+        // Opted-in columns: cells showing "GitHub" or "our repo" as a link
+        // are read as the URL behind them.
+        let link_headers: Vec<String> = sheet_columns
+            .keys()
+            .filter(|h| {
+                link_columns.iter().any(|c| {
+                    c.eq_ignore_ascii_case(h) || rename_column(h, &submission_rules) == c.as_str()
+                })
+            })
+            .cloned()
+            .collect();
+        if !link_headers.is_empty() {
+            let links = read_column_links(
+                sheets,
+                &config.spreadsheet_id,
+                &config.read_sheet_name,
+                &link_headers,
+            )
+            .await?;
+            let replaced = apply_cell_links(&mut sheet_columns, &links);
+            println!(
+                "🔗 Read link targets for {:?}: {} cell(s) replaced.",
+                link_headers, replaced
+            );
+        }
+
+        // Extract sheet columns and normalize.
+        submissions = rows_from_columns(&sheet_columns, &header_rules)?;
+        repos = submissions.iter().map(|r| r.snapshot_url.clone()).collect();
     }
 
     let client = Client::new();
     let mut final_results: Vec<GitHubUpdateData> = Vec::new();
 
+    // Local results only go to results.json: there is no sheet row or search
+    // tab entry to write them to.
+    for path in &local_paths {
+        let Some(target) = local_repo(path) else {
+            println!("❌ Local repo not found: {}", path);
+            continue;
+        };
+        let repo_url = target.web_url();
+        println!("Processing {} ...", repo_url);
+        let (update_data, status, error_message) = scan_forge_repo(
+            &client,
+            &target,
+            &github_token,
+            &KEYWORDS,
+            &ALLOWED_EXTENSIONS,
+            254,
+            "Local",
+            &path_rules,
+            include_vendored,
+            &ScanOverrides::default(),
+        )
+        .await?;
+        match error_message {
+            Some(error) => println!(
+                "❌ Error processing {} ({}): {}",
+                repo_url,
+                status.map(|s| s.label()).unwrap_or("unknown"),
+                error
+            ),
+            None => {
+                println!(
+                    "✅ {}: {} keyword match(es)",
+                    repo_url, update_data.keyword_matches
+                );
+                final_results.push(update_data);
+            }
+        }
+    }
+
     let queries = [
         "\"ephemeral-rollups-sdk\" in:file filename:package.json",
         "\"ephemeral-rollups-kit\" in:file filename:package.json",
@@ -303,28 +513,143 @@ async fn main() -> Result<()> {
         "\"ephemeral-rollups-sdk\" in:file filename:Cargo.toml",
         "\"ephemeral-rollups-pinocchio\" in:file filename:Cargo.toml",
     ];
-    let filtered_repo_urls: Vec<String> = if run_search {
+    let mut filtered_repo_urls: Vec<String> = if run_search {
         search_github_repos(&queries, &github_token).await?
     } else {
         Vec::<String>::new()
     };
+    // Reverse dependencies on crates.io and npm find repos code search
+    // hasn't indexed; they go through the same Search-tab pipeline.
+    if run_search && env_bool("SCRAPER_REGISTRY_DISCOVERY").unwrap_or(false) {
+        for repo_url in discover_registry_repos(&client).await {
+            if !filtered_repo_urls
+                .iter()
+                .any(|u| u.eq_ignore_ascii_case(&repo_url))
+            {
+                filtered_repo_urls.push(repo_url);
+            }
+        }
+    }
+
+    let scanned = run_scan(
+        &ScanContext {
+            client: &client,
+            github_token: &github_token,
+            config: &config,
+            sheets: sheets.as_deref(),
+            path_rules: &path_rules,
+            include_vendored,
+            continue_from_results,
+            filtered_repo_urls: &filtered_repo_urls,
+            search_headers: &search_headers,
+            search_cols: &search_cols,
+            search_rows: &search_rows,
+            update_data_headers: &update_data_headers,
+            update_data_cols: &update_data_cols,
+            user_write_cols: &user_write_cols,
+            repos: &repos,
+            repo_cells: &repo_cells,
+            submissions: &submissions,
+            sheet_columns: &sheet_columns,
+            submission_rules: &submission_rules,
+        },
+        &mut final_results,
+    )
+    .await;
+    // Writes queued behind the quota still go out when a repo or row fails.
+    let flushed = match sheets.as_deref() {
+        Some(sheets) => sheets.flush().await,
+        None => Ok(()),
+    };
+    with_flush_result(scanned, flushed)?;
+
+    // Save all results
+    let json = serde_json::to_string_pretty(&final_results)?;
+    File::create("results.json")?.write_all(json.as_bytes())?;
+
+    println!("✅ All results saved.");
+
+    Ok(())
+}
+
+/// What `main` resolved before scanning: settings, the read sheet's rows and
+/// the columns results are written to.
+struct ScanContext<'a> {
+    client: &'a Client,
+    github_token: &'a str,
+    config: &'a Config,
+    sheets: Option<&'a dyn SheetStore>,
+    path_rules: &'a [PathRule],
+    include_vendored: bool,
+    continue_from_results: bool,
+    filtered_repo_urls: &'a [String],
+    search_headers: &'a [String],
+    search_cols: &'a [String],
+    search_rows: &'a HashMap<String, (usize, String)>,
+    update_data_headers: &'a [String],
+    update_data_cols: &'a [String],
+    user_write_cols: &'a [String],
+    repos: &'a [String],
+    repo_cells: &'a [String],
+    submissions: &'a [SubmissionRow],
+    sheet_columns: &'a HashMap<String, Vec<String>>,
+    submission_rules: &'a HeaderRules,
+}
+
+/// Scans the Search tab's repos, then every row of the read sheet, writing
+/// each result as it's ready. Results for `results.json` are collected in
+/// `final_results`.
+async fn run_scan(ctx: &ScanContext<'_>, final_results: &mut Vec<GitHubUpdateData>) -> Result<()> {
+    let ScanContext {
+        client,
+        github_token,
+        config,
+        sheets,
+        path_rules,
+        include_vendored,
+        continue_from_results,
+        filtered_repo_urls,
+        search_headers,
+        search_cols,
+        search_rows,
+        update_data_headers,
+        update_data_cols,
+        user_write_cols,
+        repos,
+        repo_cells,
+        submissions,
+        sheet_columns,
+        submission_rules,
+    } = *ctx;
 
-    let mut search_row_idx = 2;
-    if filtered_repo_urls.len() > 0 {
+    if !filtered_repo_urls.is_empty() {
         println!("Processing Public Search ...");
-        for repo_url in &filtered_repo_urls {
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let mut search_tab = SearchTab::new(
+            &config.search_write_sheet_name,
+            search_headers,
+            search_cols,
+            search_rows,
+            &today,
+        );
+        for repo_url in filtered_repo_urls {
             println!("Processing {} ...", repo_url);
 
-            let (update_data, _error_message) = handle_github_repo_url(
-                &client,
+            let (update_data, _status, _error_message) = handle_github_repo_url(
+                client,
                 repo_url,
-                &github_token,
+                github_token,
                 &KEYWORDS,
                 &ALLOWED_EXTENSIONS,
                 254,
                 "Public Search",
+                path_rules,
+                include_vendored,
+                &ScanOverrides::default(),
             )
             .await?;
+            let canonical_url = update_data.canonical_url.as_deref();
+            search_tab.see(repo_url, canonical_url);
 
             // Ingest data into elasticsearch
             if update_data.commit_sha.is_empty() {
@@ -354,35 +679,48 @@ async fn main() -> Result<()> {
                 final_results.push(update_data.clone());
             }
 
-            if let Some(sheets) = sheets.as_ref() {
-                println!("Writing {} row", search_row_idx);
-                write_named_cells(
+            if let Some(sheets) = sheets {
+                let cells = result_cells(
                     sheets,
                     &config.spreadsheet_id,
-                    &config.search_write_sheet_name,
-                    search_row_idx,
-                    &[
-                        (&search_cols[0], repo_url.clone()),
-                        (&search_cols[1], serde_json::to_string(&update_data)?),
-                        (&search_cols[2], update_data.keyword_matches.to_string()),
-                        (&search_cols[3], update_data.snapshot_url.clone()),
-                    ],
+                    &search_cols[1..],
+                    &search_headers[1..],
+                    &update_data,
                 )
                 .await?;
-
-                println!(
-                    "✅ Row {} updated in {}",
-                    search_row_idx, &config.search_write_sheet_name,
-                );
+                search_tab
+                    .write(
+                        sheets,
+                        &config.spreadsheet_id,
+                        repo_url,
+                        canonical_url,
+                        cells,
+                    )
+                    .await?;
             } else {
                 println!("Skipping Search sheet write for {}", repo_url);
             }
+        }
+
+        if let Some(sheets) = sheets {
+            search_tab
+                .mark_vanished(sheets, &config.spreadsheet_id)
+                .await?;
+            let new_repos = search_tab.new_repos();
 
-            search_row_idx += 1;
+            println!(
+                "🆕 {} newly discovered repo(s) in {}{}",
+                new_repos.len(),
+                &config.search_write_sheet_name,
+                if new_repos.is_empty() { "" } else { ":" }
+            );
+            for repo_url in new_repos {
+                println!("   {}", repo_url);
+            }
         }
     }
 
-    if let Some(sheets) = sheets.as_ref() {
+    if let Some(sheets) = sheets {
         // Going through Sheets
         let row_idx = 2;
         let row_skip: usize = env::var("ROW_SKIP")
@@ -406,25 +744,27 @@ async fn main() -> Result<()> {
         }
 
         // Columns whose header maps to snapshot_url (e.g. every 'GitHub
-        // Repo' column). Before writing a result by absolute row number,
-        // we re-read these cells to confirm the row still holds the URL we
-        // scraped — rows can move if the sheet is sorted or rows are
-        // inserted/deleted while a long scrape is running.
-        let url_check_cols = find_rule_columns(
+        // Repo' column). Results are written to the row that holds the
+        // scraped URL at write time — rows can move if the sheet is sorted
+        // or rows are inserted/deleted while a long scrape is running.
+        let url_key_cols = find_rule_columns(
             sheets,
             &config.spreadsheet_id,
             &config.read_sheet_name,
-            &rename_rules,
+            submission_rules,
             "snapshot_url",
         )
         .await?;
+        let row_locator = RowLocator::new(&config.read_sheet_name, url_key_cols);
 
         for (data_row_idx, repo_url) in repos.iter().enumerate().skip(row_skip) {
             let row_reading = row_idx + data_row_idx;
             let repo_url = repo_url.trim();
-            let cell_url = repo_url;
-            if continue_from_results
-                && row_has_value(&sheet_columns, &continue_column, data_row_idx)
+            let cell_url = repo_cells
+                .get(data_row_idx)
+                .map(|c| c.trim())
+                .unwrap_or(repo_url);
+            if continue_from_results && row_has_value(sheet_columns, &continue_column, data_row_idx)
             {
                 println!(
                     "Skipping row {} because '{}' is already filled.",
@@ -444,190 +784,217 @@ async fn main() -> Result<()> {
                 "Reading row {} in {}: {}",
                 row_reading, config.read_sheet_name, repo_url
             );
-            match classify_github_url(&repo_url) {
-                // If GitHub User
-                GitHubUrlType::User(owner) => {
-                    // Could be a user or an organization
-                    println!("👤 Detected GitHub user/org: {}", owner);
-                    let (repos, total) = fetch_user_repos(&client, &owner, &github_token, 10).await;
-                    println!("🔍 Found {} repos for {}", total, owner);
-                    let mut matched_user_repos = false;
-                    for repo_url in repos {
-                        let (mut update_data, error_message) = handle_github_repo_url(
-                            &client,
-                            &repo_url,
-                            &github_token,
-                            &KEYWORDS,
-                            &ALLOWED_EXTENSIONS,
-                            254,
-                            &config.read_sheet_name,
-                        )
-                        .await?;
+            let submission = &submissions[data_row_idx];
+            let overrides = submission.scan_overrides();
+            if !overrides.is_empty() {
+                println!("🎛️  Row overrides: {:?}", overrides);
+            }
+            // A cell may list several repos ("frontend: …, program: …").
+            let classified: Vec<(String, ForgeUrlType, String)> = extract_repo_urls(repo_url)
+                .into_iter()
+                .map(|url| {
+                    let (url_type, fixes) = classify_forge_url_detailed(&url);
+                    (url, url_type, describe_url_fixes(&fixes))
+                })
+                .collect();
+            let url_normalization = match classified.as_slice() {
+                [(_, _, fixes)] => fixes.clone(),
+                _ => classified
+                    .iter()
+                    .map(|(url, _, fixes)| format!("{}: {}", url, fixes))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            if url_normalization != "none" {
+                println!("🔧 Normalized '{}': {}", repo_url, url_normalization);
+            }
+            let targets: Vec<(ForgeRepo, String)> = classified
+                .iter()
+                .filter_map(|(_, url_type, fixes)| match url_type {
+                    ForgeUrlType::Repo(target) => Some((target.clone(), fixes.clone())),
+                    _ => None,
+                })
+                .collect();
+            let single_user = match classified.as_slice() {
+                [(_, ForgeUrlType::User(owner), _)] => Some(owner.clone()),
+                _ => None,
+            };
 
-                        // Skip if there are no keyword matches (only record users with keyword matches) or data is empty
-                        if update_data.keyword_matches == "0" || update_data.is_empty() {
-                            continue;
-                        }
-                        matched_user_repos = true;
-
-                        // Only ingest if it's not empty/default
-                        if !update_data.is_empty() {
-                            update_data.add_fields_if_exist(
-                                &cleaned_columns,
-                                &fields,
-                                data_row_idx,
-                            );
-                            let response = ingest_via_logstash(
-                                "https://elk.jonas-chen.com/logstash/",
-                                "ELK",
-                                &serde_json::to_value(&update_data)?,
-                            )
-                            .await?;
+            if let Some(owner) = single_user {
+                // A single GitHub user or organization
+                println!("👤 Detected GitHub user/org: {}", owner);
+                let (repos, total) = fetch_user_repos(client, &owner, github_token, 10).await;
+                println!("🔍 Found {} repos for {}", total, owner);
+                let mut matched_user_repos = false;
+                for repo_url in repos {
+                    let (mut update_data, _status, error_message) = handle_github_repo_url(
+                        client,
+                        &repo_url,
+                        github_token,
+                        &KEYWORDS,
+                        &ALLOWED_EXTENSIONS,
+                        254,
+                        &config.read_sheet_name,
+                        path_rules,
+                        include_vendored,
+                        &overrides,
+                    )
+                    .await?;
 
-                            println!("Ingest response: {}", response);
-                        }
-                        final_results.push(update_data.clone());
+                    // Skip if there are no keyword matches (only record users with keyword matches) or data is empty
+                    if update_data.keyword_matches == "0" || update_data.is_empty() {
+                        continue;
+                    }
+                    matched_user_repos = true;
+                    update_data.url_normalization = url_normalization.clone();
 
-                        if !row_cell_still_matches(
-                            sheets,
-                            &config.spreadsheet_id,
-                            &config.read_sheet_name,
-                            row_reading,
-                            &url_check_cols,
-                            cell_url,
+                    // Only ingest if it's not empty/default
+                    if !update_data.is_empty() {
+                        update_data.add_submission_fields(submission);
+                        let response = ingest_via_logstash(
+                            "https://elk.jonas-chen.com/logstash/",
+                            "ELK",
+                            &serde_json::to_value(&update_data)?,
                         )
+                        .await?;
+
+                        println!("Ingest response: {}", response);
+                    }
+                    final_results.push(update_data.clone());
+
+                    let Some(row) = row_locator
+                        .row_for(sheets, &config.spreadsheet_id, cell_url, row_reading)
                         .await?
-                        {
-                            println!(
-                                "⚠️  Row {} no longer holds '{}' (sheet changed mid-run); skipping write.",
-                                row_reading, cell_url
-                            );
-                            continue;
-                        }
+                    else {
+                        continue;
+                    };
+
+                    // Write the user identity block.
+                    write_named_cells(
+                        sheets,
+                        &config.spreadsheet_id,
+                        &config.user_write_sheet,
+                        row,
+                        &[
+                            (&user_write_cols[0], owner.clone()),
+                            (&user_write_cols[1], config.read_sheet_name.clone()),
+                        ],
+                    )
+                    .await?;
 
-                        // Write the user identity block.
-                        write_named_cells(
+                    if let Some(error) = error_message {
+                        println!("❌ Error processing {}: {}", repo_url, error);
+                        // Error goes into the JSON-result column.
+                        write_to_cell(
                             sheets,
                             &config.spreadsheet_id,
-                            &config.user_write_sheet,
-                            row_reading,
-                            &[
-                                (&user_write_cols[0], owner.clone()),
-                                (&user_write_cols[1], config.read_sheet_name.clone()),
-                            ],
+                            &config.write_sheet_name,
+                            &update_data_cols[0],
+                            row,
+                            &format!("❌ Error: {}", error),
                         )
                         .await?;
-
-                        if let Some(error) = error_message {
-                            println!("❌ Error processing {}: {}", repo_url, error);
-                            // Error goes into the JSON-result column.
-                            write_to_cell(
-                                sheets,
-                                &config.spreadsheet_id,
-                                &config.write_sheet_name,
-                                &update_data_cols[0],
-                                row_reading,
-                                &format!("❌ Error: {}", error),
-                            )
-                            .await?;
-                        } else {
-                            write_named_cells(
+                    } else {
+                        write_entered_cells(
+                            sheets,
+                            &config.spreadsheet_id,
+                            &config.write_sheet_name,
+                            row,
+                            &result_cells(
                                 sheets,
                                 &config.spreadsheet_id,
-                                &config.write_sheet_name,
-                                row_reading,
-                                &[
-                                    (&update_data_cols[0], serde_json::to_string(&update_data)?),
-                                    (
-                                        &update_data_cols[1],
-                                        update_data.keyword_matches.to_string(),
-                                    ),
-                                    (&update_data_cols[2], update_data.snapshot_url.clone()),
-                                ],
+                                update_data_cols,
+                                update_data_headers,
+                                &update_data,
                             )
-                            .await?;
-                            println!("✅ Row {} updated", row_reading);
-                        }
+                            .await?,
+                        )
+                        .await?;
+                        println!("✅ Row {} updated", row);
                     }
+                }
 
-                    if !matched_user_repos {
-                        let update_data = GitHubUpdateData {
-                            owner: owner.clone(),
-                            origin: config.read_sheet_name.clone(),
-                            keyword_matches: "0".to_string(),
-                            ..Default::default()
-                        };
-                        final_results.push(update_data.clone());
+                if !matched_user_repos {
+                    let update_data = GitHubUpdateData {
+                        owner: owner.clone(),
+                        origin: config.read_sheet_name.clone(),
+                        keyword_matches: "0".to_string(),
+                        url_normalization: url_normalization.clone(),
+                        ..Default::default()
+                    };
+                    final_results.push(update_data.clone());
 
-                        if !row_cell_still_matches(
-                            sheets,
-                            &config.spreadsheet_id,
-                            &config.read_sheet_name,
-                            row_reading,
-                            &url_check_cols,
-                            cell_url,
-                        )
+                    let Some(row) = row_locator
+                        .row_for(sheets, &config.spreadsheet_id, cell_url, row_reading)
                         .await?
-                        {
-                            println!(
-                                "⚠️  Row {} no longer holds '{}' (sheet changed mid-run); skipping write.",
-                                row_reading, cell_url
-                            );
-                            continue;
-                        }
+                    else {
+                        continue;
+                    };
 
-                        write_named_cells(
-                            sheets,
-                            &config.spreadsheet_id,
-                            &config.user_write_sheet,
-                            row_reading,
-                            &[
-                                (&user_write_cols[0], owner.clone()),
-                                (&user_write_cols[1], config.read_sheet_name.clone()),
-                            ],
-                        )
-                        .await?;
+                    write_named_cells(
+                        sheets,
+                        &config.spreadsheet_id,
+                        &config.user_write_sheet,
+                        row,
+                        &[
+                            (&user_write_cols[0], owner.clone()),
+                            (&user_write_cols[1], config.read_sheet_name.clone()),
+                        ],
+                    )
+                    .await?;
 
-                        write_named_cells(
+                    write_entered_cells(
+                        sheets,
+                        &config.spreadsheet_id,
+                        &config.write_sheet_name,
+                        row,
+                        &result_cells(
                             sheets,
                             &config.spreadsheet_id,
-                            &config.write_sheet_name,
-                            row_reading,
-                            &[
-                                (&update_data_cols[0], serde_json::to_string(&update_data)?),
-                                (
-                                    &update_data_cols[1],
-                                    update_data.keyword_matches.to_string(),
-                                ),
-                                (&update_data_cols[2], update_data.snapshot_url.clone()),
-                            ],
+                            update_data_cols,
+                            update_data_headers,
+                            &update_data,
                         )
-                        .await?;
-                        println!(
-                            "✅ Row {} marked complete; no keyword matches found for {}.",
-                            row_reading, owner
-                        );
-                    }
+                        .await?,
+                    )
+                    .await?;
+                    println!(
+                        "✅ Row {} marked complete; no keyword matches found for {}.",
+                        row, owner
+                    );
                 }
-
-                // If GitHub Repo
-                GitHubUrlType::Repo { owner, repo_name } => {
-                    println!("📦 Detected GitHub repo: {}/{}", owner, repo_name);
-                    let repo_url = format!("https://github.com/{}/{}", owner, repo_name);
-                    let (mut update_data, error_message) = handle_github_repo_url(
-                        &client,
+            } else if !targets.is_empty() {
+                // Repos on any supported forge; several per cell are scanned
+                // one by one and merged into a single row result.
+                let mut parts: Vec<GitHubUpdateData> = Vec::new();
+                let mut errors: Vec<String> = Vec::new();
+                let mut statuses: Vec<String> = Vec::new();
+                for (target, fixes) in &targets {
+                    println!(
+                        "📦 Detected {} repo: {}/{}",
+                        target.kind.as_str(),
+                        target.owner,
+                        target.repo_name
+                    );
+                    let repo_url = target.web_url();
+                    let (mut update_data, status, error_message) = handle_github_repo_url(
+                        client,
                         &repo_url,
-                        &github_token,
+                        github_token,
                         &KEYWORDS,
                         &ALLOWED_EXTENSIONS,
                         254,
                         &config.read_sheet_name,
+                        path_rules,
+                        include_vendored,
+                        &overrides,
                     )
                     .await?;
 
                     // Only ingest if it's not empty/default
                     if !update_data.is_empty() {
-                        update_data.add_fields_if_exist(&cleaned_columns, &fields, data_row_idx);
+                        update_data.url_normalization = fixes.clone();
+                        update_data.add_submission_fields(submission);
                         let response = ingest_via_logstash(
                             "https://elk.jonas-chen.com/logstash/",
                             "ELK",
@@ -640,96 +1007,125 @@ async fn main() -> Result<()> {
 
                     final_results.push(update_data.clone());
 
-                    if !row_cell_still_matches(
-                        sheets,
-                        &config.spreadsheet_id,
-                        &config.read_sheet_name,
-                        row_reading,
-                        &url_check_cols,
-                        cell_url,
-                    )
-                    .await?
-                    {
-                        println!(
-                            "⚠️  Row {} no longer holds '{}' (sheet changed mid-run); skipping write.",
-                            row_reading, cell_url
-                        );
-                        continue;
-                    }
-
-                    // Write the update data to Sheets
-                    if let Some(error) = error_message {
-                        println!("❌ Error processing {}: {}", repo_url, error);
-                        // Error goes into the JSON-result column.
-                        write_to_cell(
-                            sheets,
-                            &config.spreadsheet_id,
-                            &config.write_sheet_name,
-                            &update_data_cols[0],
-                            row_reading,
-                            &format!("❌ Error: {}", error),
-                        )
-                        .await?;
+                    let status = status.map(|s| s.label()).unwrap_or("unknown");
+                    statuses.push(if targets.len() > 1 {
+                        format!("{}: {}", repo_url, status)
                     } else {
-                        write_named_cells(
-                            sheets,
-                            &config.spreadsheet_id,
-                            &config.write_sheet_name,
-                            row_reading,
-                            &[
-                                (&update_data_cols[0], serde_json::to_string(&update_data)?),
-                                (
-                                    &update_data_cols[1],
-                                    update_data.keyword_matches.to_string(),
-                                ),
-                                (&update_data_cols[2], update_data.snapshot_url.clone()),
-                            ],
-                        )
-                        .await?;
-
-                        println!("✅ Row {} updated", row_reading);
+                        status.to_string()
+                    });
+                    match error_message {
+                        Some(error) => {
+                            println!("❌ Error processing {}: {}", repo_url, error);
+                            errors.push(if targets.len() > 1 {
+                                format!("{}: {}", repo_url, error)
+                            } else {
+                                error
+                            });
+                        }
+                        None => parts.push(update_data),
                     }
                 }
 
-                GitHubUrlType::Invalid => {
-                    println!("❗ Invalid GitHub URL: {}", repo_url);
-                    if !row_cell_still_matches(
+                let Some(row) = row_locator
+                    .row_for(sheets, &config.spreadsheet_id, cell_url, row_reading)
+                    .await?
+                else {
+                    continue;
+                };
+
+                // Write the update data to Sheets. The status column covers
+                // every repo in the cell, scanned or not.
+                let repo_status = statuses.join("\n");
+                let status_col =
+                    column_for(update_data_cols, update_data_headers, REPO_STATUS_HEADER)?;
+                if parts.is_empty() {
+                    // Error goes into the JSON-result column.
+                    write_named_cells(
                         sheets,
                         &config.spreadsheet_id,
-                        &config.read_sheet_name,
-                        row_reading,
-                        &url_check_cols,
-                        cell_url,
+                        &config.write_sheet_name,
+                        row,
+                        &[
+                            (
+                                &update_data_cols[0],
+                                format!("❌ Error: {}", errors.join("; ")),
+                            ),
+                            (status_col, repo_status),
+                        ],
                     )
-                    .await?
-                    {
-                        println!(
-                            "⚠️  Row {} no longer holds '{}' (sheet changed mid-run); skipping write.",
-                            row_reading, cell_url
-                        );
-                        continue;
+                    .await?;
+                } else {
+                    let mut update_data = merge_row_results(parts);
+                    update_data.url_normalization = url_normalization.clone();
+                    let mut cells = result_cells(
+                        sheets,
+                        &config.spreadsheet_id,
+                        update_data_cols,
+                        update_data_headers,
+                        &update_data,
+                    )
+                    .await?;
+                    if let Some(cell) = cells.iter_mut().find(|(c, _)| *c == status_col) {
+                        cell.1 = literal(&repo_status);
                     }
-                    write_to_cell(
+                    write_entered_cells(
                         sheets,
                         &config.spreadsheet_id,
                         &config.write_sheet_name,
-                        &update_data_cols[0],
-                        row_reading,
-                        &format!("❌ Invalid GitHub URL: {}", repo_url),
+                        row,
+                        &cells,
                     )
                     .await?;
+
+                    println!(
+                        "✅ Row {} updated ({} repo(s), {} failed)",
+                        row,
+                        update_data.repos.len().max(1),
+                        errors.len()
+                    );
                 }
+            } else {
+                let error = match classified.as_slice() {
+                    [(_, ForgeUrlType::Gist { .. }, _)] => {
+                        format!("❌ Gist links are not scanned: {}", repo_url)
+                    }
+                    [(url, ..)] if url.starts_with("file:") => format!(
+                        "❌ Local paths are only scanned from SCRAPER_LOCAL_PATHS: {}",
+                        repo_url
+                    ),
+                    _ => format!("❌ Invalid repository URL: {}", repo_url),
+                };
+                println!("❗ {}", error);
+                let Some(row) = row_locator
+                    .row_for(sheets, &config.spreadsheet_id, cell_url, row_reading)
+                    .await?
+                else {
+                    continue;
+                };
+                // Error goes into the JSON-result column, the fixes tried
+                // into the URL Normalization column.
+                write_named_cells(
+                    sheets,
+                    &config.spreadsheet_id,
+                    &config.write_sheet_name,
+                    row,
+                    &[
+                        (&update_data_cols[0], error),
+                        (
+                            column_for(
+                                update_data_cols,
+                                update_data_headers,
+                                URL_NORMALIZATION_HEADER,
+                            )?,
+                            url_normalization.clone(),
+                        ),
+                    ],
+                )
+                .await?;
             }
         }
     } else {
         println!("Skipping sheet row processing.");
     }
-
-    // Save all results
-    let json = serde_json::to_string_pretty(&final_results)?;
-    File::create("results.json")?.write_all(json.as_bytes())?;
-
-    println!("✅ All results saved.");
-
     Ok(())
 }
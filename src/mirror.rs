@@ -0,0 +1,343 @@
+use crate::forge::{Forge, ForgeKind, ForgeRepoInfo, LookupError};
+use crate::local::LocalForge;
+use crate::types::Contributor;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use gix::bstr::{BString, ByteSlice};
+use gix::credentials::helper::Action;
+use gix::credentials::protocol::Outcome;
+use gix::protocol::handshake::Ref;
+use gix::refs::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+use gix::refs::Target;
+use gix::remote::Direction;
+use reqwest::Client;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::time::{timeout, Duration};
+
+/// Branches and tags, plus `HEAD` so the mirror follows the remote's
+/// default branch; a full mirror would also pull every GitHub `refs/pull/*`
+/// head.
+const MIRROR_REFSPECS: [&str; 3] = [
+    "+refs/heads/*:refs/heads/*",
+    "+refs/tags/*:refs/tags/*",
+    "HEAD",
+];
+
+/// Upper bound for one clone or fetch, so a stuck remote can't stall a run.
+const GIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Bare mirrors of scanned repos, kept under one directory across runs
+/// (`SCRAPER_GIT_CACHE`). The first scan clones, later ones fetch only
+/// what changed. gix does the transfer and the reads, on the blocking pool.
+pub struct GitCache {
+    dir: PathBuf,
+}
+
+impl GitCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        std::env::var("SCRAPER_GIT_CACHE")
+            .ok()
+            .filter(|d| !d.trim().is_empty())
+            .map(|d| Self::new(d.trim()))
+    }
+
+    /// `<cache>/<host>/<owner>/<repo>.git`, or `None` if a part would step
+    /// outside the cache.
+    fn mirror_path(&self, host: &str, owner: &str, repo: &str) -> Option<PathBuf> {
+        let mut path = self.dir.clone();
+        for part in [host].into_iter().chain(owner.split('/')) {
+            if part.is_empty() || part == "." || part == ".." || part.contains('\\') {
+                return None;
+            }
+            path.push(part);
+        }
+        if repo.is_empty() || repo.contains(['/', '\\']) || repo.starts_with('.') {
+            return None;
+        }
+        path.push(format!("{}.git", repo));
+        Some(path)
+    }
+
+    /// Clones `remote` into the mirror for `host/owner/repo`, or fetches into
+    /// it when it already exists, and returns its path. `https://` and
+    /// `file://` remotes both work.
+    pub async fn sync(
+        &self,
+        remote: &str,
+        host: &str,
+        owner: &str,
+        repo: &str,
+        credentials: Option<(String, String)>,
+    ) -> Result<PathBuf> {
+        let path = self
+            .mirror_path(host, owner, repo)
+            .with_context(|| format!("unsafe mirror path for {}/{}", owner, repo))?;
+
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let task = {
+            let (remote, path, interrupt) = (remote.to_string(), path.clone(), interrupt.clone());
+            tokio::task::spawn_blocking(move || {
+                update_mirror(&remote, &path, credentials, &interrupt)
+            })
+        };
+        match timeout(GIT_TIMEOUT, task).await {
+            Ok(joined) => joined.context("mirror sync panicked")??,
+            Err(_) => {
+                // The transfer stops at its next interrupt check and cleans
+                // up after itself.
+                interrupt.store(true, Ordering::Relaxed);
+                bail!("git transfer timed out");
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Fetches into the mirror at `path`, or clones it when there is none yet.
+fn update_mirror(
+    remote: &str,
+    path: &Path,
+    credentials: Option<(String, String)>,
+    interrupt: &AtomicBool,
+) -> Result<()> {
+    if path.join("HEAD").is_file() {
+        let mut repo = gix::open(path)?;
+        return fetch(&mut repo, remote, credentials, interrupt);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Clone next to the target and move it into place, so an interrupted
+    // clone never looks like a usable mirror.
+    let partial = path.with_extension("git.partial");
+    let _ = std::fs::remove_dir_all(&partial);
+    let cloned = gix::init_bare(&partial)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut repo| fetch(&mut repo, remote, credentials, interrupt));
+    if let Err(e) = cloned {
+        let _ = std::fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Brings `repo`'s branches, tags and `HEAD` in line with `remote`.
+/// Credentials are answered in memory, so the token never reaches the
+/// mirror's config, and nothing ever prompts.
+#[allow(clippy::result_large_err)] // gix's credential callback signature
+fn fetch(
+    repo: &mut gix::Repository,
+    remote: &str,
+    credentials: Option<(String, String)>,
+    interrupt: &AtomicBool,
+) -> Result<()> {
+    repo.committer_or_set_generic_fallback()?;
+    let outcome = repo
+        .remote_at(remote)?
+        .with_refspecs(MIRROR_REFSPECS, Direction::Fetch)?
+        .connect(Direction::Fetch)?
+        .with_credentials(move |action| match action {
+            Action::Get(ctx) => Ok(credentials.clone().map(|(username, password)| Outcome {
+                identity: gix::sec::identity::Account {
+                    username,
+                    password,
+                    oauth_refresh_token: None,
+                },
+                next: ctx.into(),
+            })),
+            Action::Store(_) | Action::Erase(_) => Ok(None),
+        })
+        .prepare_fetch(gix::progress::Discard, Default::default())?
+        .with_write_packed_refs_only(true)
+        .receive(gix::progress::Discard, interrupt)
+        .context("git fetch failed")?;
+
+    let fetched: HashSet<&BString> = outcome
+        .ref_map
+        .mappings
+        .iter()
+        .filter_map(|m| m.local.as_ref())
+        .collect();
+    let mut edits = Vec::new();
+    // Prune branches and tags the remote no longer has.
+    for reference in repo.references()?.all()? {
+        let Ok(reference) = reference else {
+            continue;
+        };
+        let name = reference.name();
+        let full = name.as_bstr();
+        if (full.starts_with(b"refs/heads/") || full.starts_with(b"refs/tags/"))
+            && !fetched.contains(&full.to_owned())
+        {
+            edits.push(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                },
+                name: name.to_owned(),
+                deref: false,
+            });
+        }
+    }
+    let remote_head = outcome.ref_map.remote_refs.iter().find_map(|r| match r {
+        Ref::Symbolic {
+            full_ref_name,
+            target,
+            ..
+        }
+        | Ref::Unborn {
+            full_ref_name,
+            target,
+        } if full_ref_name == "HEAD" => Some(target),
+        _ => None,
+    });
+    if let Some(target) = remote_head {
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange::default(),
+                expected: PreviousValue::Any,
+                new: Target::Symbolic(target.as_bstr().try_into()?),
+            },
+            name: "HEAD".try_into()?,
+            deref: false,
+        });
+    }
+    repo.edit_references(edits)?;
+    Ok(())
+}
+
+/// A forge whose files and history are read from a local mirror. Metadata
+/// and browser links still come from the forge itself.
+pub struct MirroredForge {
+    remote: Box<dyn Forge>,
+    mirror: LocalForge,
+}
+
+/// Syncs the mirror for `owner/repo` and scans through it, or keeps using
+/// `forge`'s REST API when the mirror can't be brought up to date.
+pub async fn with_mirror(
+    forge: Box<dyn Forge>,
+    cache: &GitCache,
+    host: &str,
+    owner: &str,
+    repo: &str,
+) -> Box<dyn Forge> {
+    let synced = cache
+        .sync(
+            &forge.clone_url(owner, repo),
+            host,
+            owner,
+            repo,
+            forge.clone_credentials(),
+        )
+        .await;
+    match synced {
+        Ok(path) => Box::new(MirroredForge {
+            remote: forge,
+            mirror: LocalForge::open(path),
+        }),
+        Err(e) => {
+            println!(
+                "⚠️  Mirror of {}/{} unavailable, using the API: {:#}",
+                owner, repo, e
+            );
+            forge
+        }
+    }
+}
+
+#[async_trait]
+impl Forge for MirroredForge {
+    fn kind(&self) -> ForgeKind {
+        self.remote.kind()
+    }
+
+    fn file_url(&self, owner: &str, repo: &str, git_ref: &str, path: &str) -> String {
+        self.remote.file_url(owner, repo, git_ref, path)
+    }
+
+    fn snapshot_url(&self, owner: &str, repo: &str, commit_sha: &str) -> String {
+        self.remote.snapshot_url(owner, repo, commit_sha)
+    }
+
+    fn clone_url(&self, owner: &str, repo: &str) -> String {
+        self.remote.clone_url(owner, repo)
+    }
+
+    fn clone_credentials(&self) -> Option<(String, String)> {
+        self.remote.clone_credentials()
+    }
+
+    async fn repo_info(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+    ) -> Result<ForgeRepoInfo, LookupError> {
+        self.remote.repo_info(client, owner, repo).await
+    }
+
+    async fn list_tree(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<String>> {
+        self.mirror.list_tree(client, owner, repo, git_ref).await
+    }
+
+    async fn fetch_file(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+        path: &str,
+    ) -> Option<String> {
+        self.mirror
+            .fetch_file(client, owner, repo, git_ref, path)
+            .await
+    }
+
+    async fn last_commit(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<(String, String, String, String)> {
+        self.mirror.last_commit(client, owner, repo, git_ref).await
+    }
+
+    async fn is_empty(&self, client: &Client, owner: &str, repo: &str, git_ref: &str) -> bool {
+        self.remote.is_empty(client, owner, repo, git_ref).await
+    }
+
+    async fn contributors(
+        &self,
+        client: &Client,
+        owner: &str,
+        repo: &str,
+        git_ref: &str,
+    ) -> Option<Vec<Contributor>> {
+        let contributors = self
+            .mirror
+            .contributors(client, owner, repo, git_ref)
+            .await?;
+        Some(
+            self.remote
+                .resolve_logins(client, owner, repo, contributors)
+                .await,
+        )
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::env;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+/// Google's default per-user quotas for read and for write requests.
+const DEFAULT_PER_MINUTE: usize = 60;
+const DEFAULT_MAX_RETRIES: u32 = 5;
+/// Backoff cap; one quota window is as long as a 429 can last.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Sliding one-minute window of sent requests.
+pub struct RateLimiter {
+    per_minute: usize,
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(per_minute: usize) -> Self {
+        Self {
+            per_minute: per_minute.max(1),
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Records a request if the window has room, otherwise returns how long
+    /// until it will.
+    fn reserve(&self) -> Result<(), Duration> {
+        self.reserve_at(Instant::now())
+    }
+
+    fn reserve_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut sent = self.sent.lock().unwrap();
+        while sent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+        {
+            sent.pop_front();
+        }
+        if sent.len() < self.per_minute {
+            sent.push_back(now);
+            return Ok(());
+        }
+        Err(WINDOW - now.duration_since(sent[0]))
+    }
+
+    /// Whether a request could be sent right now without waiting.
+    pub fn has_room(&self) -> bool {
+        let now = Instant::now();
+        let sent = self.sent.lock().unwrap();
+        sent.iter()
+            .filter(|t| now.duration_since(**t) < WINDOW)
+            .count()
+            < self.per_minute
+    }
+
+    /// Waits for room in the window and records the request.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.reserve() {
+            sleep(wait).await;
+        }
+    }
+}
+
+/// Which Sheets quota a request counts against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaKind {
+    Read,
+    Write,
+}
+
+/// Client-side pacing for the Sheets API: requests wait for room in the
+/// per-minute read or write quota, and 429s and 5xx responses are retried
+/// with exponential backoff instead of aborting a long run.
+pub struct SheetsQuota {
+    reads: RateLimiter,
+    writes: RateLimiter,
+    max_retries: u32,
+}
+
+fn env_usize(name: &str) -> Option<usize> {
+    env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+impl SheetsQuota {
+    /// Quotas from `SHEETS_READS_PER_MINUTE` and `SHEETS_WRITES_PER_MINUTE`
+    /// (60 each, Google's per-user default) and the retry budget from
+    /// `SHEETS_MAX_RETRIES` (5).
+    pub fn from_env() -> Self {
+        Self {
+            reads: RateLimiter::new(
+                env_usize("SHEETS_READS_PER_MINUTE").unwrap_or(DEFAULT_PER_MINUTE),
+            ),
+            writes: RateLimiter::new(
+                env_usize("SHEETS_WRITES_PER_MINUTE").unwrap_or(DEFAULT_PER_MINUTE),
+            ),
+            max_retries: env_usize("SHEETS_MAX_RETRIES")
+                .map(|n| n as u32)
+                .unwrap_or(DEFAULT_MAX_RETRIES),
+        }
+    }
+
+    pub fn limiter(&self, kind: QuotaKind) -> &RateLimiter {
+        match kind {
+            QuotaKind::Read => &self.reads,
+            QuotaKind::Write => &self.writes,
+        }
+    }
+
+    /// Runs `send` once quota allows, again after a backoff for as long as
+    /// it fails with a retryable error. `send` builds a fresh API call each
+    /// time, since `doit()` consumes the call builder.
+    pub async fn call<R, T, F, Fut>(&self, kind: QuotaKind, what: &str, mut send: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = google_sheets4::Result<(R, T)>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.limiter(kind).acquire().await;
+            let err = match send().await {
+                Ok((_, value)) => return Ok(value),
+                Err(err) => err,
+            };
+            let Some(reason) = retry_reason(&err) else {
+                return Err(err.into());
+            };
+            if attempt >= self.max_retries {
+                eprintln!("⚠️  Sheets {} retry budget exhausted ({})", what, reason);
+                return Err(err.into());
+            }
+            let backoff = backoff(attempt);
+            attempt += 1;
+            eprintln!(
+                "⏳ Sheets {} failed ({}); retry {}/{} in {}s",
+                what,
+                reason,
+                attempt,
+                self.max_retries,
+                backoff.as_secs()
+            );
+            sleep(backoff).await;
+        }
+    }
+}
+
+/// 1s, 2s, 4s, … before retry `attempt + 1`, capped at `MAX_BACKOFF`.
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(6)).min(MAX_BACKOFF)
+}
+
+/// Why `err` is worth retrying: a quota 429, a 5xx, or a dropped
+/// connection. `None` for errors that will fail the same way again.
+fn retry_reason(err: &google_sheets4::Error) -> Option<String> {
+    let status = match err {
+        google_sheets4::Error::HttpError(e) => return Some(e.to_string()),
+        // The API's JSON error body carries the HTTP status as `code`.
+        google_sheets4::Error::BadRequest(body) => body["error"]["code"].as_u64()? as u16,
+        google_sheets4::Error::Failure(resp) => resp.status().as_u16(),
+        _ => return None,
+    };
+    (status == 429 || (500..600).contains(&status)).then(|| format!("HTTP {}", status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bad_request(code: u16) -> google_sheets4::Error {
+        google_sheets4::Error::BadRequest(serde_json::json!({
+            "error": {"code": code, "message": "nope"}
+        }))
+    }
+
+    fn failure(code: u16) -> google_sheets4::Error {
+        google_sheets4::Error::Failure(
+            hyper::Response::builder()
+                .status(code)
+                .body(hyper::Body::empty())
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn retries_quota_and_server_errors_only() {
+        assert_eq!(retry_reason(&bad_request(429)).as_deref(), Some("HTTP 429"));
+        assert_eq!(retry_reason(&bad_request(503)).as_deref(), Some("HTTP 503"));
+        assert_eq!(retry_reason(&failure(500)).as_deref(), Some("HTTP 500"));
+        assert_eq!(retry_reason(&bad_request(400)), None);
+        assert_eq!(retry_reason(&bad_request(403)), None);
+        assert_eq!(retry_reason(&failure(404)), None);
+        assert_eq!(
+            retry_reason(&google_sheets4::Error::BadRequest(serde_json::json!({}))),
+            None
+        );
+        assert_eq!(retry_reason(&google_sheets4::Error::Cancelled), None);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let secs: Vec<u64> = (0..9).map(|a| backoff(a).as_secs()).collect();
+        assert_eq!(secs, [1, 2, 4, 8, 16, 32, 60, 60, 60]);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn reserve_waits_for_the_oldest_request_to_leave_the_window() {
+        let limiter = RateLimiter::new(2);
+        let start = Instant::now();
+
+        assert_eq!(limiter.reserve_at(start), Ok(()));
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(10)), Ok(()));
+        assert_eq!(
+            limiter.reserve_at(start + Duration::from_secs(20)),
+            Err(Duration::from_secs(40))
+        );
+
+        // The first request rolls out of the window; the second still counts.
+        assert_eq!(limiter.reserve_at(start + WINDOW), Ok(()));
+        assert_eq!(
+            limiter.reserve_at(start + WINDOW + Duration::from_secs(1)),
+            Err(Duration::from_secs(9))
+        );
+        assert_eq!(
+            limiter.reserve_at(start + WINDOW + Duration::from_secs(10)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn a_zero_quota_still_lets_one_request_through() {
+        let limiter = RateLimiter::new(0);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve_at(start), Ok(()));
+        assert!(limiter.reserve_at(start).is_err());
+    }
+}
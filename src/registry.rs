@@ -0,0 +1,497 @@
+use crate::forge::{classify_forge_url, ForgeUrlType};
+use crate::github::is_first_party_repo;
+use anyhow::{bail, Context, Result};
+use reqwest::{header::USER_AGENT, Client};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::future::Future;
+use tokio::time::{sleep, Duration};
+
+/// Crates whose dependents are looked up when `SCRAPER_DISCOVERY_CRATES`
+/// isn't set.
+const DEFAULT_CRATES: [&str; 3] = [
+    "ephemeral-rollups-sdk",
+    "ephemeral-rollups-pinocchio",
+    "ephemeral-vrf-sdk",
+];
+const DEFAULT_NPM_SCOPE: &str = "magicblock-labs";
+
+const CRATES_IO: &str = "https://crates.io";
+/// crates.io asks crawlers for at most one request per second.
+const CRATES_IO_REQUEST_GAP: Duration = Duration::from_secs(1);
+/// Pages of 100 dependents read per npm package.
+const NPM_DEPENDENT_PAGES: usize = 10;
+
+/// Registry endpoints; each can point at a local stand-in.
+struct Registries {
+    crates_io: String,
+    npm: String,
+    npm_dependents: String,
+}
+
+impl Registries {
+    fn from_env() -> Self {
+        let base = |var: &str, default: &str| {
+            std::env::var(var)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or_else(|| default.to_string())
+                .trim()
+                .trim_end_matches('/')
+                .to_string()
+        };
+        Self {
+            crates_io: base("CRATES_IO_API_URL", CRATES_IO),
+            npm: base("NPM_REGISTRY_URL", "https://registry.npmjs.org"),
+            npm_dependents: base("NPM_DEPENDENTS_URL", "https://packages.ecosyste.ms"),
+        }
+    }
+}
+
+fn list_from_env(var: &str) -> Option<Vec<String>> {
+    let list: Vec<String> = std::env::var(var)
+        .ok()?
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    (!list.is_empty()).then_some(list)
+}
+
+async fn get_json(client: &Client, url: &str, query: &[(&str, String)]) -> Option<Value> {
+    let resp = client
+        .get(url)
+        .query(query)
+        .header(USER_AGENT, "integration-validation (repo discovery)")
+        .send()
+        .await
+        .ok()?;
+    if !resp.status().is_success() {
+        eprintln!("Registry request {} failed: {}", url, resp.status());
+        return None;
+    }
+    resp.json().await.ok()
+}
+
+/// Turns a registry `repository` value (`git+https://….git`,
+/// `git://…`, `git+ssh://git@…`, `github:owner/repo`, bare `owner/repo`)
+/// into a forge web URL.
+pub fn normalize_repository(raw: &str) -> Option<String> {
+    let raw = raw.trim().trim_start_matches("git+");
+    let expanded;
+    let raw = match ["git://", "ssh://git@"]
+        .iter()
+        .find_map(|scheme| raw.strip_prefix(scheme))
+    {
+        Some(rest) => {
+            expanded = format!("https://{}", rest);
+            &expanded
+        }
+        None => raw,
+    };
+    let expanded;
+    let raw = match raw.split_once(':') {
+        Some(("github", rest)) => rest,
+        Some(("gitlab", rest)) => {
+            expanded = format!("https://gitlab.com/{}", rest);
+            &expanded
+        }
+        Some(("bitbucket", rest)) => {
+            expanded = format!("https://bitbucket.org/{}", rest);
+            &expanded
+        }
+        _ => raw,
+    };
+    match classify_forge_url(raw) {
+        ForgeUrlType::Repo(repo) => Some(repo.web_url()),
+        _ => None,
+    }
+}
+
+impl Registries {
+    fn crates_io_gap(&self) -> Option<Duration> {
+        (self.crates_io == CRATES_IO).then_some(CRATES_IO_REQUEST_GAP)
+    }
+
+    /// Names of crates that depend on `crate_name` in any published version.
+    async fn crate_dependents(&self, client: &Client, crate_name: &str) -> Vec<String> {
+        let url = format!(
+            "{}/api/v1/crates/{}/reverse_dependencies",
+            self.crates_io, crate_name
+        );
+        crate_dependents_from_pages(|page| {
+            let url = &url;
+            async move {
+                if let Some(gap) = self.crates_io_gap() {
+                    sleep(gap).await;
+                }
+                let query = [("per_page", "100".to_string()), ("page", page.to_string())];
+                get_json(client, url, &query).await
+            }
+        })
+        .await
+    }
+
+    async fn crate_repository(&self, client: &Client, crate_name: &str) -> Option<String> {
+        if let Some(gap) = self.crates_io_gap() {
+            sleep(gap).await;
+        }
+        let url = format!("{}/api/v1/crates/{}", self.crates_io, crate_name);
+        let body = get_json(client, &url, &[]).await?;
+        crate_repository_of(&body)
+    }
+
+    /// Every package published under `@scope`.
+    async fn npm_scope_packages(&self, client: &Client, scope: &str) -> Vec<String> {
+        let url = format!("{}/-/v1/search", self.npm);
+        let query = [
+            ("text", format!("scope:{}", scope)),
+            ("size", "250".to_string()),
+        ];
+        match get_json(client, &url, &query).await {
+            Some(body) => scope_packages_of(&body, scope),
+            None => Vec::new(),
+        }
+    }
+
+    /// Packages depending on `package`, from the ecosyste.ms packages API:
+    /// npm itself has listed none since its `dependedUpon` view was retired.
+    /// Fails when the answer is missing or not a package list, so an outage
+    /// isn't mistaken for a package nobody uses.
+    async fn npm_dependents(&self, client: &Client, package: &str) -> Result<Vec<String>> {
+        let url = format!(
+            "{}/api/v1/registries/npmjs.org/packages/{}/dependent_packages",
+            self.npm_dependents,
+            package.replace('/', "%2F")
+        );
+        npm_dependents_from_pages(&url, |page| {
+            let url = &url;
+            async move {
+                let query = [("per_page", "100".to_string()), ("page", page.to_string())];
+                get_json(client, url, &query).await
+            }
+        })
+        .await
+    }
+
+    async fn npm_repository(&self, client: &Client, package: &str) -> Option<String> {
+        let url = format!("{}/{}/latest", self.npm, package.replace('/', "%2F"));
+        let body = get_json(client, &url, &[]).await?;
+        npm_repository_of(&body)
+    }
+}
+
+/// Reads crates.io reverse-dependency pages 1, 2, … from `fetch_page`
+/// until one fails, comes back empty or reaches the reported total,
+/// listing each dependent crate once.
+async fn crate_dependents_from_pages<F, Fut>(mut fetch_page: F) -> Vec<String>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Option<Value>>,
+{
+    let mut names: Vec<String> = Vec::new();
+    for page in 1.. {
+        let Some(body) = fetch_page(page).await else {
+            break;
+        };
+        let versions = body["versions"].as_array().cloned().unwrap_or_default();
+        for name in versions.iter().filter_map(|v| v["crate"].as_str()) {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        let total = body["meta"]["total"].as_u64().unwrap_or(0);
+        if versions.is_empty() || page * 100 >= total {
+            break;
+        }
+    }
+    names
+}
+
+/// Reads ecosyste.ms dependent-package pages 1, 2, … from `fetch_page`
+/// until one comes back short or `NPM_DEPENDENT_PAGES` have been read.
+/// A failed page or one that isn't a package list fails the whole lookup.
+async fn npm_dependents_from_pages<F, Fut>(url: &str, mut fetch_page: F) -> Result<Vec<String>>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Option<Value>>,
+{
+    let mut names: Vec<String> = Vec::new();
+    for page in 1..=NPM_DEPENDENT_PAGES {
+        let body = fetch_page(page)
+            .await
+            .with_context(|| format!("no usable answer from {}", url))?;
+        let Some(packages) = body.as_array() else {
+            bail!("{} answered with something other than a package list", url);
+        };
+        names.extend(
+            packages
+                .iter()
+                .filter_map(|p| p["name"].as_str())
+                .map(|name| name.to_string()),
+        );
+        if packages.len() < 100 {
+            break;
+        }
+    }
+    Ok(names)
+}
+
+/// The `repository` of a crates.io `/crates/<name>` answer.
+fn crate_repository_of(body: &Value) -> Option<String> {
+    body["crate"]["repository"].as_str().map(|r| r.to_string())
+}
+
+/// Package names under `@scope` in an npm search answer; the search also
+/// matches packages that merely mention the scope.
+fn scope_packages_of(body: &Value, scope: &str) -> Vec<String> {
+    body["objects"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|o| o["package"]["name"].as_str())
+        .filter(|name| name.starts_with(&format!("@{}/", scope)))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// The `repository` of an npm package manifest, given either as a string
+/// or as `{ "type": "git", "url": … }`.
+fn npm_repository_of(body: &Value) -> Option<String> {
+    match &body["repository"] {
+        Value::String(url) => Some(url.clone()),
+        repository => repository["url"].as_str().map(|u| u.to_string()),
+    }
+}
+
+/// Repos of crates depending on the MagicBlock crates and of npm packages
+/// depending on any `@magicblock-labs/*` package. Complements GitHub code
+/// search, which misses unindexed repos; first-party repos are left out.
+pub async fn discover_registry_repos(client: &Client) -> Vec<String> {
+    let registries = Registries::from_env();
+    let mut packages: Vec<(&str, String)> = Vec::new();
+
+    let crates = list_from_env("SCRAPER_DISCOVERY_CRATES")
+        .unwrap_or_else(|| DEFAULT_CRATES.iter().map(|c| c.to_string()).collect());
+    for crate_name in &crates {
+        let dependents = registries.crate_dependents(client, crate_name).await;
+        println!(
+            "📦 crates.io: {} crate(s) depend on {}",
+            dependents.len(),
+            crate_name
+        );
+        packages.extend(dependents.into_iter().map(|d| ("crates.io", d)));
+    }
+
+    let scope = std::env::var("SCRAPER_DISCOVERY_NPM_SCOPE")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_NPM_SCOPE.to_string());
+    let scope = scope.trim().trim_start_matches('@');
+    for package in registries.npm_scope_packages(client, scope).await {
+        let dependents = match registries.npm_dependents(client, &package).await {
+            Ok(dependents) => dependents,
+            Err(e) => {
+                eprintln!("❌ npm dependents of {} unavailable: {:#}", package, e);
+                continue;
+            }
+        };
+        println!(
+            "📦 npm: {} package(s) depend on {}",
+            dependents.len(),
+            package
+        );
+        packages.extend(dependents.into_iter().map(|d| ("npm", d)));
+    }
+
+    let mut seen: HashSet<(&str, String)> = HashSet::new();
+    let mut repo_urls: Vec<String> = Vec::new();
+    for (registry, package) in packages {
+        if !seen.insert((registry, package.clone())) {
+            continue;
+        }
+        let repository = match registry {
+            "crates.io" => registries.crate_repository(client, &package).await,
+            _ => registries.npm_repository(client, &package).await,
+        };
+        let Some(url) = repository.as_deref().and_then(normalize_repository) else {
+            println!("⚠️  No repository URL for {} package {}", registry, package);
+            continue;
+        };
+        if !is_first_party_repo(&url) && !repo_urls.iter().any(|u| u.eq_ignore_ascii_case(&url)) {
+            repo_urls.push(url);
+        }
+    }
+    println!(
+        "Found {} unique repos from registry dependents",
+        repo_urls.len()
+    );
+    repo_urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    fn crate_page(names: &[&str], total: u64) -> Value {
+        let versions: Vec<Value> = names.iter().map(|n| json!({"crate": n})).collect();
+        json!({"versions": versions, "meta": {"total": total}})
+    }
+
+    fn npm_page(len: usize) -> Value {
+        let packages: Vec<Value> = (0..len)
+            .map(|i| json!({"name": format!("pkg-{}", i)}))
+            .collect();
+        Value::Array(packages)
+    }
+
+    #[tokio::test]
+    async fn reads_crate_dependents_up_to_the_reported_total() {
+        let fetched = Cell::new(0);
+        let names = crate_dependents_from_pages(|page| {
+            fetched.set(page);
+            // Every version of a dependent is listed, so crates repeat.
+            let names = if page == 1 {
+                vec!["game", "game", "dex"]
+            } else {
+                vec!["dex", "bot"]
+            };
+            async move { Some(crate_page(&names, 150)) }
+        })
+        .await;
+        assert_eq!(fetched.get(), 2);
+        assert_eq!(names, ["game", "dex", "bot"]);
+    }
+
+    #[tokio::test]
+    async fn stops_reading_crate_dependents_on_an_empty_or_failed_page() {
+        let fetched = Cell::new(0);
+        let names = crate_dependents_from_pages(|page| {
+            fetched.set(page);
+            let body = (page == 1).then(|| crate_page(&["game"], 1_000));
+            async move { body.or(Some(crate_page(&[], 1_000))) }
+        })
+        .await;
+        assert_eq!((fetched.get(), names), (2, vec!["game".to_string()]));
+
+        let names = crate_dependents_from_pages(|page| {
+            let body = (page == 1).then(|| crate_page(&["game"], 1_000));
+            async move { body }
+        })
+        .await;
+        assert_eq!(names, ["game"]);
+    }
+
+    #[tokio::test]
+    async fn reads_npm_dependents_until_a_short_page_or_the_page_cap() {
+        let fetched = Cell::new(0);
+        let names = npm_dependents_from_pages("deps", |page| {
+            fetched.set(page);
+            let len = if page == 1 { 100 } else { 3 };
+            async move { Some(npm_page(len)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!((fetched.get(), names.len()), (2, 103));
+
+        let names = npm_dependents_from_pages("deps", |page| {
+            fetched.set(page);
+            async { Some(npm_page(100)) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(fetched.get(), NPM_DEPENDENT_PAGES);
+        assert_eq!(names.len(), NPM_DEPENDENT_PAGES * 100);
+    }
+
+    #[tokio::test]
+    async fn a_failed_or_malformed_npm_page_fails_the_lookup() {
+        let failed = npm_dependents_from_pages("deps", |page| {
+            let body = (page == 1).then(|| npm_page(100));
+            async move { body }
+        })
+        .await;
+        assert!(failed
+            .unwrap_err()
+            .to_string()
+            .contains("no usable answer from deps"));
+
+        let malformed =
+            npm_dependents_from_pages("deps", |_| async { Some(json!({"error": "not found"})) })
+                .await;
+        assert!(malformed.unwrap_err().to_string().contains("package list"));
+    }
+
+    #[test]
+    fn reads_repository_fields_from_registry_answers() {
+        let krate =
+            json!({"crate": {"name": "game", "repository": "https://github.com/team/game"}});
+        assert_eq!(
+            crate_repository_of(&krate).as_deref(),
+            Some("https://github.com/team/game")
+        );
+        assert_eq!(
+            crate_repository_of(&json!({"crate": {"repository": null}})),
+            None
+        );
+
+        let as_object =
+            json!({"repository": {"type": "git", "url": "git+https://github.com/team/app.git"}});
+        assert_eq!(
+            npm_repository_of(&as_object).as_deref(),
+            Some("git+https://github.com/team/app.git")
+        );
+        let as_string = json!({"repository": "github:team/app"});
+        assert_eq!(
+            npm_repository_of(&as_string).as_deref(),
+            Some("github:team/app")
+        );
+        assert_eq!(npm_repository_of(&json!({"name": "app"})), None);
+
+        let search = json!({"objects": [
+            {"package": {"name": "@magicblock-labs/ephemeral-rollups-sdk"}},
+            {"package": {"name": "magicblock-labs-fan-club"}},
+            {"package": {"name": "@magicblock-labs/vrf-sdk"}},
+        ]});
+        assert_eq!(
+            scope_packages_of(&search, "magicblock-labs"),
+            [
+                "@magicblock-labs/ephemeral-rollups-sdk",
+                "@magicblock-labs/vrf-sdk"
+            ]
+        );
+    }
+
+    #[test]
+    fn normalizes_registry_repository_values_to_repo_urls() {
+        let cases = [
+            (
+                "git+https://github.com/team/app.git",
+                Some("https://github.com/team/app"),
+            ),
+            (
+                "git://github.com/team/app.git",
+                Some("https://github.com/team/app"),
+            ),
+            (
+                "git+ssh://git@github.com/team/app.git",
+                Some("https://github.com/team/app"),
+            ),
+            ("github:team/app", Some("https://github.com/team/app")),
+            ("team/app", Some("https://github.com/team/app")),
+            ("gitlab:group/app", Some("https://gitlab.com/group/app")),
+            ("bitbucket:team/app", Some("https://bitbucket.org/team/app")),
+            (
+                "https://github.com/team/app/tree/main/packages/sdk",
+                Some("https://github.com/team/app"),
+            ),
+            ("https://example.com", None),
+            ("", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_repository(raw).as_deref(), expected, "{}", raw);
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::a1::{column_number_to_letter, A1Range, Column};
+use crate::sheet_store::SheetStore;
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Where a keyed row is at write time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowMatch {
+    Found(usize),
+    /// No row holds the key any more (deleted, or the cell was edited).
+    Missing,
+    /// Several rows hold the key and none of them is the row it was read
+    /// from, so there's no telling which one is meant.
+    Ambiguous(Vec<usize>),
+}
+
+/// Finds the current row of a record by its key (repo URL, program
+/// address, …) instead of trusting the row number it was read from: rows
+/// move when the sheet is sorted or edited while a long run is going.
+pub struct RowLocator {
+    sheet_name: String,
+    /// Columns holding the key; a row matches if any of them does.
+    key_columns: Vec<String>,
+    /// Applied to cells and looked-up keys alike.
+    normalize: fn(&str) -> String,
+    /// A row's key is all of its key cells together, not any one of them.
+    compound: bool,
+}
+
+/// Key → rows, from one read of the key columns.
+pub struct RowIndex {
+    rows: HashMap<String, Vec<usize>>,
+    /// No key columns: every record stays where it was read.
+    unkeyed: bool,
+    normalize: fn(&str) -> String,
+    /// The last row holding any key, or 1 when there is none.
+    last_row: usize,
+}
+
+fn trimmed(key: &str) -> String {
+    key.trim().to_string()
+}
+
+/// Row `i`'s cell of a column read by `read_ranges`, trimmed.
+fn cell(column: &[Vec<String>], i: usize) -> &str {
+    column
+        .get(i)
+        .and_then(|cells| cells.first())
+        .map_or("", |c| c.trim())
+}
+
+impl RowLocator {
+    pub fn new(sheet_name: &str, key_columns: Vec<String>) -> Self {
+        Self {
+            sheet_name: sheet_name.to_string(),
+            key_columns,
+            normalize: trimmed,
+            compound: false,
+        }
+    }
+
+    /// Matches keys after `normalize` instead of as trimmed text, for keys
+    /// with several spellings (URLs).
+    pub fn with_normalized_keys(mut self, normalize: fn(&str) -> String) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Keys each row on all of its key columns at once, for records told
+    /// apart by several cells (repo and commit); look them up by
+    /// `compound_key` of the same cells in column order.
+    pub fn with_compound_key(mut self) -> Self {
+        self.compound = true;
+        self
+    }
+
+    /// The key of a row holding `cells` in a compound-keyed locator's
+    /// columns.
+    pub fn compound_key(cells: &[&str]) -> String {
+        cells
+            .iter()
+            .map(|c| c.trim())
+            .collect::<Vec<_>>()
+            .join("\t")
+    }
+
+    /// Locator keyed on every column whose row-1 header is exactly
+    /// `header` (duplicate headers included).
+    pub async fn for_header(
+        sheets: &dyn SheetStore,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        header: &str,
+    ) -> Result<Self> {
+        let headers = sheets.read_header(spreadsheet_id, sheet_name).await?;
+        let key_columns = headers
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.trim() == header)
+            .map(|(i, _)| column_number_to_letter(i + 1))
+            .collect();
+        Ok(Self::new(sheet_name, key_columns))
+    }
+
+    /// Reads the key columns afresh, in one request.
+    pub async fn snapshot(
+        &self,
+        sheets: &dyn SheetStore,
+        spreadsheet_id: &str,
+    ) -> Result<RowIndex> {
+        let ranges = self
+            .key_columns
+            .iter()
+            .map(|col| {
+                let column = col.parse::<Column>()?;
+                Ok(A1Range::column_from(&self.sheet_name, column, 2).to_string())
+            })
+            .collect::<Result<Vec<String>>>()?;
+        let columns = sheets.read_ranges(spreadsheet_id, &ranges).await?;
+        let keyed: Vec<(usize, String)> = if self.compound {
+            let row_count = columns.iter().map(Vec::len).max().unwrap_or(0);
+            (0..row_count)
+                .map(|i| {
+                    let cells: Vec<&str> = columns.iter().map(|c| cell(c, i)).collect();
+                    (i, cells)
+                })
+                .filter(|(_, cells)| cells.iter().any(|c| !c.is_empty()))
+                .map(|(i, cells)| (i, Self::compound_key(&cells)))
+                .collect()
+        } else {
+            columns
+                .iter()
+                .flat_map(|column| (0..column.len()).map(move |i| (i, cell(column, i))))
+                .filter(|(_, key)| !key.is_empty())
+                .map(|(i, key)| (i, key.to_string()))
+                .collect()
+        };
+        let mut rows: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, key) in keyed {
+            let found = rows.entry((self.normalize)(&key)).or_default();
+            if !found.contains(&(i + 2)) {
+                found.push(i + 2);
+            }
+        }
+        for found in rows.values_mut() {
+            found.sort();
+        }
+        let last_row = rows.values().flatten().copied().max().unwrap_or(1);
+        Ok(RowIndex {
+            rows,
+            unkeyed: self.key_columns.is_empty(),
+            normalize: self.normalize,
+            last_row,
+        })
+    }
+
+    /// The current row of `key`, read from `hint`; `None` (with a warning)
+    /// when it can't be told.
+    pub async fn row_for(
+        &self,
+        sheets: &dyn SheetStore,
+        spreadsheet_id: &str,
+        key: &str,
+        hint: usize,
+    ) -> Result<Option<usize>> {
+        if self.key_columns.is_empty() {
+            return Ok(Some(hint));
+        }
+        Ok(self
+            .snapshot(sheets, spreadsheet_id)
+            .await?
+            .row_for(key, hint))
+    }
+}
+
+impl RowIndex {
+    /// `hint` wins whenever it still holds `key`, so rows repeating the same
+    /// key each keep their own result.
+    pub fn locate(&self, key: &str, hint: usize) -> RowMatch {
+        if self.unkeyed {
+            return RowMatch::Found(hint);
+        }
+        match self.rows.get(&(self.normalize)(key)).map(|r| r.as_slice()) {
+            None | Some([]) => RowMatch::Missing,
+            Some(rows) if rows.contains(&hint) => RowMatch::Found(hint),
+            Some([row]) => RowMatch::Found(*row),
+            Some(rows) => RowMatch::Ambiguous(rows.to_vec()),
+        }
+    }
+
+    /// Every row holding `key`, in sheet order.
+    pub fn rows(&self, key: &str) -> &[usize] {
+        self.rows
+            .get(&(self.normalize)(key))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The first row after every keyed row, where a new record goes.
+    pub fn next_row(&self) -> usize {
+        self.last_row + 1
+    }
+
+    /// Like `locate`, reporting moved, missing and ambiguous rows.
+    pub fn row_for(&self, key: &str, hint: usize) -> Option<usize> {
+        match self.locate(key, hint) {
+            RowMatch::Found(row) => {
+                if row != hint {
+                    println!("↕️  '{}' moved from row {} to row {}.", key, hint, row);
+                }
+                Some(row)
+            }
+            RowMatch::Missing => {
+                eprintln!(
+                    "⚠️  No row holds '{}' any more (was row {}); skipping write.",
+                    key, hint
+                );
+                None
+            }
+            RowMatch::Ambiguous(rows) => {
+                eprintln!(
+                    "⚠️  '{}' (was row {}) is now in rows {:?}; skipping ambiguous write.",
+                    key, hint, rows
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet_store::MemorySheetStore;
+
+    fn sheet(rows: &[&[&str]]) -> MemorySheetStore {
+        let sheets = MemorySheetStore::new();
+        sheets.add_tab(
+            "Teams",
+            rows.iter()
+                .map(|r| r.iter().map(|c| c.to_string()).collect())
+                .collect(),
+        );
+        sheets
+    }
+
+    #[tokio::test]
+    async fn finds_rows_by_key_across_duplicate_key_columns() {
+        let sheets = sheet(&[
+            &["GitHub Repo", "Team", "GitHub Repo"],
+            &["https://github.com/ann/game", "Ann", ""],
+            &["", "Bob", " https://github.com/bob/app "],
+            &["https://github.com/cy/dup", "Cy", ""],
+            &["https://github.com/cy/dup", "Cy 2", ""],
+        ]);
+        let locator = RowLocator::for_header(&sheets, "", "Teams", "GitHub Repo")
+            .await
+            .unwrap();
+        let index = locator.snapshot(&sheets, "").await.unwrap();
+
+        // A key found in the second column, trimmed, after the row moved up.
+        assert_eq!(
+            index.locate("https://github.com/bob/app", 5),
+            RowMatch::Found(3)
+        );
+        assert_eq!(
+            index.locate("https://github.com/ann/game", 2),
+            RowMatch::Found(2)
+        );
+        // Repeated keys each keep their own row, unless neither is the hint.
+        assert_eq!(
+            index.locate("https://github.com/cy/dup", 5),
+            RowMatch::Found(5)
+        );
+        assert_eq!(
+            index.locate("https://github.com/cy/dup", 9),
+            RowMatch::Ambiguous(vec![4, 5])
+        );
+        assert_eq!(
+            index.locate("https://github.com/gone/x", 2),
+            RowMatch::Missing
+        );
+        assert_eq!(index.row_for("https://github.com/gone/x", 2), None);
+    }
+
+    #[tokio::test]
+    async fn follows_a_row_moved_after_it_was_read() {
+        let sheets = sheet(&[
+            &["GitHub Repo"],
+            &["https://github.com/ann/game"],
+            &["https://github.com/bob/app"],
+        ]);
+        let locator = RowLocator::new("Teams", vec!["A".to_string()]);
+        // Rows sorted the other way round mid-run.
+        sheets.add_tab(
+            "Teams",
+            vec![
+                vec!["GitHub Repo".to_string()],
+                vec!["https://github.com/bob/app".to_string()],
+                vec!["https://github.com/ann/game".to_string()],
+            ],
+        );
+        let row = locator
+            .row_for(&sheets, "", "https://github.com/ann/game", 2)
+            .await
+            .unwrap();
+        assert_eq!(row, Some(3));
+    }
+
+    #[tokio::test]
+    async fn leaves_rows_in_place_without_key_columns() {
+        let sheets = sheet(&[&["Team"], &["Ann"]]);
+        let locator = RowLocator::for_header(&sheets, "", "Teams", "GitHub Repo")
+            .await
+            .unwrap();
+        let row = locator.row_for(&sheets, "", "anything", 7).await.unwrap();
+        assert_eq!(row, Some(7));
+    }
+
+    #[tokio::test]
+    async fn matches_normalized_keys_and_appends_after_the_last_keyed_row() {
+        let sheets = sheet(&[
+            &["Repo URL", "Canonical Repo URL"],
+            &["https://github.com/Ann/Game/", ""],
+            &["", ""],
+            &["https://github.com/bob/old", "https://github.com/bob/app"],
+        ]);
+        let locator = RowLocator::new("Teams", vec!["A".to_string(), "B".to_string()])
+            .with_normalized_keys(|k| k.trim_end_matches('/').to_lowercase());
+        let index = locator.snapshot(&sheets, "").await.unwrap();
+
+        assert_eq!(
+            index.locate("https://github.com/ann/game", 0),
+            RowMatch::Found(2)
+        );
+        assert_eq!(
+            index.locate("https://github.com/bob/app", 0),
+            RowMatch::Found(4)
+        );
+        assert_eq!(index.next_row(), 5);
+
+        let empty = sheet(&[&["Repo URL", "Canonical Repo URL"]]);
+        assert_eq!(locator.snapshot(&empty, "").await.unwrap().next_row(), 2);
+    }
+
+    #[tokio::test]
+    async fn compound_keys_need_every_cell_to_match() {
+        let sheets = sheet(&[
+            &["Repo", "Commit SHA"],
+            &["team/game", "abc123"],
+            &["fork/game", "abc123"],
+            &["team/game", "def456"],
+            &["team/game", "abc123"],
+            &["", "9f9f9f"],
+        ]);
+        let index = RowLocator::new("Teams", vec!["A".to_string(), "B".to_string()])
+            .with_compound_key()
+            .snapshot(&sheets, "")
+            .await
+            .unwrap();
+
+        let key = RowLocator::compound_key(&["team/game", " abc123 "]);
+        assert_eq!(index.rows(&key), [2, 5]);
+        assert_eq!(index.locate(&key, 5), RowMatch::Found(5));
+        assert_eq!(
+            index.locate(&RowLocator::compound_key(&["fork/game", "abc123"]), 0),
+            RowMatch::Found(3)
+        );
+        assert!(index
+            .rows(&RowLocator::compound_key(&["team/game", "9f9f9f"]))
+            .is_empty());
+        assert_eq!(index.next_row(), 7);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::header_rules::{HeaderRule, HeaderRules};
+use crate::sheet_store::SheetStore;
+use crate::sheets::{clean_column_names, read_columns_from_sheet};
+use anyhow::{anyhow, bail, Result};
+use serde::de::value::{Error as CellError, MapDeserializer};
+use serde::de::{self, DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
+use serde::{forward_to_deserialize_any, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A struct read from sheet rows. Each field takes the cell under the
+/// header its `ALIASES` map to it, or else under the header named like it
+/// (its serde name, so `#[serde(rename = "Repo URL")]` matches that header
+/// exactly). Configured header rules (`SCRAPER_HEADER_RULES`) are layered
+/// on top of `ALIASES`. Blank cells count as missing, so fields need
+/// `#[serde(default)]` or an `Option` type.
+pub trait SheetRow: DeserializeOwned {
+    const ALIASES: &'static [HeaderRule] = &[];
+}
+
+/// One typed value per data row of `columns` (as returned by
+/// `read_columns_from_sheet`), headers mapped by `rules` over
+/// `T::ALIASES`. Cells are trimmed.
+pub fn rows_from_columns<T: SheetRow>(
+    columns: &HashMap<String, Vec<String>>,
+    rules: &HeaderRules,
+) -> Result<Vec<T>> {
+    let rules = rules.with_defaults(T::ALIASES)?;
+    let mut named = clean_column_names(columns.clone(), &rules);
+    for (header, values) in columns {
+        named
+            .entry(header.clone())
+            .or_insert_with(|| values.clone());
+    }
+    let row_count = named.values().map(Vec::len).max().unwrap_or(0);
+    (0..row_count)
+        .map(|i| {
+            let cells = named.iter().filter_map(|(name, values)| {
+                let value = values.get(i)?.trim();
+                (!value.is_empty()).then_some((name.as_str(), Cell(value)))
+            });
+            T::deserialize(MapDeserializer::<_, CellError>::new(cells))
+                .map_err(|e| anyhow!("row {}: {}", i + 2, e))
+        })
+        .collect()
+}
+
+/// Reads `sheet_name` (within `range`, blank for all of it) as typed rows,
+/// starting at row 2.
+pub async fn read_rows<T: SheetRow>(
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    sheet_name: &str,
+    range: &str,
+    rules: &HeaderRules,
+) -> Result<Vec<T>> {
+    let columns = read_columns_from_sheet(sheets, spreadsheet_id, sheet_name, range).await?;
+    rows_from_columns(&columns, rules)
+}
+
+/// The field names a derived `Deserialize` struct reads, in declaration
+/// order; for listing them in `--explain-columns`.
+pub fn struct_fields<T: DeserializeOwned>() -> &'static [&'static str] {
+    struct Fields(&'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for &mut Fields {
+        type Error = CellError;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, CellError> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, CellError> {
+            self.0 = fields;
+            Err(de::Error::custom("fields recorded"))
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields = Fields(&[]);
+    let _ = T::deserialize(&mut fields);
+    fields.0
+}
+
+/// Cell text for every field of `value`, keyed by serde name: strings as
+/// they are, numbers and booleans as text, `None` as a blank cell and
+/// anything nested as JSON.
+pub fn row_values<T: Serialize>(value: &T) -> Result<HashMap<String, String>> {
+    let Value::Object(fields) = serde_json::to_value(value)? else {
+        bail!("a sheet row must serialize to a struct");
+    };
+    Ok(fields
+        .into_iter()
+        .map(|(name, value)| {
+            let text = match value {
+                Value::Null => String::new(),
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            (name, text)
+        })
+        .collect())
+}
+
+/// Pairs resolved columns with `value`'s cells for their headers (`cols`
+/// and `headers` in the same order). Headers without a matching field are
+/// left out, so one struct can serve layouts with optional columns.
+pub fn named_cells<'a, T: Serialize>(
+    value: &T,
+    cols: &'a [String],
+    headers: &[String],
+) -> Result<Vec<(&'a str, String)>> {
+    let mut values = row_values(value)?;
+    Ok(cols
+        .iter()
+        .zip(headers)
+        .filter_map(|(c, h)| values.remove(h.as_str()).map(|v| (c.as_str(), v)))
+        .collect())
+}
+
+/// A cell's text, deserialized as whatever the field asks for.
+struct Cell<'a>(&'a str);
+
+impl Cell<'_> {
+    fn parse<T: std::str::FromStr>(&self, what: &str) -> Result<T, CellError> {
+        self.0
+            .parse()
+            .map_err(|_| de::Error::custom(format!("'{}' is not {}", self.0, what)))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, CellError> for Cell<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_cell {
+    ($($method:ident => $visit:ident, $what:literal;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CellError> {
+                visitor.$visit(self.parse($what)?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Cell<'_> {
+    type Error = CellError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CellError> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CellError> {
+        visitor.visit_some(self)
+    }
+
+    /// Checkbox columns read `TRUE`/`FALSE`; forms often say yes/no.
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CellError> {
+        match self.0.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => visitor.visit_bool(true),
+            "false" | "no" | "n" | "0" => visitor.visit_bool(false),
+            _ => Err(de::Error::custom(format!("'{}' is not yes or no", self.0))),
+        }
+    }
+
+    /// Unit variants, by name.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CellError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_cell! {
+        deserialize_i8 => visit_i8, "a number";
+        deserialize_i16 => visit_i16, "a number";
+        deserialize_i32 => visit_i32, "a number";
+        deserialize_i64 => visit_i64, "a number";
+        deserialize_u8 => visit_u8, "a whole number";
+        deserialize_u16 => visit_u16, "a whole number";
+        deserialize_u32 => visit_u32, "a whole number";
+        deserialize_u64 => visit_u64, "a whole number";
+        deserialize_f32 => visit_f32, "a number";
+        deserialize_f64 => visit_f64, "a number";
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct newtype_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Track {
+        Defi,
+        Gaming,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Team {
+        #[serde(rename = "Repo URL", default)]
+        repo_url: String,
+        #[serde(default)]
+        members: u32,
+        #[serde(default)]
+        shipped: bool,
+        score: Option<f64>,
+        #[serde(skip_serializing)]
+        track: Option<Track>,
+    }
+
+    impl SheetRow for Team {
+        const ALIASES: &'static [HeaderRule] = &[HeaderRule::contains("members", "team size", 20)];
+    }
+
+    fn columns(cells: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        cells
+            .iter()
+            .map(|(header, values)| {
+                (
+                    header.to_string(),
+                    values.iter().map(|v| v.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn read(cells: &[(&str, &[&str])]) -> Result<Vec<Team>> {
+        rows_from_columns(&columns(cells), &HeaderRules::new(Vec::new()).unwrap())
+    }
+
+    #[test]
+    fn cells_deserialize_as_the_field_types() {
+        let rows = read(&[
+            ("Repo URL", &[" https://github.com/a/b ", ""]),
+            ("Team Size (people)", &["3", ""]),
+            ("shipped", &["Yes", "FALSE"]),
+            ("score", &["7.5", ""]),
+            ("track", &["gaming", "defi"]),
+        ])
+        .unwrap();
+        assert_eq!(
+            rows,
+            [
+                Team {
+                    repo_url: "https://github.com/a/b".to_string(),
+                    members: 3,
+                    shipped: true,
+                    score: Some(7.5),
+                    track: Some(Track::Gaming),
+                },
+                Team {
+                    repo_url: String::new(),
+                    members: 0,
+                    shipped: false,
+                    score: None,
+                    track: Some(Track::Defi),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn renamed_fields_match_their_header_exactly() {
+        let rows = read(&[("repo url", &["https://github.com/a/b"])]).unwrap();
+        assert_eq!(rows[0].repo_url, "");
+    }
+
+    #[test]
+    fn bad_cells_name_the_row_and_the_value() {
+        let err = read(&[("members", &["2", "lots"])]).unwrap_err();
+        assert_eq!(err.to_string(), "row 3: 'lots' is not a whole number");
+
+        let err = read(&[("shipped", &["maybe"])]).unwrap_err();
+        assert_eq!(err.to_string(), "row 2: 'maybe' is not yes or no");
+
+        assert!(read(&[("track", &["nft"])]).is_err());
+    }
+
+    #[test]
+    fn lists_struct_fields_in_declaration_order() {
+        assert_eq!(
+            struct_fields::<Team>(),
+            ["Repo URL", "members", "shipped", "score", "track"]
+        );
+    }
+
+    #[test]
+    fn writes_fields_as_cell_text() {
+        let team = Team {
+            repo_url: "https://github.com/a/b".to_string(),
+            members: 4,
+            shipped: true,
+            score: None,
+            track: None,
+        };
+        let values = row_values(&team).unwrap();
+        assert_eq!(values["members"], "4");
+        assert_eq!(values["shipped"], "true");
+        assert_eq!(values["score"], "");
+
+        let cols = ["C".to_string(), "D".to_string(), "E".to_string()];
+        let headers = [
+            "members".to_string(),
+            "Notes".to_string(),
+            "Repo URL".to_string(),
+        ];
+        assert_eq!(
+            named_cells(&team, &cols, &headers).unwrap(),
+            [
+                ("C", "4".to_string()),
+                ("E", "https://github.com/a/b".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use crate::cell_format::{literal, url_link};
+use crate::forge::{classify_forge_url_detailed, ForgeRepo, ForgeUrlType};
+use crate::header_rules::HeaderRules;
+use crate::row_locator::{RowLocator, RowMatch};
+use crate::rows::{read_rows, SheetRow};
+use crate::sheet_store::SheetStore;
+use crate::sheets::{write_entered_cells, write_named_cells};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub const CANONICAL_URL_HEADER: &str = "Canonical Repo URL";
+pub const FIRST_SEEN_HEADER: &str = "First Seen";
+pub const LAST_SEEN_HEADER: &str = "Last Seen";
+pub const SEARCH_STATUS_HEADER: &str = "Search Status";
+/// `Search Status` of a repo the latest search returned.
+const LISTED_STATUS: &str = "listed";
+/// `Search Status` of a Search-tab repo that the latest search no longer
+/// returned. Such rows are kept, with their notes, rather than deleted.
+const VANISHED_STATUS: &str = "vanished";
+
+/// Identity of a repo on the Search tab: its forge web URL, lowercased, so
+/// `owner/repo`, `.git` and `/tree/...` spellings of one repo match.
+pub fn search_repo_key(url: &str) -> String {
+    match classify_forge_url_detailed(url).0 {
+        ForgeUrlType::Repo(repo) => ForgeRepo {
+            git_ref: None,
+            ..repo
+        }
+        .web_url()
+        .to_lowercase(),
+        _ => url.trim().trim_end_matches('/').to_lowercase(),
+    }
+}
+
+/// The Search tab columns that identify a row and its state.
+#[derive(Deserialize)]
+struct SearchTabRow {
+    #[serde(rename = "Repo URL", default)]
+    repo_url: String,
+    #[serde(rename = "Canonical Repo URL", default)]
+    canonical_url: String,
+    #[serde(rename = "Search Status", default)]
+    status: String,
+}
+
+impl SheetRow for SearchTabRow {}
+
+/// Rows already on the Search tab, keyed by `search_repo_key` of their
+/// canonical URL (or `Repo URL` when that's blank), with the row each was
+/// read from and its `Search Status`. The rows are only hints: writes
+/// resolve the current row through a `RowLocator`.
+pub async fn read_search_rows(
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    sheet_name: &str,
+    header_rules: &HeaderRules,
+) -> Result<HashMap<String, (usize, String)>> {
+    let rows: Vec<SearchTabRow> =
+        read_rows(sheets, spreadsheet_id, sheet_name, "", header_rules).await?;
+    let mut keyed: HashMap<String, (usize, String)> = HashMap::new();
+    for (i, row) in rows.iter().enumerate() {
+        let url = match row.canonical_url.as_str() {
+            "" => row.repo_url.as_str(),
+            canonical => canonical,
+        };
+        if url.is_empty() {
+            continue;
+        }
+        keyed
+            .entry(search_repo_key(url))
+            .or_insert((i + 2, row.status.clone()));
+    }
+    Ok(keyed)
+}
+
+/// The column paired with `header`.
+fn column_of<'a>(headers: &[String], cols: &'a [String], header: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .position(|h| h == header)
+        .and_then(|i| cols.get(i))
+        .map(|c| c.as_str())
+}
+
+/// Merges one search run into the Search tab: each returned repo updates
+/// the row already holding it (by canonical URL, then by the URL searched)
+/// or gets a new one, and rows the run didn't return are marked vanished.
+/// `headers` and `cols` pair the tab's headers with their columns, `Repo
+/// URL` first; `rows` are the tab's rows read before the run.
+pub struct SearchTab<'a> {
+    sheet_name: &'a str,
+    headers: &'a [String],
+    cols: &'a [String],
+    rows: &'a HashMap<String, (usize, String)>,
+    locator: RowLocator,
+    today: String,
+    seen_keys: Vec<String>,
+    new_repos: Vec<String>,
+}
+
+impl<'a> SearchTab<'a> {
+    pub fn new(
+        sheet_name: &'a str,
+        headers: &'a [String],
+        cols: &'a [String],
+        rows: &'a HashMap<String, (usize, String)>,
+        today: &str,
+    ) -> Self {
+        // Search rows are found by URL at write time, like the main
+        // sheet's: the tab can be sorted or edited during a long run.
+        let key_columns = ["Repo URL", CANONICAL_URL_HEADER]
+            .into_iter()
+            .filter_map(|h| column_of(headers, cols, h))
+            .map(str::to_string)
+            .collect();
+        Self {
+            sheet_name,
+            headers,
+            cols,
+            rows,
+            locator: RowLocator::new(sheet_name, key_columns).with_normalized_keys(search_repo_key),
+            today: today.to_string(),
+            seen_keys: Vec::new(),
+            new_repos: Vec::new(),
+        }
+    }
+
+    /// The column of `header`, when the tab has one.
+    fn col(&self, header: &str) -> Option<&'a str> {
+        column_of(self.headers, self.cols, header)
+    }
+
+    /// Repos the run found that weren't on the tab yet, in write order.
+    pub fn new_repos(&self) -> &[String] {
+        &self.new_repos
+    }
+
+    /// Keys of a returned repo, canonical URL first, so a repo that moved
+    /// since it was first listed keeps its row.
+    fn keys(repo_url: &str, canonical_url: Option<&str>) -> Vec<String> {
+        canonical_url
+            .into_iter()
+            .chain([repo_url])
+            .map(search_repo_key)
+            .collect()
+    }
+
+    /// Records that the run returned `repo_url`, so its row isn't marked
+    /// vanished even when it gets no result written.
+    pub fn see(&mut self, repo_url: &str, canonical_url: Option<&str>) {
+        for key in Self::keys(repo_url, canonical_url) {
+            if !self.seen_keys.contains(&key) {
+                self.seen_keys.push(key);
+            }
+        }
+    }
+
+    /// Writes `cells` (the result columns) for a returned repo, with its
+    /// `Last Seen` date and status; a repo new to the tab also gets its URL
+    /// and `First Seen` date in the next free row. Returns the row written,
+    /// or `None` when several rows hold the repo.
+    pub async fn write(
+        &mut self,
+        sheets: &dyn SheetStore,
+        spreadsheet_id: &str,
+        repo_url: &str,
+        canonical_url: Option<&str>,
+        mut cells: Vec<(&'a str, String)>,
+    ) -> Result<Option<usize>> {
+        self.see(repo_url, canonical_url);
+        let keys = Self::keys(repo_url, canonical_url);
+        let hint = keys
+            .iter()
+            .find_map(|k| self.rows.get(k))
+            .map_or(0, |r| r.0);
+        cells.extend(
+            self.col(LAST_SEEN_HEADER)
+                .map(|c| (c, literal(&self.today))),
+        );
+        cells.extend(
+            self.col(SEARCH_STATUS_HEADER)
+                .map(|c| (c, literal(LISTED_STATUS))),
+        );
+
+        let index = self.locator.snapshot(sheets, spreadsheet_id).await?;
+        let located = keys
+            .iter()
+            .map(|k| (k, index.locate(k, hint)))
+            .find(|(_, m)| *m != RowMatch::Missing);
+        let row = match located {
+            Some((_, RowMatch::Found(row))) => {
+                if hint != 0 && row != hint {
+                    println!("↕️  '{}' moved from row {} to row {}.", repo_url, hint, row);
+                }
+                row
+            }
+            Some((key, RowMatch::Ambiguous(rows))) => {
+                eprintln!(
+                    "⚠️  '{}' is in rows {:?} of {}; skipping ambiguous write.",
+                    key, rows, self.sheet_name
+                );
+                return Ok(None);
+            }
+            _ => {
+                cells.push((self.cols[0].as_str(), url_link(repo_url)));
+                cells.extend(
+                    self.col(FIRST_SEEN_HEADER)
+                        .map(|c| (c, literal(&self.today))),
+                );
+                self.new_repos.push(repo_url.to_string());
+                index.next_row()
+            }
+        };
+        println!("Writing {} row", row);
+        write_entered_cells(sheets, spreadsheet_id, self.sheet_name, row, &cells).await?;
+        println!("✅ Row {} updated in {}", row, self.sheet_name);
+        Ok(Some(row))
+    }
+
+    /// Sets `Search Status` to vanished on every row read before the run
+    /// whose repo the run didn't return, wherever the row is now. Rows
+    /// already marked are left alone. Returns the rows written.
+    pub async fn mark_vanished(
+        &self,
+        sheets: &dyn SheetStore,
+        spreadsheet_id: &str,
+    ) -> Result<Vec<usize>> {
+        let Some(status_col) = self.col(SEARCH_STATUS_HEADER) else {
+            return Ok(Vec::new());
+        };
+        let mut vanished: Vec<(usize, &String)> = self
+            .rows
+            .iter()
+            .filter(|(key, (_, status))| {
+                !self.seen_keys.contains(key) && status.as_str() != VANISHED_STATUS
+            })
+            .map(|(key, (row, _))| (*row, key))
+            .collect();
+        vanished.sort();
+        vanished.dedup_by_key(|(row, _)| *row);
+
+        let index = self.locator.snapshot(sheets, spreadsheet_id).await?;
+        let mut written = Vec::new();
+        for (hint, key) in &vanished {
+            let Some(row) = index.row_for(key, *hint) else {
+                continue;
+            };
+            println!("👻 {} is no longer returned by search (row {})", key, row);
+            write_named_cells(
+                sheets,
+                spreadsheet_id,
+                self.sheet_name,
+                row,
+                &[(status_col, VANISHED_STATUS.to_string())],
+            )
+            .await?;
+            written.push(row);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet_store::MemorySheetStore;
+
+    const TAB: &str = "Search";
+
+    fn strings(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn headers() -> Vec<String> {
+        strings(&[
+            "Repo URL",
+            "Scraper Result (JSON)",
+            CANONICAL_URL_HEADER,
+            FIRST_SEEN_HEADER,
+            LAST_SEEN_HEADER,
+            SEARCH_STATUS_HEADER,
+        ])
+    }
+
+    fn cols() -> Vec<String> {
+        strings(&["A", "B", "C", "D", "E", "F"])
+    }
+
+    /// A Search tab holding `rows` under `headers()`.
+    async fn search_tab(rows: &[&[&str]]) -> (MemorySheetStore, HashMap<String, (usize, String)>) {
+        let sheets = MemorySheetStore::new();
+        let mut tab = vec![headers()];
+        tab.extend(rows.iter().map(|r| strings(r)));
+        sheets.add_tab(TAB, tab);
+        let read = read_search_rows(&sheets, "id", TAB, &HeaderRules::new(Vec::new()).unwrap())
+            .await
+            .unwrap();
+        (sheets, read)
+    }
+
+    fn result(json: &str) -> Vec<(&'static str, String)> {
+        vec![("B", literal(json))]
+    }
+
+    #[test]
+    fn spellings_of_one_repo_share_a_key() {
+        let key = search_repo_key("https://github.com/Team/Game");
+        assert_eq!(key, "https://github.com/team/game");
+        for spelling in [
+            "team/game",
+            "https://github.com/team/game.git",
+            "https://github.com/team/game/tree/main/programs",
+            "github.com/TEAM/game/",
+        ] {
+            assert_eq!(search_repo_key(spelling), key, "{}", spelling);
+        }
+        assert_eq!(search_repo_key(" Not a repo/ "), "not a repo");
+    }
+
+    #[tokio::test]
+    async fn reads_rows_by_canonical_url_then_repo_url() {
+        let (_, rows) = search_tab(&[
+            &[
+                "team/game",
+                "",
+                "https://github.com/studio/game",
+                "",
+                "",
+                "listed",
+            ],
+            &["https://github.com/team/dex.git", "", "", "", "", ""],
+            &["", "{}", "", "", "", ""],
+            &["https://github.com/team/dex", "", "", "", "", "vanished"],
+        ])
+        .await;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows["https://github.com/studio/game"],
+            (2, "listed".to_string())
+        );
+        // The first row holding a repo is the one kept.
+        assert_eq!(rows["https://github.com/team/dex"], (3, String::new()));
+    }
+
+    #[tokio::test]
+    async fn a_new_repo_gets_the_next_row_and_both_dates() {
+        let (sheets, rows) = search_tab(&[&[
+            "https://github.com/team/game",
+            "{}",
+            "",
+            "2026-01-01",
+            "2026-01-01",
+            "listed",
+        ]])
+        .await;
+        let (headers, cols) = (headers(), cols());
+        let mut tab = SearchTab::new(TAB, &headers, &cols, &rows, "2026-10-18");
+
+        let row = tab
+            .write(&sheets, "id", "team/dex", None, result("{\"dex\":1}"))
+            .await
+            .unwrap();
+        assert_eq!(row, Some(3));
+        assert_eq!(tab.new_repos(), ["team/dex"]);
+        let written = &sheets.tab(TAB).unwrap()[2];
+        assert_eq!(
+            written[1..],
+            strings(&["{\"dex\":1}", "", "2026-10-18", "2026-10-18", "listed"])
+        );
+        assert!(written[0].contains("team/dex"));
+    }
+
+    #[tokio::test]
+    async fn a_listed_repo_keeps_its_row_and_first_seen_date() {
+        let (sheets, rows) = search_tab(&[
+            &[
+                "https://github.com/team/game",
+                "{}",
+                "",
+                "2026-01-01",
+                "2026-01-01",
+                "vanished",
+            ],
+            &[
+                "https://github.com/team/dex",
+                "{}",
+                "",
+                "2026-02-01",
+                "2026-02-01",
+                "listed",
+            ],
+        ])
+        .await;
+        let (headers, cols) = (headers(), cols());
+        let mut tab = SearchTab::new(TAB, &headers, &cols, &rows, "2026-10-18");
+
+        // Another spelling of the same repo.
+        let row = tab
+            .write(&sheets, "id", "team/game.git", None, result("{\"new\":1}"))
+            .await
+            .unwrap();
+        assert_eq!(row, Some(2));
+        // Moved since it was listed: the URL searched still finds its row.
+        let row = tab
+            .write(
+                &sheets,
+                "id",
+                "https://github.com/team/dex",
+                Some("https://github.com/studio/dex"),
+                result("{}"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(row, Some(3));
+        assert!(tab.new_repos().is_empty());
+
+        let written = sheets.tab(TAB).unwrap();
+        assert_eq!(written.len(), 3);
+        assert_eq!(
+            written[1][1..],
+            strings(&["{\"new\":1}", "", "2026-01-01", "2026-10-18", "listed"])
+        );
+        assert_eq!(
+            written[2][3..],
+            strings(&["2026-02-01", "2026-10-18", "listed"])
+        );
+    }
+
+    #[tokio::test]
+    async fn rows_the_search_no_longer_returns_are_marked_vanished() {
+        let (sheets, rows) = search_tab(&[
+            &["https://github.com/team/game", "", "", "", "", "listed"],
+            &["https://github.com/team/dex", "", "", "", "", "listed"],
+            &["https://github.com/team/old", "", "", "", "", "vanished"],
+            &["https://github.com/team/bot", "", "", "", "", "listed"],
+        ])
+        .await;
+        let (headers, cols) = (headers(), cols());
+        let mut tab = SearchTab::new(TAB, &headers, &cols, &rows, "2026-10-18");
+        tab.write(&sheets, "id", "team/game", None, result("{}"))
+            .await
+            .unwrap();
+        // Returned but not scanned (no commit): still not vanished.
+        tab.see("team/bot", None);
+
+        // Someone sorted the tab during the run.
+        let mut sorted = sheets.tab(TAB).unwrap();
+        sorted[1..].reverse();
+        sheets.add_tab(TAB, sorted);
+
+        let marked = tab.mark_vanished(&sheets, "id").await.unwrap();
+        assert_eq!(marked, [4]);
+        let statuses: Vec<(String, String)> = sheets.tab(TAB).unwrap()[1..]
+            .iter()
+            .map(|r| (r[0].clone(), r[5].clone()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (
+                    "https://github.com/team/bot".to_string(),
+                    "listed".to_string()
+                ),
+                (
+                    "https://github.com/team/old".to_string(),
+                    "vanished".to_string()
+                ),
+                (
+                    "https://github.com/team/dex".to_string(),
+                    "vanished".to_string()
+                ),
+                (
+                    "https://github.com/team/game".to_string(),
+                    "listed".to_string()
+                ),
+            ]
+        );
+    }
+}
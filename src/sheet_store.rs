@@ -0,0 +1,963 @@
+use crate::a1::{A1Range, Column};
+use crate::cell_format::{displayed, ColumnFormat, HighlightWhen, Rgb};
+use crate::quota::{QuotaKind, SheetsQuota};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use google_sheets4::{
+    api::{
+        AddConditionalFormatRuleRequest, AddSheetRequest, AppendDimensionRequest,
+        BatchUpdateSpreadsheetRequest, BatchUpdateValuesRequest, BooleanCondition, BooleanRule,
+        CellData, CellFormat, Color, ConditionValue, ConditionalFormatRule, GridRange,
+        NumberFormat, RepeatCellRequest, Request, Sheet, SheetProperties, ValueRange,
+    },
+    Sheets,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Where the sheet helpers in `sheets.rs` read and write. Ranges are
+/// `A1Range`s in their string form (`'Tab'`, `'Tab'!1:1`, `'Tab'!C2:C`);
+/// values come back row-major with trailing empty cells and rows dropped,
+/// the way the Sheets API returns them.
+#[async_trait]
+pub trait SheetStore: Send + Sync {
+    async fn read_values(&self, spreadsheet_id: &str, range: &str) -> Result<Vec<Vec<String>>>;
+
+    /// Several ranges at once, one result per range in request order.
+    async fn read_ranges(
+        &self,
+        spreadsheet_id: &str,
+        ranges: &[String],
+    ) -> Result<Vec<Vec<Vec<String>>>> {
+        let mut results = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            results.push(self.read_values(spreadsheet_id, range).await?);
+        }
+        Ok(results)
+    }
+
+    /// Row 1 of `sheet_name`.
+    async fn read_header(&self, spreadsheet_id: &str, sheet_name: &str) -> Result<Vec<String>> {
+        let range = A1Range::row(sheet_name, 1).to_string();
+        let rows = self.read_values(spreadsheet_id, &range).await?;
+        Ok(rows.into_iter().next().unwrap_or_default())
+    }
+
+    /// Display text and link target of every cell from row 2 down, one list
+    /// per column letter in `columns`. Backends without links return the
+    /// text alone.
+    async fn read_links(
+        &self,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        columns: &[String],
+    ) -> Result<Vec<Vec<LinkedCell>>> {
+        let ranges = column_ranges(sheet_name, columns)?;
+        let values = self.read_ranges(spreadsheet_id, &ranges).await?;
+        Ok(values
+            .into_iter()
+            .map(|rows| {
+                rows.into_iter()
+                    .map(|row| LinkedCell {
+                        text: row.into_iter().next().unwrap_or_default(),
+                        link: None,
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Writes every range in one request, values stored as given.
+    async fn write_values(&self, spreadsheet_id: &str, updates: Vec<ValueRange>) -> Result<()>;
+
+    /// Writes values as if typed into the sheet (`USER_ENTERED`), so
+    /// formulas such as `HYPERLINK` and numbers take effect; build them
+    /// with `cell_format`. Backends without formulas store what the sheet
+    /// would show.
+    async fn write_entered(&self, spreadsheet_id: &str, updates: Vec<ValueRange>) -> Result<()> {
+        let updates = updates
+            .into_iter()
+            .map(|update| ValueRange {
+                values: update.values.map(|rows| {
+                    rows.iter()
+                        .map(|row| row.iter().map(|v| displayed(v)).collect())
+                        .collect()
+                }),
+                ..update
+            })
+            .collect();
+        self.write_values(spreadsheet_id, updates).await
+    }
+
+    /// Applies number formats, clipping and highlight rules to columns of
+    /// `sheet_name`. Backends without formatting ignore it.
+    async fn format_columns(
+        &self,
+        _spreadsheet_id: &str,
+        _sheet_name: &str,
+        _formats: &[ColumnFormat],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Grows the tab to at least `needed_cols` columns; writes past the
+    /// grid's right edge fail otherwise.
+    async fn expand_grid(
+        &self,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        needed_cols: usize,
+    ) -> Result<()>;
+
+    /// Creates `sheet_name` (empty) if the spreadsheet has no such tab.
+    async fn ensure_sheet(&self, spreadsheet_id: &str, sheet_name: &str) -> Result<()>;
+
+    /// A URL opening `sheet_name` at `row`, for linking to it from another
+    /// cell. `None` for backends that can't be linked into.
+    async fn sheet_link(
+        &self,
+        _spreadsheet_id: &str,
+        _sheet_name: &str,
+        _row: usize,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Sends any writes the backend is still holding back. Call before
+    /// exiting.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A run's result combined with the `flush` after it: the run's error comes
+/// first, and a flush that failed too is appended rather than hiding it.
+pub fn with_flush_result(run: Result<()>, flushed: Result<()>) -> Result<()> {
+    match (run, flushed) {
+        (Err(e), Err(flush)) => Err(anyhow!(
+            "{:#}; flushing queued writes also failed: {:#}",
+            e,
+            flush
+        )),
+        (run, flushed) => run.and(flushed),
+    }
+}
+
+/// `'Tab'!C2:C` for each column letter in `columns`.
+fn column_ranges(sheet_name: &str, columns: &[String]) -> Result<Vec<String>> {
+    columns
+        .iter()
+        .map(|col| Ok(A1Range::column_from(sheet_name, col.parse::<Column>()?, 2).to_string()))
+        .collect()
+}
+
+/// One cell as read by `SheetStore::read_links`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkedCell {
+    pub text: String,
+    pub link: Option<String>,
+}
+
+/// The Google Sheets API, paced by `SheetsQuota`. While the write quota
+/// is spent, writes queue up instead of waiting and go out together as one
+/// batch request once it has room again; reads send the queue first so
+/// they always see earlier writes.
+pub struct GoogleSheetStore {
+    sheets: Sheets,
+    quota: SheetsQuota,
+    /// Writes not sent yet, with their spreadsheet id and value input
+    /// option (`RAW` or `USER_ENTERED`), in write order.
+    pending: Mutex<Vec<(String, &'static str, ValueRange)>>,
+}
+
+impl GoogleSheetStore {
+    pub fn new(sheets: Sheets) -> Self {
+        Self {
+            sheets,
+            quota: SheetsQuota::from_env(),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sends every queued write, one batch request per spreadsheet and
+    /// input option, waiting for write quota if need be. A batch that fails
+    /// goes back on the queue with every batch after it, ahead of anything
+    /// queued meanwhile, so a later flush retries them.
+    async fn send_pending(&self) -> Result<()> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        let mut batches: Vec<(String, &str, Vec<ValueRange>)> = Vec::new();
+        for (spreadsheet_id, input, update) in pending {
+            match batches
+                .iter_mut()
+                .find(|(id, i, _)| *id == spreadsheet_id && *i == input)
+            {
+                Some((_, _, updates)) => updates.push(update),
+                None => batches.push((spreadsheet_id, input, vec![update])),
+            }
+        }
+        let mut batches = batches.into_iter();
+        while let Some((spreadsheet_id, input, updates)) = batches.next() {
+            let req = BatchUpdateValuesRequest {
+                value_input_option: Some(input.to_string()),
+                data: Some(updates),
+                ..Default::default()
+            };
+            let sent = self
+                .quota
+                .call(QuotaKind::Write, "write", || {
+                    self.sheets
+                        .spreadsheets()
+                        .values_batch_update(req.clone(), &spreadsheet_id)
+                        .doit()
+                })
+                .await;
+            if let Err(e) = sent {
+                let failed = (spreadsheet_id, input, req.data.unwrap_or_default());
+                let unsent: Vec<_> = std::iter::once(failed)
+                    .chain(batches)
+                    .flat_map(|(id, input, updates)| {
+                        updates.into_iter().map(move |u| (id.clone(), input, u))
+                    })
+                    .collect();
+                let mut pending = self.pending.lock().unwrap();
+                pending.splice(0..0, unsent);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Queues `updates`, then sends the queue unless the write quota is
+    /// spent.
+    async fn queue_writes(
+        &self,
+        spreadsheet_id: &str,
+        input: &'static str,
+        updates: Vec<ValueRange>,
+    ) -> Result<()> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+        let was_empty = {
+            let mut pending = self.pending.lock().unwrap();
+            let was_empty = pending.is_empty();
+            pending.extend(
+                updates
+                    .into_iter()
+                    .map(|u| (spreadsheet_id.to_string(), input, u)),
+            );
+            was_empty
+        };
+        if self.quota.limiter(QuotaKind::Write).has_room() {
+            return self.send_pending().await;
+        }
+        if was_empty {
+            println!("📦 Sheets write quota spent; queueing writes until it has room.");
+        }
+        Ok(())
+    }
+
+    /// The tab named `sheet_name`, with its properties and conditional
+    /// formats, if there is one.
+    async fn sheet(&self, spreadsheet_id: &str, sheet_name: &str) -> Result<Option<Sheet>> {
+        let spreadsheet = self
+            .quota
+            .call(QuotaKind::Read, "grid read", || {
+                self.sheets.spreadsheets().get(spreadsheet_id).doit()
+            })
+            .await?;
+        Ok(spreadsheet
+            .sheets
+            .unwrap_or_default()
+            .into_iter()
+            .find(|s| s.properties.as_ref().and_then(|p| p.title.as_deref()) == Some(sheet_name)))
+    }
+
+    async fn find_sheet(&self, spreadsheet_id: &str, sheet_name: &str) -> Result<Sheet> {
+        self.sheet(spreadsheet_id, sheet_name)
+            .await?
+            .ok_or_else(|| anyhow!("sheet '{}' not found in spreadsheet", sheet_name))
+    }
+
+    async fn send_requests(
+        &self,
+        spreadsheet_id: &str,
+        what: &str,
+        requests: Vec<Request>,
+    ) -> Result<()> {
+        let req = BatchUpdateSpreadsheetRequest {
+            requests: Some(requests),
+            ..Default::default()
+        };
+        self.quota
+            .call(QuotaKind::Write, what, || {
+                self.sheets
+                    .spreadsheets()
+                    .batch_update(req.clone(), spreadsheet_id)
+                    .doit()
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Rows 2 down of one column.
+fn data_column(sheet_id: i32, column: Column) -> GridRange {
+    GridRange {
+        sheet_id: Some(sheet_id),
+        start_row_index: Some(1),
+        start_column_index: Some(column.index() as i32),
+        end_column_index: Some(column.number() as i32),
+        ..Default::default()
+    }
+}
+
+fn color(rgb: Rgb) -> Color {
+    Color {
+        red: Some(rgb.0),
+        green: Some(rgb.1),
+        blue: Some(rgb.2),
+        alpha: None,
+    }
+}
+
+fn condition(when: &HighlightWhen) -> BooleanCondition {
+    let (kind, value) = match when {
+        HighlightWhen::TextStartsWith(text) => ("TEXT_STARTS_WITH", text.clone()),
+        HighlightWhen::NumberGreaterThan(n) => ("NUMBER_GREATER", n.to_string()),
+        HighlightWhen::Formula(formula) => ("CUSTOM_FORMULA", formula.clone()),
+    };
+    BooleanCondition {
+        type_: Some(kind.to_string()),
+        values: Some(vec![ConditionValue {
+            user_entered_value: Some(value),
+            relative_date: None,
+        }]),
+    }
+}
+
+/// Whether `existing` already has a rule on the same range with the same
+/// condition, so re-runs don't stack duplicate rules.
+fn has_rule(
+    existing: &[ConditionalFormatRule],
+    range: &GridRange,
+    cond: &BooleanCondition,
+) -> bool {
+    let key = |ranges: &Option<Vec<GridRange>>, cond: Option<&BooleanCondition>| {
+        serde_json::to_value((ranges, cond)).unwrap_or_default()
+    };
+    let wanted = key(&Some(vec![range.clone()]), Some(cond));
+    existing.iter().any(|rule| {
+        let cond = rule
+            .boolean_rule
+            .as_ref()
+            .and_then(|b| b.condition.as_ref());
+        key(&rule.ranges, cond) == wanted
+    })
+}
+
+/// Every link target in a cell: the cell-level `hyperlink` plus the links
+/// of individual rich-text runs (several repos linked from one cell), in
+/// order and de-duplicated. `None` when the cell links nowhere.
+fn cell_links(cell: &CellData) -> Option<String> {
+    let mut links: Vec<String> = Vec::new();
+    let runs = cell.text_format_runs.iter().flatten();
+    let run_links = runs.filter_map(|r| r.format.as_ref()?.link.as_ref()?.uri.clone());
+    for link in cell.hyperlink.clone().into_iter().chain(run_links) {
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    (!links.is_empty()).then(|| links.join("\n"))
+}
+
+#[async_trait]
+impl SheetStore for GoogleSheetStore {
+    async fn read_values(&self, spreadsheet_id: &str, range: &str) -> Result<Vec<Vec<String>>> {
+        self.send_pending().await?;
+        let resp = self
+            .quota
+            .call(QuotaKind::Read, "read", || {
+                self.sheets
+                    .spreadsheets()
+                    .values_get(spreadsheet_id, range)
+                    .doit()
+            })
+            .await?;
+        Ok(resp.values.unwrap_or_default())
+    }
+
+    async fn read_ranges(
+        &self,
+        spreadsheet_id: &str,
+        ranges: &[String],
+    ) -> Result<Vec<Vec<Vec<String>>>> {
+        if ranges.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.send_pending().await?;
+        let resp = self
+            .quota
+            .call(QuotaKind::Read, "batch read", || {
+                let mut call = self.sheets.spreadsheets().values_batch_get(spreadsheet_id);
+                for range in ranges {
+                    call = call.add_ranges(range);
+                }
+                call.doit()
+            })
+            .await?;
+        let value_ranges = resp.value_ranges.unwrap_or_default();
+        let mut results: Vec<Vec<Vec<String>>> = value_ranges
+            .into_iter()
+            .map(|vr| vr.values.unwrap_or_default())
+            .collect();
+        results.resize(ranges.len(), Vec::new());
+        Ok(results)
+    }
+
+    async fn read_links(
+        &self,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        columns: &[String],
+    ) -> Result<Vec<Vec<LinkedCell>>> {
+        if columns.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ranges = column_ranges(sheet_name, columns)?;
+        self.send_pending().await?;
+        let spreadsheet = self
+            .quota
+            .call(QuotaKind::Read, "link read", || {
+                let mut call = self.sheets.spreadsheets().get(spreadsheet_id).param(
+                    "fields",
+                    "sheets(data(rowData(values(formattedValue,hyperlink,textFormatRuns(format(link(uri)))))))",
+                );
+                for range in &ranges {
+                    call = call.add_ranges(range);
+                }
+                call.doit()
+            })
+            .await?;
+        // One GridData per requested range, in request order.
+        let grids = spreadsheet
+            .sheets
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .and_then(|s| s.data)
+            .unwrap_or_default();
+        let mut results: Vec<Vec<LinkedCell>> = grids
+            .into_iter()
+            .map(|grid| {
+                grid.row_data
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|row| {
+                        let Some(cell) = row.values.as_ref().and_then(|v| v.first()) else {
+                            return LinkedCell::default();
+                        };
+                        LinkedCell {
+                            text: cell.formatted_value.clone().unwrap_or_default(),
+                            link: cell_links(cell),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        results.resize(columns.len(), Vec::new());
+        Ok(results)
+    }
+
+    /// `values.batchUpdate` counts as ONE write request against the
+    /// 60/min/user quota regardless of how many ranges it contains, so
+    /// while the quota is spent the writes are queued and merged into the
+    /// next request rather than waited on.
+    async fn write_values(&self, spreadsheet_id: &str, updates: Vec<ValueRange>) -> Result<()> {
+        self.queue_writes(spreadsheet_id, "RAW", updates).await
+    }
+
+    async fn write_entered(&self, spreadsheet_id: &str, updates: Vec<ValueRange>) -> Result<()> {
+        self.queue_writes(spreadsheet_id, "USER_ENTERED", updates)
+            .await
+    }
+
+    /// New sheets default to 26 columns and Google Sheets enforces this as
+    /// a hard limit on writes.
+    async fn expand_grid(
+        &self,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        needed_cols: usize,
+    ) -> Result<()> {
+        self.send_pending().await?;
+        let sheet = self.find_sheet(spreadsheet_id, sheet_name).await?;
+        let props = sheet
+            .properties
+            .ok_or_else(|| anyhow!("sheet '{}' missing properties", sheet_name))?;
+        let sheet_id = props
+            .sheet_id
+            .ok_or_else(|| anyhow!("sheet '{}' missing sheet_id", sheet_name))?;
+        let current_cols = props
+            .grid_properties
+            .and_then(|g| g.column_count)
+            .unwrap_or(0) as usize;
+
+        if needed_cols <= current_cols {
+            return Ok(());
+        }
+
+        let to_add = (needed_cols - current_cols) as i32;
+        let request = Request {
+            append_dimension: Some(AppendDimensionRequest {
+                sheet_id: Some(sheet_id),
+                dimension: Some("COLUMNS".to_string()),
+                length: Some(to_add),
+            }),
+            ..Default::default()
+        };
+        self.send_requests(spreadsheet_id, "grid resize", vec![request])
+            .await
+    }
+
+    /// One `batchUpdate` for all of `formats`: a `repeatCell` per column
+    /// with a number format or clipping, and an `addConditionalFormatRule`
+    /// per highlight the tab doesn't have yet.
+    async fn format_columns(
+        &self,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        formats: &[ColumnFormat],
+    ) -> Result<()> {
+        if formats.is_empty() {
+            return Ok(());
+        }
+        self.send_pending().await?;
+        let sheet = self.find_sheet(spreadsheet_id, sheet_name).await?;
+        let sheet_id = sheet
+            .properties
+            .and_then(|p| p.sheet_id)
+            .ok_or_else(|| anyhow!("sheet '{}' missing sheet_id", sheet_name))?;
+        let existing = sheet.conditional_formats.unwrap_or_default();
+
+        let mut requests: Vec<Request> = Vec::new();
+        for format in formats {
+            let range = data_column(sheet_id, format.column);
+            let mut fields: Vec<&str> = Vec::new();
+            let mut cell_format = CellFormat::default();
+            if let Some(pattern) = format.number_pattern {
+                cell_format.number_format = Some(NumberFormat {
+                    type_: Some("NUMBER".to_string()),
+                    pattern: Some(pattern.to_string()),
+                });
+                fields.push("userEnteredFormat.numberFormat");
+            }
+            if format.clip {
+                cell_format.wrap_strategy = Some("CLIP".to_string());
+                fields.push("userEnteredFormat.wrapStrategy");
+            }
+            if !fields.is_empty() {
+                requests.push(Request {
+                    repeat_cell: Some(RepeatCellRequest {
+                        range: Some(range.clone()),
+                        cell: Some(CellData {
+                            user_entered_format: Some(cell_format),
+                            ..Default::default()
+                        }),
+                        fields: Some(fields.join(",")),
+                    }),
+                    ..Default::default()
+                });
+            }
+            for highlight in &format.highlights {
+                let cond = condition(&highlight.when);
+                if has_rule(&existing, &range, &cond) {
+                    continue;
+                }
+                requests.push(Request {
+                    add_conditional_format_rule: Some(AddConditionalFormatRuleRequest {
+                        index: Some(0),
+                        rule: Some(ConditionalFormatRule {
+                            ranges: Some(vec![range.clone()]),
+                            boolean_rule: Some(BooleanRule {
+                                condition: Some(cond),
+                                format: Some(CellFormat {
+                                    background_color: Some(color(highlight.color)),
+                                    ..Default::default()
+                                }),
+                            }),
+                            ..Default::default()
+                        }),
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+        if requests.is_empty() {
+            return Ok(());
+        }
+        self.send_requests(spreadsheet_id, "format", requests).await
+    }
+
+    async fn ensure_sheet(&self, spreadsheet_id: &str, sheet_name: &str) -> Result<()> {
+        if self.sheet(spreadsheet_id, sheet_name).await?.is_some() {
+            return Ok(());
+        }
+        let request = Request {
+            add_sheet: Some(AddSheetRequest {
+                properties: Some(SheetProperties {
+                    title: Some(sheet_name.to_string()),
+                    ..Default::default()
+                }),
+            }),
+            ..Default::default()
+        };
+        self.send_requests(spreadsheet_id, "add sheet", vec![request])
+            .await
+    }
+
+    async fn sheet_link(
+        &self,
+        spreadsheet_id: &str,
+        sheet_name: &str,
+        row: usize,
+    ) -> Result<Option<String>> {
+        let sheet_id = self
+            .find_sheet(spreadsheet_id, sheet_name)
+            .await?
+            .properties
+            .and_then(|p| p.sheet_id)
+            .ok_or_else(|| anyhow!("sheet '{}' missing sheet_id", sheet_name))?;
+        Ok(Some(format!(
+            "https://docs.google.com/spreadsheets/d/{}/edit#gid={}&range=A{}",
+            spreadsheet_id, sheet_id, row
+        )))
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.send_pending().await
+    }
+}
+
+/// Default grid width of a new Google sheet tab.
+const DEFAULT_GRID_COLUMNS: usize = 26;
+
+#[derive(Debug, Clone, Default)]
+struct MemoryTab {
+    rows: Vec<Vec<String>>,
+    columns: usize,
+}
+
+/// Tabs held in memory, with the same range, trimming and grid-width rules
+/// as Google Sheets, so the sheet logic can run without the API.
+#[derive(Debug, Default)]
+pub struct MemorySheetStore {
+    tabs: Mutex<HashMap<String, MemoryTab>>,
+    /// Files have no grid; writes may land in any column.
+    unbounded: bool,
+}
+
+impl MemorySheetStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn without_grid_limit() -> Self {
+        Self {
+            unbounded: true,
+            ..Self::default()
+        }
+    }
+
+    /// Adds (or replaces) a tab holding `rows`, row 1 first.
+    pub fn add_tab(&self, sheet_name: &str, rows: Vec<Vec<String>>) {
+        let columns = rows
+            .iter()
+            .map(|r| r.len())
+            .max()
+            .unwrap_or(0)
+            .max(DEFAULT_GRID_COLUMNS);
+        self.tabs
+            .lock()
+            .unwrap()
+            .insert(sheet_name.to_string(), MemoryTab { rows, columns });
+    }
+
+    /// Current contents of a tab, or `None` if it doesn't exist.
+    pub fn tab(&self, sheet_name: &str) -> Option<Vec<Vec<String>>> {
+        let tabs = self.tabs.lock().unwrap();
+        tabs.get(sheet_name).map(|t| t.rows.clone())
+    }
+}
+
+#[async_trait]
+impl SheetStore for MemorySheetStore {
+    async fn read_values(&self, _spreadsheet_id: &str, range: &str) -> Result<Vec<Vec<String>>> {
+        let grid: A1Range = range.parse()?;
+        let (start_row, start_col) = grid.start_index();
+        let (end_row, end_col) = grid.end_index();
+        let tabs = self.tabs.lock().unwrap();
+        let tab = tabs
+            .get(grid.sheet_name()?)
+            .ok_or_else(|| anyhow!("Unable to parse range: {}", range))?;
+        let end_row = end_row.map_or(tab.rows.len(), |r| r + 1);
+        let mut values: Vec<Vec<String>> = tab
+            .rows
+            .iter()
+            .take(end_row)
+            .skip(start_row)
+            .map(|row| {
+                let end_col = end_col.map_or(row.len(), |c| (c + 1).min(row.len()));
+                let mut cells: Vec<String> = row
+                    .get(start_col.min(end_col)..end_col)
+                    .unwrap_or_default()
+                    .to_vec();
+                while cells.last().is_some_and(|c| c.is_empty()) {
+                    cells.pop();
+                }
+                cells
+            })
+            .collect();
+        while values.last().is_some_and(|r| r.is_empty()) {
+            values.pop();
+        }
+        Ok(values)
+    }
+
+    async fn write_values(&self, _spreadsheet_id: &str, updates: Vec<ValueRange>) -> Result<()> {
+        let mut tabs = self.tabs.lock().unwrap();
+        for update in updates {
+            let range = update.range.unwrap_or_default();
+            let grid: A1Range = range.parse()?;
+            let (start_row, start_col) = grid.start_index();
+            let tab = tabs
+                .get_mut(grid.sheet_name()?)
+                .ok_or_else(|| anyhow!("Unable to parse range: {}", range))?;
+            let mut values = update.values.unwrap_or_default();
+            if update.major_dimension.as_deref() == Some("COLUMNS") {
+                let height = values.iter().map(|c| c.len()).max().unwrap_or(0);
+                values = (0..height)
+                    .map(|r| {
+                        values
+                            .iter()
+                            .map(|c| c.get(r).cloned().unwrap_or_default())
+                            .collect()
+                    })
+                    .collect();
+            }
+            for (r, row_values) in values.into_iter().enumerate() {
+                let row = start_row + r;
+                let last_col = start_col + row_values.len();
+                if !self.unbounded && last_col > tab.columns {
+                    bail!(
+                        "Range ({}) exceeds grid limits. Max columns: {}",
+                        range,
+                        tab.columns
+                    );
+                }
+                if tab.rows.len() <= row {
+                    tab.rows.resize(row + 1, Vec::new());
+                }
+                let cells = &mut tab.rows[row];
+                if cells.len() < last_col {
+                    cells.resize(last_col, String::new());
+                }
+                for (c, value) in row_values.into_iter().enumerate() {
+                    cells[start_col + c] = value;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn expand_grid(
+        &self,
+        _spreadsheet_id: &str,
+        sheet_name: &str,
+        needed_cols: usize,
+    ) -> Result<()> {
+        let mut tabs = self.tabs.lock().unwrap();
+        let tab = tabs
+            .get_mut(sheet_name)
+            .ok_or_else(|| anyhow!("sheet '{}' not found in spreadsheet", sheet_name))?;
+        tab.columns = tab.columns.max(needed_cols);
+        Ok(())
+    }
+
+    async fn ensure_sheet(&self, _spreadsheet_id: &str, sheet_name: &str) -> Result<()> {
+        if self.tab(sheet_name).is_none() {
+            self.add_tab(sheet_name, Vec::new());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheets::{resolve_or_append_columns, write_row};
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn teams() -> MemorySheetStore {
+        let sheets = MemorySheetStore::new();
+        sheets.add_tab(
+            "Founder's Camp",
+            vec![
+                row(&["Team", "GitHub Repo", "Notes"]),
+                row(&["Ann", "https://github.com/ann/game", ""]),
+                row(&["Bob", "", ""]),
+                row(&[]),
+            ],
+        );
+        sheets
+    }
+
+    #[tokio::test]
+    async fn reads_ranges_trimmed_like_the_sheets_api() {
+        let sheets = teams();
+        let header = sheets.read_header("", "Founder's Camp").await.unwrap();
+        assert_eq!(header, ["Team", "GitHub Repo", "Notes"]);
+
+        // Trailing blank cells and rows are dropped.
+        let values = sheets
+            .read_values("", "'Founder''s Camp'!A2:C")
+            .await
+            .unwrap();
+        assert_eq!(
+            values,
+            [row(&["Ann", "https://github.com/ann/game"]), row(&["Bob"])]
+        );
+
+        let columns = sheets
+            .read_ranges(
+                "",
+                &[
+                    "'Founder''s Camp'!B2:B".to_string(),
+                    "'Founder''s Camp'!C1:C".to_string(),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(columns[0], [row(&["https://github.com/ann/game"])]);
+        assert_eq!(columns[1], [row(&["Notes"])]);
+
+        assert!(sheets.read_values("", "'Search'!A1:C").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn writes_rows_and_columns() {
+        let sheets = teams();
+        write_row(&sheets, "", "Founder's Camp", "C", 3, row(&["new", "team"]))
+            .await
+            .unwrap();
+        sheets
+            .write_values(
+                "",
+                vec![ValueRange {
+                    range: Some("'Founder''s Camp'!E2".to_string()),
+                    values: Some(vec![row(&["12", "7"])]),
+                    major_dimension: Some("COLUMNS".to_string()),
+                }],
+            )
+            .await
+            .unwrap();
+
+        let tab = sheets.tab("Founder's Camp").unwrap();
+        assert_eq!(tab[2], ["Bob", "", "new", "team", "7"]);
+        assert_eq!(tab[1][4], "12");
+    }
+
+    #[tokio::test]
+    async fn writes_past_the_grid_edge_only_after_expanding_it() {
+        let sheets = teams();
+        assert!(
+            write_row(&sheets, "", "Founder's Camp", "Z", 1, row(&["x", "y"]))
+                .await
+                .is_err()
+        );
+
+        sheets.expand_grid("", "Founder's Camp", 27).await.unwrap();
+        write_row(&sheets, "", "Founder's Camp", "Z", 1, row(&["x", "y"]))
+            .await
+            .unwrap();
+        assert_eq!(sheets.tab("Founder's Camp").unwrap()[0][26], "y");
+
+        assert!(sheets.expand_grid("", "Search", 30).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn appends_missing_headers_growing_the_grid() {
+        let sheets = teams();
+        let headers: Vec<String> = (0..30).map(|i| format!("Result {}", i)).collect();
+        let mut wanted = vec!["GitHub Repo".to_string()];
+        wanted.extend(headers);
+
+        let cols = resolve_or_append_columns(&sheets, "", "Founder's Camp", &wanted)
+            .await
+            .unwrap();
+        assert_eq!(cols[0], "B");
+        assert_eq!(cols[1], "D");
+        assert_eq!(cols[30], "AG");
+        let header = sheets.read_header("", "Founder's Camp").await.unwrap();
+        assert_eq!(header.len(), 33);
+        assert_eq!(header[32], "Result 29");
+    }
+
+    #[test]
+    fn a_failed_flush_does_not_hide_the_run_error() {
+        let both = with_flush_result(Err(anyhow!("row 7 failed")), Err(anyhow!("quota")));
+        let message = format!("{:#}", both.unwrap_err());
+        assert!(message.starts_with("row 7 failed"), "{}", message);
+        assert!(message.contains("quota"), "{}", message);
+
+        let flush_only = with_flush_result(Ok(()), Err(anyhow!("quota")));
+        assert_eq!(flush_only.unwrap_err().to_string(), "quota");
+        assert!(with_flush_result(Ok(()), Ok(())).is_ok());
+    }
+
+    fn linked_cell(hyperlink: Option<&str>, run_links: &[Option<&str>]) -> CellData {
+        use google_sheets4::api::{Link, TextFormat, TextFormatRun};
+        let runs = run_links
+            .iter()
+            .enumerate()
+            .map(|(i, uri)| TextFormatRun {
+                start_index: Some(i as i32 * 10),
+                format: Some(TextFormat {
+                    link: uri.map(|u| Link {
+                        uri: Some(u.to_string()),
+                    }),
+                    ..Default::default()
+                }),
+            })
+            .collect();
+        CellData {
+            hyperlink: hyperlink.map(str::to_string),
+            text_format_runs: Some(runs),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_cell_and_rich_text_links_in_order() {
+        let web = "https://github.com/team/web";
+        let api = "https://github.com/team/api";
+        assert_eq!(
+            cell_links(&linked_cell(Some(web), &[])).as_deref(),
+            Some(web)
+        );
+        // One link per run, the cell-level link repeated in its run.
+        assert_eq!(
+            cell_links(&linked_cell(Some(web), &[Some(web), None, Some(api)])),
+            Some(format!("{}\n{}", web, api))
+        );
+        assert_eq!(
+            cell_links(&linked_cell(None, &[None, Some(api)])).as_deref(),
+            Some(api)
+        );
+        assert_eq!(cell_links(&linked_cell(None, &[None])), None);
+        assert_eq!(cell_links(&CellData::default()), None);
+    }
+}
@@ -1,17 +1,15 @@
+use crate::a1::{column_number_to_letter, A1Range, Column};
+use crate::file_store::FileSheetStore;
+use crate::header_rules::HeaderRules;
+use crate::sheet_store::{GoogleSheetStore, SheetStore};
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
-use google_sheets4::{
-    api::{
-        AppendDimensionRequest, BatchUpdateSpreadsheetRequest, BatchUpdateValuesRequest, Request,
-        ValueRange,
-    },
-    Sheets,
-};
+use google_sheets4::{api::ValueRange, Sheets};
 use hyper_rustls::HttpsConnectorBuilder;
 use std::collections::HashMap;
 use yup_oauth2::{ServiceAccountAuthenticator, ServiceAccountKey};
 
-pub async fn init_sheets() -> Result<Sheets> {
+pub async fn init_sheets() -> Result<GoogleSheetStore> {
     let creds_b64 = std::env::var("GOOGLE_SERVICE_ACCOUNT_B64")
         .expect("Missing GOOGLE_SERVICE_ACCOUNT_B64 env var");
     let creds_json = general_purpose::STANDARD.decode(creds_b64)?;
@@ -25,52 +23,45 @@ pub async fn init_sheets() -> Result<Sheets> {
         .build();
 
     let client = hyper::Client::builder().build::<_, hyper::Body>(https);
-    Ok(Sheets::new(client, auth))
+    Ok(GoogleSheetStore::new(Sheets::new(client, auth)))
+}
+
+/// The sheet backend for this run: the local CSV/XLSX file named by
+/// `SHEET_FILE` when set (the spreadsheet ID is then ignored), otherwise
+/// Google Sheets.
+pub async fn open_sheet_store() -> Result<Box<dyn SheetStore>> {
+    match std::env::var("SHEET_FILE") {
+        Ok(path) if !path.trim().is_empty() => {
+            println!("Using sheet file {}", path.trim());
+            Ok(Box::new(FileSheetStore::open(path.trim())?))
+        }
+        _ => Ok(Box::new(init_sheets().await?)),
+    }
 }
 
 pub async fn read_from_sheet(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     range: &str,
 ) -> Result<Vec<String>> {
-    let read_range = if range.trim().is_empty() {
-        format!("'{}'", sheet_name)
-    } else {
-        format!("'{}'!{}", sheet_name, range)
-    };
-
-    let resp = sheets
-        .spreadsheets()
-        .values_get(spreadsheet_id, &read_range)
-        .doit()
-        .await?;
-    let rows = resp.1.values.unwrap_or_default();
+    let read_range = A1Range::on_sheet(sheet_name, range)?.to_string();
+    let rows = sheets.read_values(spreadsheet_id, &read_range).await?;
     Ok(rows
         .iter()
-        .map(|r| r.get(0).unwrap_or(&"".to_string()).clone())
+        .map(|r| r.first().unwrap_or(&"".to_string()).clone())
         .collect())
 }
 
 pub async fn read_columns_from_sheet(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     range: &str,
 ) -> Result<HashMap<String, Vec<String>>, anyhow::Error> {
-    let read_range = if range.trim().is_empty() {
-        format!("'{}'", sheet_name)
-    } else {
-        format!("'{}'!{}", sheet_name, range)
-    };
-
-    let resp = sheets
-        .spreadsheets()
-        .values_get(spreadsheet_id, &read_range)
-        .doit()
-        .await?;
+    let read_range = A1Range::on_sheet(sheet_name, range)?.to_string();
 
-    let rows = resp.1.values.unwrap_or_default();
+    let rows = sheets.read_values(spreadsheet_id, &read_range).await?;
 
     if rows.is_empty() {
         return Ok(HashMap::new());
@@ -113,181 +104,184 @@ pub async fn read_columns_from_sheet(
     Ok(columns)
 }
 
-pub fn clean_column_names(
-    original_columns: HashMap<String, Vec<String>>,
-    rules: &[(Vec<&str>, &str)],
-) -> HashMap<String, Vec<String>> {
-    // When several headers match the same rule, exactly one column must win:
-    // concatenating them (the old behavior) produced a value vector longer
-    // than the sheet, so row indices ran past the last row. The header
-    // matching the earliest keyword in the rule wins; ties break on header
-    // name so the outcome is deterministic despite HashMap iteration order.
-    let mut best: HashMap<String, (usize, String, Vec<String>)> = HashMap::new();
-    let mut cleaned: HashMap<String, Vec<String>> = HashMap::new();
-
-    for (original_name, values) in original_columns {
-        let lower = original_name.to_lowercase();
-        let hit = rules.iter().find_map(|(keywords, new_name)| {
-            keywords
-                .iter()
-                .position(|k| lower.contains(&k.to_lowercase()))
-                .map(|kw_idx| (kw_idx, *new_name))
-        });
+/// Reads the link targets behind the cells of each header in `headers`,
+/// one entry per data row (row 2 onward). Like `read_columns_from_sheet`,
+/// duplicate header columns are merged per row: the link comes from the
+/// first occurrence whose display text is non-empty.
+pub async fn read_column_links(
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    sheet_name: &str,
+    headers: &[String],
+) -> Result<HashMap<String, Vec<Option<String>>>> {
+    let header_row = sheets.read_header(spreadsheet_id, sheet_name).await?;
 
-        let Some((kw_idx, new_name)) = hit else {
-            cleaned.insert(original_name, values);
-            continue;
-        };
+    let wanted: Vec<(String, String)> = header_row
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| headers.contains(h))
+        .map(|(i, h)| (h.clone(), column_number_to_letter(i + 1)))
+        .collect();
+    if wanted.is_empty() {
+        return Ok(HashMap::new());
+    }
 
-        match best.get_mut(new_name) {
-            None => {
-                best.insert(new_name.to_string(), (kw_idx, original_name, values));
+    let columns: Vec<String> = wanted.iter().map(|(_, col)| col.clone()).collect();
+    let cells = sheets
+        .read_links(spreadsheet_id, sheet_name, &columns)
+        .await?;
+
+    let mut links: HashMap<String, Vec<Option<String>>> = HashMap::new();
+    let mut filled: HashMap<String, Vec<bool>> = HashMap::new();
+    for ((header, _), rows) in wanted.iter().zip(cells) {
+        let column = links.entry(header.clone()).or_default();
+        let seen = filled.entry(header.clone()).or_default();
+        for (i, row) in rows.iter().enumerate() {
+            if column.len() <= i {
+                column.resize(i + 1, None);
+                seen.resize(i + 1, false);
             }
-            Some(current) => {
-                let (cur_idx, cur_name, _) = &*current;
-                if (kw_idx, original_name.as_str()) < (*cur_idx, cur_name.as_str()) {
-                    eprintln!(
-                        "⚠️  Multiple columns match '{}': using '{}', ignoring '{}'.",
-                        new_name, original_name, cur_name
-                    );
-                    *current = (kw_idx, original_name, values);
-                } else {
-                    eprintln!(
-                        "⚠️  Multiple columns match '{}': using '{}', ignoring '{}'.",
-                        new_name, cur_name, original_name
-                    );
-                }
+            if !row.text.trim().is_empty() && !seen[i] {
+                seen[i] = true;
+                column[i] = row.link.clone();
             }
         }
     }
+    Ok(links)
+}
 
-    for (new_name, (_, _, values)) in best {
-        cleaned.insert(new_name, values);
-    }
-
-    cleaned
+/// Whether a cell's display text already carries a URL, in which case its
+/// link target is left alone.
+fn shows_url(text: &str) -> bool {
+    text.contains("://")
+        || text
+            .split_whitespace()
+            .any(|t| t.contains('.') && t.contains('/'))
 }
 
-pub fn rename_column(column_name: &str, rules: &[(Vec<&str>, &str)]) -> String {
-    let lower = column_name.to_lowercase();
+/// Replaces display values that aren't URLs ("GitHub", "our repo") with the
+/// link targets behind them. Returns how many cells were replaced.
+pub fn apply_cell_links(
+    columns: &mut HashMap<String, Vec<String>>,
+    links: &HashMap<String, Vec<Option<String>>>,
+) -> usize {
+    let mut replaced = 0;
+    for (header, column_links) in links {
+        let Some(values) = columns.get_mut(header) else {
+            continue;
+        };
+        for (value, link) in values.iter_mut().zip(column_links) {
+            if let Some(link) = link {
+                if !shows_url(value) {
+                    *value = link.clone();
+                    replaced += 1;
+                }
+            }
+        }
+    }
+    replaced
+}
 
-    for (keywords, new_name) in rules {
-        if keywords.iter().any(|k| lower.contains(&k.to_lowercase())) {
-            return new_name.to_string();
+/// Renames columns to the field their header maps to under `rules`;
+/// headers matching no rule keep their own name.
+pub fn clean_column_names(
+    original_columns: HashMap<String, Vec<String>>,
+    rules: &HeaderRules,
+) -> HashMap<String, Vec<String>> {
+    // When several headers map to the same field, exactly one column must
+    // win: concatenating them produced a value vector longer than the
+    // sheet, so row indices ran past the last row.
+    let resolved = rules.resolve(original_columns.keys().map(String::as_str));
+    for r in &resolved {
+        for loser in &r.passed_over {
+            eprintln!(
+                "⚠️  Multiple columns match '{}': using '{}', ignoring '{}'.",
+                r.field, r.header, loser
+            );
         }
     }
+    let winners: HashMap<&str, &str> = resolved
+        .iter()
+        .map(|r| (r.header.as_str(), r.field.as_str()))
+        .collect();
+
+    original_columns
+        .into_iter()
+        .filter_map(
+            |(original_name, values)| match winners.get(original_name.as_str()) {
+                Some(field) => Some((field.to_string(), values)),
+                None if rules.field_for(&original_name).is_some() => None,
+                None => Some((original_name, values)),
+            },
+        )
+        .collect()
+}
 
-    column_name.to_string()
+pub fn rename_column(column_name: &str, rules: &HeaderRules) -> String {
+    rules.rename(column_name)
 }
 
 pub async fn write_to_cell(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     column: &str,
     row: usize,
     value: &str,
 ) -> Result<()> {
-    let range = format!("'{}'!{}{}", sheet_name, column, row);
+    let range = A1Range::cell(sheet_name, column.parse()?, row);
 
     let body = ValueRange {
-        range: Some(range.clone()),
+        range: Some(range.to_string()),
         values: Some(vec![vec![value.to_string()]]),
         major_dimension: Some("ROWS".to_string()),
-        ..Default::default()
     };
 
-    sheets
-        .spreadsheets()
-        .values_update(body, spreadsheet_id, &range)
-        .value_input_option("RAW")
-        .doit()
-        .await?;
-
-    Ok(())
+    sheets.write_values(spreadsheet_id, vec![body]).await
 }
 
 pub async fn write_row(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     start_column: &str, // e.g., "A"
     row: usize,
     values: Vec<String>,
 ) -> Result<()> {
-    // Convert column letter (e.g., "A") to number
-    let start_col_num = column_letter_to_number(start_column);
-    let end_col_num = start_col_num + values.len() - 1;
-    let end_column = column_number_to_letter(end_col_num);
-
-    let range = format!(
-        "'{}'!{}{}:{}{}",
-        sheet_name, start_column, row, end_column, row
-    );
+    let start: Column = start_column.parse()?;
+    let end = start.offset(values.len().saturating_sub(1))?;
+    let range = A1Range::row_span(sheet_name, start, end, row);
 
     let body = ValueRange {
-        range: Some(range.clone()),
+        range: Some(range.to_string()),
         values: Some(vec![values]), // Single row of values
         major_dimension: Some("ROWS".to_string()),
-        ..Default::default()
     };
 
-    sheets
-        .spreadsheets()
-        .values_update(body, spreadsheet_id, &range)
-        .value_input_option("RAW")
-        .doit()
-        .await?;
-
-    Ok(())
+    sheets.write_values(spreadsheet_id, vec![body]).await
 }
 
 /// Submit many cell/range writes as a single Sheets API call.
 /// `values.batchUpdate` counts as ONE write request against the
 /// 60/min/user quota regardless of how many ranges it contains.
 pub async fn batch_update_values(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     updates: Vec<ValueRange>,
 ) -> Result<()> {
-    if updates.is_empty() {
-        return Ok(());
-    }
-    let req = BatchUpdateValuesRequest {
-        value_input_option: Some("RAW".to_string()),
-        data: Some(updates),
-        ..Default::default()
-    };
-    sheets
-        .spreadsheets()
-        .values_batch_update(req, spreadsheet_id)
-        .doit()
-        .await?;
-    Ok(())
+    sheets.write_values(spreadsheet_id, updates).await
 }
 
 /// Resolves a write location by header name. If `header_name` already exists
 /// in row 1, returns its column letter. Otherwise writes the header into the
 /// next empty column and returns that letter.
 pub async fn resolve_or_append_column(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     header_name: &str,
 ) -> Result<String> {
-    let range = format!("'{}'!1:1", sheet_name);
-    let resp = sheets
-        .spreadsheets()
-        .values_get(spreadsheet_id, &range)
-        .doit()
-        .await?;
-    let headers = resp
-        .1
-        .values
-        .unwrap_or_default()
-        .into_iter()
-        .next()
-        .unwrap_or_default();
+    let headers = sheets.read_header(spreadsheet_id, sheet_name).await?;
 
     if let Some(idx) = headers.iter().position(|h| h == header_name) {
         return Ok(column_number_to_letter(idx + 1));
@@ -306,57 +300,18 @@ pub async fn resolve_or_append_column(
     Ok(next_col)
 }
 
-/// Make sure the named sheet has at least `needed_cols` columns. If it
-/// already does, returns immediately. Otherwise appends the missing columns
-/// via a single `batchUpdate` call. New sheets default to 26 columns and
-/// Google Sheets enforces this as a hard limit on writes.
+/// Make sure the named sheet has at least `needed_cols` columns, appending
+/// the missing ones. New sheets default to 26 columns and Google Sheets
+/// enforces this as a hard limit on writes.
 pub async fn ensure_grid_columns(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     needed_cols: usize,
 ) -> Result<()> {
-    let resp = sheets.spreadsheets().get(spreadsheet_id).doit().await?;
-    let spreadsheet = resp.1;
-    let sheet = spreadsheet
-        .sheets
-        .unwrap_or_default()
-        .into_iter()
-        .find(|s| s.properties.as_ref().and_then(|p| p.title.as_deref()) == Some(sheet_name))
-        .ok_or_else(|| anyhow::anyhow!("sheet '{}' not found in spreadsheet", sheet_name))?;
-    let props = sheet
-        .properties
-        .ok_or_else(|| anyhow::anyhow!("sheet '{}' missing properties", sheet_name))?;
-    let sheet_id = props
-        .sheet_id
-        .ok_or_else(|| anyhow::anyhow!("sheet '{}' missing sheet_id", sheet_name))?;
-    let current_cols = props
-        .grid_properties
-        .and_then(|g| g.column_count)
-        .unwrap_or(0) as usize;
-
-    if needed_cols <= current_cols {
-        return Ok(());
-    }
-
-    let to_add = (needed_cols - current_cols) as i32;
-    let req = BatchUpdateSpreadsheetRequest {
-        requests: Some(vec![Request {
-            append_dimension: Some(AppendDimensionRequest {
-                sheet_id: Some(sheet_id),
-                dimension: Some("COLUMNS".to_string()),
-                length: Some(to_add),
-            }),
-            ..Default::default()
-        }]),
-        ..Default::default()
-    };
     sheets
-        .spreadsheets()
-        .batch_update(req, spreadsheet_id)
-        .doit()
-        .await?;
-    Ok(())
+        .expand_grid(spreadsheet_id, sheet_name, needed_cols)
+        .await
 }
 
 /// Resolves each header in `headers` to a column letter. For each header:
@@ -364,10 +319,11 @@ pub async fn ensure_grid_columns(
 ///     and leaves the existing column alone (preserves prior data);
 ///   - otherwise appends the header at the next empty column on the right
 ///     and returns the new letter.
+///
 /// Missing headers are written in a single batched API call. Returned
 /// letters are in the same order as the input `headers`.
 pub async fn resolve_or_append_columns(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     headers: &[String],
@@ -375,19 +331,7 @@ pub async fn resolve_or_append_columns(
     if headers.is_empty() {
         return Ok(Vec::new());
     }
-    let range = format!("'{}'!1:1", sheet_name);
-    let resp = sheets
-        .spreadsheets()
-        .values_get(spreadsheet_id, &range)
-        .doit()
-        .await?;
-    let mut existing: Vec<String> = resp
-        .1
-        .values
-        .unwrap_or_default()
-        .into_iter()
-        .next()
-        .unwrap_or_default();
+    let mut existing = sheets.read_header(spreadsheet_id, sheet_name).await?;
 
     let mut letters: Vec<String> = Vec::with_capacity(headers.len());
     let mut header_writes: Vec<ValueRange> = Vec::new();
@@ -397,15 +341,14 @@ pub async fn resolve_or_append_columns(
         if let Some(idx) = existing.iter().position(|h| h == header) {
             letters.push(column_number_to_letter(idx + 1));
         } else {
-            let col_num = existing.len() + 1;
-            let col = column_number_to_letter(col_num);
+            let column = Column::from_index(existing.len())?;
+            let col = column.to_string();
             existing.push(header.clone());
-            max_col_used = max_col_used.max(col_num);
+            max_col_used = max_col_used.max(column.number());
             header_writes.push(ValueRange {
-                range: Some(format!("'{}'!{}1", sheet_name, col)),
+                range: Some(A1Range::cell(sheet_name, column, 1).to_string()),
                 values: Some(vec![vec![header.clone()]]),
                 major_dimension: Some("ROWS".to_string()),
-                ..Default::default()
             });
             letters.push(col);
         }
@@ -420,7 +363,23 @@ pub async fn resolve_or_append_columns(
 /// Writes multiple `(column_letter, value)` pairs in a single row using one
 /// batched API call. Use when the target columns aren't contiguous.
 pub async fn write_named_cells(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
+    spreadsheet_id: &str,
+    sheet_name: &str,
+    row: usize,
+    cells: &[(&str, String)],
+) -> Result<()> {
+    if cells.is_empty() {
+        return Ok(());
+    }
+    let updates = cell_updates(sheet_name, row, cells)?;
+    batch_update_values(sheets, spreadsheet_id, updates).await
+}
+
+/// Like `write_named_cells`, for values built with `cell_format` (links,
+/// numbers, literal text), written as if typed into the sheet.
+pub async fn write_entered_cells(
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
     row: usize,
@@ -429,40 +388,39 @@ pub async fn write_named_cells(
     if cells.is_empty() {
         return Ok(());
     }
-    let updates: Vec<ValueRange> = cells
+    let updates = cell_updates(sheet_name, row, cells)?;
+    sheets.write_entered(spreadsheet_id, updates).await
+}
+
+/// One single-cell `ValueRange` per `(column_letter, value)` pair in `row`,
+/// for callers that batch several rows into one write.
+pub fn cell_updates(
+    sheet_name: &str,
+    row: usize,
+    cells: &[(&str, String)],
+) -> Result<Vec<ValueRange>> {
+    cells
         .iter()
-        .map(|(col, val)| ValueRange {
-            range: Some(format!("'{}'!{}{}", sheet_name, col, row)),
-            values: Some(vec![vec![val.clone()]]),
-            major_dimension: Some("ROWS".to_string()),
-            ..Default::default()
+        .map(|(col, val)| {
+            Ok(ValueRange {
+                range: Some(A1Range::cell(sheet_name, col.parse()?, row).to_string()),
+                values: Some(vec![vec![val.clone()]]),
+                major_dimension: Some("ROWS".to_string()),
+            })
         })
-        .collect();
-    batch_update_values(sheets, spreadsheet_id, updates).await
+        .collect()
 }
 
 /// Returns the column letters of every header in row 1 that `rename_column`
 /// maps to `target` under `rules` (e.g. all 'GitHub Repo'-like columns).
 pub async fn find_rule_columns(
-    sheets: &Sheets,
+    sheets: &dyn SheetStore,
     spreadsheet_id: &str,
     sheet_name: &str,
-    rules: &[(Vec<&str>, &str)],
+    rules: &HeaderRules,
     target: &str,
 ) -> Result<Vec<String>> {
-    let range = format!("'{}'!1:1", sheet_name);
-    let resp = sheets
-        .spreadsheets()
-        .values_get(spreadsheet_id, &range)
-        .doit()
-        .await?;
-    let headers = resp
-        .1
-        .values
-        .unwrap_or_default()
-        .into_iter()
-        .next()
-        .unwrap_or_default();
+    let headers = sheets.read_header(spreadsheet_id, sheet_name).await?;
     Ok(headers
         .iter()
         .enumerate()
@@ -471,50 +429,61 @@ pub async fn find_rule_columns(
         .collect())
 }
 
-/// Re-reads the given cells of one row in a single batchGet call and returns
-/// true if any of them still holds `expected` (trimmed). Rows can move under
-/// a long-running scrape (sorting, inserted/deleted rows); callers use this
-/// right before writing results by absolute row number. An empty `columns`
-/// list disables the check and returns true.
-pub async fn row_cell_still_matches(
-    sheets: &Sheets,
-    spreadsheet_id: &str,
-    sheet_name: &str,
-    row: usize,
-    columns: &[String],
-    expected: &str,
-) -> Result<bool> {
-    if columns.is_empty() {
-        return Ok(true);
-    }
-    let mut call = sheets.spreadsheets().values_batch_get(spreadsheet_id);
-    for col in columns {
-        call = call.add_ranges(&format!("'{}'!{}{}", sheet_name, col, row));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_urls_from_labels() {
+        assert!(shows_url("https://github.com/team/game"));
+        assert!(shows_url("repo: github.com/team/game"));
+        assert!(!shows_url("GitHub"));
+        assert!(!shows_url("our repo / docs"));
+        assert!(!shows_url("v1.2"));
+        assert!(!shows_url(""));
     }
-    let resp = call.doit().await?;
-    let value_ranges = resp.1.value_ranges.unwrap_or_default();
-    Ok(value_ranges.iter().any(|vr| {
-        vr.values
-            .as_ref()
-            .and_then(|rows| rows.first())
-            .and_then(|r| r.first())
-            .map(|v| v.trim() == expected)
-            .unwrap_or(false)
-    }))
-}
-
-pub fn column_letter_to_number(letter: &str) -> usize {
-    letter.chars().fold(0, |acc, c| {
-        acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1)
-    })
-}
 
-pub fn column_number_to_letter(mut num: usize) -> String {
-    let mut result = String::new();
-    while num > 0 {
-        let rem = (num - 1) % 26;
-        result.insert(0, (b'A' + rem as u8) as char);
-        num = (num - 1) / 26;
+    #[test]
+    fn replaces_labels_with_their_link_targets() {
+        let url = "https://github.com/team/game";
+        let mut columns = HashMap::from([(
+            "GitHub Repo".to_string(),
+            vec![
+                url.to_string(),
+                "GitHub".to_string(),
+                "frontend, program".to_string(),
+                "github.com/team/old".to_string(),
+                "no link".to_string(),
+            ],
+        )]);
+        let links = HashMap::from([
+            (
+                "GitHub Repo".to_string(),
+                vec![
+                    Some(url.to_string()),
+                    Some(url.to_string()),
+                    Some(
+                        "https://github.com/team/web\nhttps://github.com/team/program".to_string(),
+                    ),
+                    Some("https://github.com/team/new".to_string()),
+                    None,
+                ],
+            ),
+            ("Missing".to_string(), vec![Some(url.to_string())]),
+        ]);
+
+        assert_eq!(apply_cell_links(&mut columns, &links), 2);
+        assert_eq!(
+            columns["GitHub Repo"],
+            [
+                url,
+                url,
+                "https://github.com/team/web\nhttps://github.com/team/program",
+                // The cell already shows a URL; its link target is left alone.
+                "github.com/team/old",
+                "no link",
+            ]
+        );
+        assert!(!columns.contains_key("Missing"));
     }
-    result
 }
@@ -1,14 +1,189 @@
+use crate::cell_format::{
+    hyperlink, literal, number, url_link, ColumnFormat, HighlightWhen, GREEN, RED,
+};
+use crate::forge::ForgeKind;
+use crate::header_rules::HeaderRule;
+use crate::rows::SheetRow;
 use serde::{Deserialize, Serialize};
-use serde_json::{from_value, to_value, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// What kind of code a matched file is, decided by `classify::categorize_path`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum PathCategory {
+    Program,
+    Client,
+    Test,
+    Example,
+    Vendored,
+    Config,
+}
+
+impl PathCategory {
+    /// Program, client and config hits describe what the team actually ships.
+    pub fn is_production(self) -> bool {
+        matches!(
+            self,
+            PathCategory::Program | PathCategory::Client | PathCategory::Config
+        )
+    }
+}
+
+/// Whether a submitted repo could be scanned, and if not, why.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RepoStatus {
+    Active,
+    Archived,
+    /// The repo exists but has no commits yet.
+    Empty,
+    /// Deleted, or private; most forges don't tell the two apart.
+    NotFound,
+    /// The forge refused the token (private repo, revoked access).
+    Inaccessible,
+    /// Taken down, e.g. by a DMCA notice.
+    Blocked,
+}
+
+impl RepoStatus {
+    /// Text written to the `Repo Status` column.
+    pub fn label(self) -> &'static str {
+        match self {
+            RepoStatus::Active => "active",
+            RepoStatus::Archived => "archived",
+            RepoStatus::Empty => "empty",
+            RepoStatus::NotFound => "not found",
+            RepoStatus::Inaccessible => "private/inaccessible",
+            RepoStatus::Blocked => "blocked",
+        }
+    }
+
+    /// Error message for a repo that couldn't be scanned.
+    pub fn error(self) -> &'static str {
+        match self {
+            RepoStatus::Active | RepoStatus::Archived => "Failed to process repo data",
+            RepoStatus::Empty => "Repository is empty",
+            RepoStatus::NotFound => "Repository not found (deleted or private)",
+            RepoStatus::Inaccessible => "Repository is private or inaccessible",
+            RepoStatus::Blocked => "Repository access blocked",
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Clone, Deserialize, Default, PartialEq)]
 pub struct KeywordResult {
     pub count: usize,
     pub files: Vec<String>,
+    /// `count` split by the category of the file each hit was in.
+    #[serde(default)]
+    pub categories: BTreeMap<PathCategory, usize>,
 }
 pub type RepoMap = HashMap<String, KeywordResult>;
 
+/// Hits attributed to one workspace member (Cargo or JS workspace).
+#[derive(Debug, Serialize, Clone, Deserialize, Default, PartialEq)]
+pub struct WorkspaceMemberResult {
+    /// `cargo` or `npm`.
+    pub manifest: String,
+    pub count: usize,
+    pub keywords: BTreeMap<String, usize>,
+}
+
+/// Per-row scan settings from the sheet's `Scan Branch`, `Scan Path` and
+/// `Extra Keywords` columns.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ScanOverrides {
+    /// Branch, tag or commit to scan instead of the default branch.
+    pub branch: Option<String>,
+    /// Only files under this directory are scanned.
+    pub path: Option<String>,
+    /// Searched for on top of the built-in keyword list.
+    pub extra_keywords: Vec<String>,
+}
+
+impl ScanOverrides {
+    /// Reads the raw cell values; blank cells mean "no override". Extra
+    /// keywords may be separated by commas, semicolons or newlines.
+    pub fn from_cells(branch: &str, path: &str, extra_keywords: &str) -> Self {
+        let non_empty = |s: &str| Some(s.to_string()).filter(|s| !s.is_empty());
+        let mut keywords: Vec<String> = Vec::new();
+        for kw in extra_keywords.split([',', ';', '\n']).map(str::trim) {
+            if !kw.is_empty() && !keywords.iter().any(|k| k.eq_ignore_ascii_case(kw)) {
+                keywords.push(kw.to_string());
+            }
+        }
+        Self {
+            branch: non_empty(branch.trim()),
+            path: non_empty(path.trim().trim_matches('/')),
+            extra_keywords: keywords,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.branch.is_none() && self.path.is_none() && self.extra_keywords.is_empty()
+    }
+}
+
+/// One human committer on the scanned branch.
+#[derive(Debug, Serialize, Clone, Deserialize, Default, PartialEq)]
+pub struct Contributor {
+    /// GitHub login, when the commit email is linked to an account.
+    pub login: Option<String>,
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    pub first_commit: String,
+    pub last_commit: String,
+}
+
+/// One repo of a merged row result: its identity, status and counts, the
+/// rest being part of the merged view.
+#[derive(Debug, Serialize, Clone, Deserialize, Default, PartialEq)]
+pub struct RepoSummary {
+    pub owner: String,
+    pub repo_name: String,
+    pub commit_sha: String,
+    pub snapshot_url: String,
+    pub repo_status: Option<RepoStatus>,
+    pub keyword_matches: String,
+    pub keyword_counts: BTreeMap<String, usize>,
+    pub files_processed: String,
+    pub depth_score: u32,
+    pub team_size: Option<usize>,
+}
+
+impl RepoSummary {
+    pub fn new(data: &GitHubUpdateData) -> Self {
+        Self {
+            owner: data.owner.clone(),
+            repo_name: data.repo_name.clone(),
+            commit_sha: data.commit_sha.clone(),
+            snapshot_url: data.snapshot_url.clone(),
+            repo_status: data.repo_status,
+            keyword_matches: data.keyword_matches.clone(),
+            keyword_counts: data
+                .keyword_counts
+                .iter()
+                .map(|(k, v)| (k.clone(), v.count))
+                .collect(),
+            files_processed: data.files_processed.clone(),
+            depth_score: data.depth_score,
+            team_size: data.team_size,
+        }
+    }
+}
+
+/// Everything `process_repo` learned from one repo's files.
+#[derive(Debug, Clone, Default)]
+pub struct RepoScan {
+    pub keyword_counts: RepoMap,
+    pub file_types: String,
+    pub files_processed: usize,
+    /// Keyed by member directory; hits outside every member land under
+    /// `(root)`. Empty when the repo declares no workspace.
+    pub workspace_breakdown: BTreeMap<String, WorkspaceMemberResult>,
+}
+
 pub struct Config {
     pub read_sheet_name: String,         // Sheet to read from
     pub write_sheet_name: String,        // Sheet to write to
@@ -31,10 +206,48 @@ pub struct GitHubUpdateData {
     pub name: String,
     pub owner: String,
     pub repo_name: String,
+    /// Code host the repo was scanned on.
+    pub forge: Option<ForgeKind>,
+    /// The repo's current web URL as reported by its forge; differs from
+    /// the submitted URL when the repo was renamed or transferred.
+    pub canonical_url: Option<String>,
+    pub repo_status: Option<RepoStatus>,
     pub snapshot_url: String,
+    /// Rewrites applied to the submitted URL (`describe_url_fixes`).
+    pub url_normalization: String,
     pub origin: String,
     pub file_types: String,
     pub files_processed: String,
+    pub integration_type: String,
+    pub integration_features: Vec<String>,
+    pub depth_score: u32,
+    pub category_counts: BTreeMap<PathCategory, usize>,
+    pub workspace_breakdown: BTreeMap<String, WorkspaceMemberResult>,
+
+    // Repository metadata (None when the repo lookup failed):
+    pub stars: Option<u64>,
+    pub forks: Option<u64>,
+    pub topics: Vec<String>,
+    pub license: Option<String>,
+    pub repo_created_at: Option<String>,
+    pub repo_pushed_at: Option<String>,
+    pub archived: Option<bool>,
+    /// Bytes of code per language.
+    pub languages: BTreeMap<String, u64>,
+    pub homepage: Option<String>,
+
+    /// Human contributors, most commits first (bots filtered out).
+    pub contributors: Vec<Contributor>,
+    pub team_size: Option<usize>,
+
+    /// Per-row settings from the sheet's override columns, when any were set.
+    pub scan_overrides: Option<ScanOverrides>,
+
+    /// The repos merged into this result when one sheet cell listed
+    /// several; the fields above are then the merged view
+    /// (`merge_row_results`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repos: Vec<RepoSummary>,
 
     // Optional fields:
     pub location: Option<String>,
@@ -47,51 +260,351 @@ pub struct GitHubUpdateData {
     pub wallet: Option<String>,
 }
 
+/// Sheet headers for the repository metadata columns of
+/// `ScraperResultRow`, in sheet order.
+pub const REPO_METADATA_HEADERS: [&str; 9] = [
+    "Repo Stars",
+    "Repo Forks",
+    "Repo Topics",
+    "Repo License",
+    "Repo Created At",
+    "Repo Pushed At",
+    "Repo Archived",
+    "Repo Languages",
+    "Repo Homepage",
+];
+
 impl GitHubUpdateData {
+    /// Languages as percentages of code bytes, largest first.
+    fn language_shares(&self) -> String {
+        let total: u64 = self.languages.values().sum();
+        let mut languages: Vec<(&String, &u64)> = self.languages.iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(a.1));
+        languages
+            .iter()
+            .map(|(lang, bytes)| format!("{} {}%", lang, **bytes * 100 / total.max(1)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Whether nothing was scanned or looked up: every field is still at
+    /// its default.
     pub fn is_empty(&self) -> bool {
-        let json = serde_json::to_value(self).unwrap_or(Value::Null);
-        match json {
-            Value::Object(map) => map.values().all(|v| match v {
-                Value::String(s) => s.is_empty(),
-                Value::Null => true,
-                Value::Object(m) => m.is_empty(),
-                Value::Array(a) => a.is_empty(),
-                _ => false,
-            }),
-            _ => true,
+        *self == Self::default()
+    }
+
+    /// Fills the fields still blank from the row's submission columns.
+    pub fn add_submission_fields(&mut self, row: &SubmissionRow) {
+        if self.snapshot_url.is_empty() {
+            self.snapshot_url = row.snapshot_url.clone();
+        }
+        if self.files_processed.is_empty() {
+            self.files_processed = row.files_processed.clone().unwrap_or_default();
+        }
+        for (field, value) in [
+            (&mut self.presentation_link, &row.presentation_link),
+            (&mut self.technical_link, &row.technical_link),
+            (&mut self.location, &row.location),
+            (&mut self.tracks, &row.tracks),
+            (&mut self.contact, &row.contact),
+        ] {
+            if field.as_deref().is_none_or(str::is_empty) {
+                field.clone_from(value);
+            }
         }
     }
+}
 
-    pub fn add_fields_if_exist(
-        &mut self,
-        columns: &HashMap<String, Vec<String>>,
-        fields: &[&str],
-        row_index: usize,
-    ) {
-        let mut val = to_value(&*self).unwrap();
-
-        if let Value::Object(ref mut map) = val {
-            for &field in fields {
-                let needs_update = match map.get(field) {
-                    Some(Value::String(s)) => s.is_empty(),
-                    Some(Value::Null) => true,
-                    None => true,
-                    _ => false,
-                };
+/// The submission columns of a sheet row. Override columns ("Repo
+/// Branch") outrank the bare "repo" match, and a lone `gh` only counts as a
+/// whole word ("Highlights" isn't a repo link).
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct SubmissionRow {
+    pub scan_branch: String,
+    pub scan_path: String,
+    pub extra_keywords: String,
+    pub snapshot_url: String,
+    pub presentation_link: Option<String>,
+    pub website_link: Option<String>,
+    pub technical_link: Option<String>,
+    pub files_processed: Option<String>,
+    pub location: Option<String>,
+    pub tracks: Option<String>,
+    pub contact: Option<String>,
+    pub wallet: Option<String>,
+    pub social_link: Option<String>,
+}
 
-                if !needs_update {
-                    continue;
-                }
+impl SheetRow for SubmissionRow {
+    const ALIASES: &'static [HeaderRule] = &[
+        HeaderRule::contains("scan_branch", "scan branch", 50),
+        // Exact only: a loose "branch" would bind "Default Branch" and
+        // change which ref gets scanned.
+        HeaderRule::exact("scan_branch", "branch to scan", 40),
+        HeaderRule::exact("scan_branch", "scan ref", 40),
+        HeaderRule::contains("scan_path", "scan path", 50),
+        HeaderRule::contains("scan_path", "subdirectory", 40),
+        HeaderRule::contains("scan_path", "subdir", 40),
+        HeaderRule::contains("extra_keywords", "extra keyword", 50),
+        HeaderRule::exact("snapshot_url", "repo url", 30),
+        HeaderRule::exact("snapshot_url", "github repo", 30),
+        HeaderRule::contains("snapshot_url", "github", 20),
+        HeaderRule::contains("snapshot_url", "repo", 10),
+        HeaderRule::regex("snapshot_url", r"\bgh\b", 10),
+        HeaderRule::contains("presentation_link", "presentation", 20),
+        HeaderRule::contains("website_link", "website", 20),
+        HeaderRule::contains("technical_link", "technical", 20),
+        HeaderRule::contains("technical_link", "demo", 20),
+        HeaderRule::exact("files_processed", "files_processed", 20),
+        HeaderRule::contains("location", "location", 20),
+        HeaderRule::contains("location", "country", 20),
+        HeaderRule::contains("location", "residence", 20),
+        HeaderRule::contains("tracks", "track", 20),
+        HeaderRule::contains("contact", "contact", 20),
+        HeaderRule::contains("contact", "telegram", 20),
+        HeaderRule::contains("wallet", "wallet", 20),
+        HeaderRule::regex("wallet", r"\bsolana (address|pubkey)\b", 20),
+        HeaderRule::contains("social_link", "twitter", 20),
+        HeaderRule::contains("social_link", "social link", 20),
+    ];
+}
 
-                if let Some(values) = columns.get(field) {
-                    if let Some(value) = values.get(row_index) {
-                        map.insert(field.to_string(), Value::String(value.clone()));
-                    }
+impl SubmissionRow {
+    pub fn scan_overrides(&self) -> ScanOverrides {
+        ScanOverrides::from_cells(&self.scan_branch, &self.scan_path, &self.extra_keywords)
+    }
+}
+
+/// The result columns written for one scanned repo, named by header.
+/// Layouts without a column (such as the optional `REPO_METADATA_HEADERS`
+/// block) simply don't get that cell.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ScraperResultRow {
+    #[serde(rename = "Scraper Result (JSON)")]
+    pub result_json: String,
+    #[serde(rename = "Scraper Keyword Matches")]
+    pub keyword_matches: String,
+    #[serde(rename = "Scraper Snapshot URL")]
+    pub snapshot_url: String,
+    #[serde(rename = "Scraper Integration Type")]
+    pub integration_type: String,
+    #[serde(rename = "Scraper Depth Score")]
+    pub depth_score: u32,
+    #[serde(rename = "Scraper Team Size")]
+    pub team_size: Option<usize>,
+    #[serde(rename = "Scraper URL Normalization")]
+    pub url_normalization: String,
+    #[serde(rename = "Repo Status")]
+    pub repo_status: String,
+    #[serde(rename = "Canonical Repo URL")]
+    pub canonical_url: Option<String>,
+    #[serde(rename = "Repo Stars")]
+    pub stars: Option<u64>,
+    #[serde(rename = "Repo Forks")]
+    pub forks: Option<u64>,
+    #[serde(rename = "Repo Topics")]
+    pub topics: String,
+    #[serde(rename = "Repo License")]
+    pub license: Option<String>,
+    #[serde(rename = "Repo Created At")]
+    pub repo_created_at: Option<String>,
+    #[serde(rename = "Repo Pushed At")]
+    pub repo_pushed_at: Option<String>,
+    #[serde(rename = "Repo Archived")]
+    pub archived: Option<bool>,
+    #[serde(rename = "Repo Languages")]
+    pub languages: String,
+    #[serde(rename = "Repo Homepage")]
+    pub homepage: Option<String>,
+}
+
+impl ScraperResultRow {
+    pub fn new(data: &GitHubUpdateData) -> serde_json::Result<Self> {
+        Ok(Self {
+            result_json: serde_json::to_string(data)?,
+            keyword_matches: data.keyword_matches.clone(),
+            snapshot_url: data.snapshot_url.clone(),
+            integration_type: data.integration_type.clone(),
+            depth_score: data.depth_score,
+            team_size: data.team_size,
+            url_normalization: data.url_normalization.clone(),
+            repo_status: data
+                .repo_status
+                .map(|s| s.label().to_string())
+                .unwrap_or_default(),
+            canonical_url: data.canonical_url.clone(),
+            stars: data.stars,
+            forks: data.forks,
+            topics: data.topics.join(", "),
+            license: data.license.clone(),
+            repo_created_at: data.repo_created_at.clone(),
+            repo_pushed_at: data.repo_pushed_at.clone(),
+            archived: data.archived,
+            languages: data.language_shares(),
+            homepage: data.homepage.clone(),
+        })
+    }
+
+    /// The same cells entered for the sheet (`SheetStore::write_entered`):
+    /// the snapshot, canonical and homepage URLs as links, the keyword
+    /// match count linking to the first matching file, counts as numbers
+    /// and the rest as literal text.
+    pub fn for_sheet(data: &GitHubUpdateData) -> serde_json::Result<Self> {
+        let row = Self::new(data)?;
+        let first_file = data
+            .keyword_counts
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_values()
+            .find_map(|k| k.files.first());
+        let link = |url: &Option<String>| url.as_deref().map(url_link);
+        let text = |value: &Option<String>| value.as_deref().map(literal);
+        Ok(Self {
+            result_json: literal(&row.result_json),
+            keyword_matches: match first_file {
+                Some(file) => hyperlink(file, &row.keyword_matches),
+                None => number(&row.keyword_matches),
+            },
+            snapshot_url: url_link(&row.snapshot_url),
+            integration_type: literal(&row.integration_type),
+            url_normalization: literal(&row.url_normalization),
+            repo_status: literal(&row.repo_status),
+            canonical_url: link(&row.canonical_url),
+            topics: literal(&row.topics),
+            license: text(&row.license),
+            repo_created_at: text(&row.repo_created_at),
+            repo_pushed_at: text(&row.repo_pushed_at),
+            languages: literal(&row.languages),
+            homepage: link(&row.homepage),
+            ..row
+        })
+    }
+}
+
+/// Number formats for the count columns of `ScraperResultRow` among
+/// `headers` (resolved to `cols`, in the same order), clipping for the
+/// JSON column and, with `highlight`, green for integrated repos and red
+/// for errors and unreachable repos.
+pub fn result_column_formats(
+    cols: &[String],
+    headers: &[String],
+    highlight: bool,
+) -> anyhow::Result<Vec<ColumnFormat>> {
+    let mut formats = Vec::new();
+    for (col, header) in cols.iter().zip(headers) {
+        let format = match header.as_str() {
+            "Scraper Result (JSON)" => {
+                let format = ColumnFormat {
+                    clip: true,
+                    ..ColumnFormat::new(col)?
+                };
+                match highlight {
+                    true => format.highlight(HighlightWhen::TextStartsWith("❌".into()), RED),
+                    false => format,
                 }
             }
-        }
+            "Scraper Keyword Matches"
+            | "Scraper Depth Score"
+            | "Scraper Team Size"
+            | "Repo Stars"
+            | "Repo Forks" => ColumnFormat::count(col)?,
+            "Scraper Integration Type" if highlight => ColumnFormat::new(col)?.highlight(
+                HighlightWhen::Formula(format!("=AND(LEN({c}2)>0,{c}2<>\"none\")", c = col)),
+                GREEN,
+            ),
+            "Repo Status" if highlight => ColumnFormat::new(col)?.highlight(
+                HighlightWhen::Formula(format!(
+                    "=REGEXMATCH({}2,\"not found|private/inaccessible|blocked\")",
+                    col
+                )),
+                RED,
+            ),
+            _ => continue,
+        };
+        formats.push(format);
+    }
+    Ok(formats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header_rules::HeaderRules;
+    use crate::rows::rows_from_columns;
+
+    #[test]
+    fn blank_cells_mean_no_override() {
+        let overrides = ScanOverrides::from_cells("", "  ", " ,; \n");
+        assert!(overrides.is_empty());
+        assert_eq!(overrides, ScanOverrides::default());
+    }
+
+    #[test]
+    fn reads_a_branch_a_path_and_extra_keywords() {
+        let overrides = ScanOverrides::from_cells(
+            " release/v2 ",
+            "/programs/game/",
+            "MyProgram, myprogram; session-keys\nDelegate",
+        );
+        assert_eq!(overrides.branch.as_deref(), Some("release/v2"));
+        assert_eq!(overrides.path.as_deref(), Some("programs/game"));
+        assert_eq!(
+            overrides.extra_keywords,
+            ["MyProgram", "session-keys", "Delegate"]
+        );
+        assert!(!overrides.is_empty());
+        assert_eq!(ScanOverrides::from_cells("", "/", "").path, None);
+    }
+
+    #[test]
+    fn bool_and_number_looking_cells_stay_text() {
+        let columns = HashMap::from([
+            (
+                "Scan Branch".to_string(),
+                vec!["1.0".to_string(), "TRUE".to_string()],
+            ),
+            (
+                "Scan Path".to_string(),
+                vec!["2024".to_string(), "no".to_string()],
+            ),
+            (
+                "Extra Keywords".to_string(),
+                vec!["0, false".to_string(), String::new()],
+            ),
+        ]);
+        let rows: Vec<SubmissionRow> =
+            rows_from_columns(&columns, &HeaderRules::new(Vec::new()).unwrap()).unwrap();
+        let overrides: Vec<ScanOverrides> = rows.iter().map(|r| r.scan_overrides()).collect();
+
+        assert_eq!(overrides[0].branch.as_deref(), Some("1.0"));
+        assert_eq!(overrides[0].path.as_deref(), Some("2024"));
+        assert_eq!(overrides[0].extra_keywords, ["0", "false"]);
+        assert_eq!(overrides[1].branch.as_deref(), Some("TRUE"));
+        assert_eq!(overrides[1].path.as_deref(), Some("no"));
+        assert!(overrides[1].extra_keywords.is_empty());
+    }
+
+    #[test]
+    fn update_data_is_empty_only_at_its_defaults() {
+        assert!(GitHubUpdateData::default().is_empty());
 
-        let new_self: GitHubUpdateData = from_value(val).unwrap();
-        *self = new_self;
+        let with = |data: GitHubUpdateData| !data.is_empty();
+        assert!(with(GitHubUpdateData {
+            commit_sha: "abc123".to_string(),
+            ..Default::default()
+        }));
+        assert!(with(GitHubUpdateData {
+            depth_score: 7,
+            ..Default::default()
+        }));
+        assert!(with(GitHubUpdateData {
+            archived: Some(false),
+            ..Default::default()
+        }));
+        assert!(with(GitHubUpdateData {
+            repo_status: Some(RepoStatus::NotFound),
+            ..Default::default()
+        }));
     }
 }
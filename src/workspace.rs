@@ -0,0 +1,308 @@
+use serde_json::Value;
+
+/// A workspace member directory discovered from a `Cargo.toml` `[workspace]`
+/// or a `package.json` `workspaces` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceMember {
+    /// Repo-relative directory, e.g. `programs/counter`.
+    pub path: String,
+    /// `cargo` or `npm`.
+    pub manifest: &'static str,
+}
+
+/// Manifests worth fetching to look for workspace declarations: the root
+/// `Cargo.toml` / `package.json` plus any one directory down (Anchor projects
+/// often live in `anchor/` or `program/` next to a frontend).
+pub fn workspace_manifest_paths<'a>(tree_paths: impl Iterator<Item = &'a str>) -> Vec<String> {
+    tree_paths
+        .filter(|p| !p.contains("node_modules"))
+        .filter(|p| p.matches('/').count() <= 1)
+        .filter(|p| {
+            let name = p.rsplit('/').next().unwrap_or(p);
+            name == "Cargo.toml" || name == "package.json"
+        })
+        .map(|p| p.to_string())
+        .collect()
+}
+
+/// Member patterns declared by one manifest, as `(include, exclude)` globs
+/// relative to the manifest's directory.
+fn declared_patterns(manifest_path: &str, content: &str) -> (Vec<String>, Vec<String>) {
+    let strings = |v: Option<&Value>| -> Vec<String> {
+        v.and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|s| s.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    if manifest_path.ends_with("Cargo.toml") {
+        let Ok(parsed) = toml::from_str::<toml::Value>(content) else {
+            return (Vec::new(), Vec::new());
+        };
+        let Some(workspace) = parsed.get("workspace") else {
+            return (Vec::new(), Vec::new());
+        };
+        // Round-trip through JSON so both manifests share one extractor.
+        let workspace = serde_json::to_value(workspace).unwrap_or(Value::Null);
+        (
+            strings(workspace.get("members")),
+            strings(workspace.get("exclude")),
+        )
+    } else {
+        let Ok(parsed) = serde_json::from_str::<Value>(content) else {
+            return (Vec::new(), Vec::new());
+        };
+        // `workspaces` is either an array or `{ "packages": [...] }` (yarn).
+        let workspaces = parsed.get("workspaces");
+        let include = match workspaces {
+            Some(Value::Array(_)) => strings(workspaces),
+            Some(Value::Object(o)) => strings(o.get("packages")),
+            _ => Vec::new(),
+        };
+        // npm/yarn express exclusions as `!pattern` entries.
+        let (exclude, include): (Vec<String>, Vec<String>) =
+            include.into_iter().partition(|p| p.starts_with('!'));
+        (
+            include,
+            exclude
+                .into_iter()
+                .map(|p| p.trim_start_matches('!').to_string())
+                .collect(),
+        )
+    }
+}
+
+/// Resolves the workspace members declared in `manifest_path` against the
+/// repo tree. A member is any directory matching an include glob (and no
+/// exclude glob) that contains the same kind of manifest.
+pub fn detect_members(
+    manifest_path: &str,
+    content: &str,
+    tree_paths: &[&str],
+) -> Vec<WorkspaceMember> {
+    let (include, exclude) = declared_patterns(manifest_path, content);
+    if include.is_empty() {
+        return Vec::new();
+    }
+
+    let (base, manifest_name) = match manifest_path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), manifest_path),
+    };
+    let manifest: &'static str = if manifest_name == "Cargo.toml" {
+        "cargo"
+    } else {
+        "npm"
+    };
+    let qualify = |p: &String| {
+        format!(
+            "{}{}",
+            base,
+            p.trim_start_matches("./").trim_end_matches('/')
+        )
+    };
+    let include: Vec<String> = include.iter().map(qualify).collect();
+    let exclude: Vec<String> = exclude.iter().map(qualify).collect();
+
+    let mut members: Vec<WorkspaceMember> = tree_paths
+        .iter()
+        .filter(|p| !p.contains("node_modules"))
+        .filter_map(|p| {
+            let (dir, name) = p.rsplit_once('/')?;
+            (name == manifest_name).then_some(dir)
+        })
+        .filter(|dir| include.iter().any(|g| glob_match(g, dir)))
+        .filter(|dir| !exclude.iter().any(|g| glob_match(g, dir)))
+        .map(|dir| WorkspaceMember {
+            path: dir.to_string(),
+            manifest,
+        })
+        .collect();
+    members.sort_by(|a, b| a.path.cmp(&b.path));
+    members.dedup();
+    members
+}
+
+/// The member owning `path`: the one with the longest directory prefix, so a
+/// nested member wins over its parent.
+pub fn member_for_path<'a>(
+    path: &str,
+    members: &'a [WorkspaceMember],
+) -> Option<&'a WorkspaceMember> {
+    members
+        .iter()
+        .filter(|m| path.starts_with(&format!("{}/", m.path)))
+        .max_by_key(|m| m.path.len())
+}
+
+/// Path-segment glob: `*` and `?` match within one segment, `**` matches any
+/// number of segments.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| match_segments(rest, &path[i..])),
+        Some((seg, rest)) => match path.split_first() {
+            Some((p, path_rest)) => wildcard_match(seg, p) && match_segments(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(members: &[WorkspaceMember]) -> Vec<(&str, &str)> {
+        members
+            .iter()
+            .map(|m| (m.path.as_str(), m.manifest))
+            .collect()
+    }
+
+    const TREE: &[&str] = &[
+        "Cargo.toml",
+        "package.json",
+        "programs/counter/Cargo.toml",
+        "programs/counter/src/lib.rs",
+        "programs/legacy/Cargo.toml",
+        "crates/sdk/core/Cargo.toml",
+        "crates/sdk/core/package.json",
+        "app/web/package.json",
+        "app/web/src/index.ts",
+        "app/web/node_modules/dep/package.json",
+        "packages/ui/package.json",
+        "docs/README.md",
+    ];
+
+    #[test]
+    fn finds_manifests_at_the_root_and_one_level_down() {
+        let tree = [
+            "Cargo.toml",
+            "anchor/Cargo.toml",
+            "anchor/programs/x/Cargo.toml",
+            "frontend/package.json",
+            "node_modules/package.json",
+            "README.md",
+        ];
+        assert_eq!(
+            workspace_manifest_paths(tree.into_iter()),
+            ["Cargo.toml", "anchor/Cargo.toml", "frontend/package.json"]
+        );
+    }
+
+    #[test]
+    fn cargo_members_follow_globs_and_skip_excludes() {
+        let manifest = r#"
+            [workspace]
+            members = ["programs/*", "crates/**"]
+            exclude = ["programs/legacy"]
+        "#;
+        assert_eq!(
+            paths(&detect_members("Cargo.toml", manifest, TREE)),
+            [("crates/sdk/core", "cargo"), ("programs/counter", "cargo")]
+        );
+    }
+
+    #[test]
+    fn cargo_without_a_workspace_has_no_members() {
+        let manifest = "[package]\nname = \"solo\"\n";
+        assert!(detect_members("Cargo.toml", manifest, TREE).is_empty());
+        assert!(detect_members("Cargo.toml", "not toml [", TREE).is_empty());
+    }
+
+    #[test]
+    fn npm_workspaces_array_with_negated_patterns() {
+        let manifest = r#"{"workspaces": ["app/*", "./packages/*/", "!packages/ui"]}"#;
+        assert_eq!(
+            paths(&detect_members("package.json", manifest, TREE)),
+            [("app/web", "npm")]
+        );
+    }
+
+    #[test]
+    fn workspaces_object_lists_its_packages() {
+        let manifest = r#"{"workspaces": {"packages": ["packages/*", "crates/sdk/*"]}}"#;
+        assert_eq!(
+            paths(&detect_members("package.json", manifest, TREE)),
+            [("crates/sdk/core", "npm"), ("packages/ui", "npm")]
+        );
+    }
+
+    #[test]
+    fn nested_manifests_resolve_relative_to_their_directory() {
+        let tree = [
+            "anchor/Cargo.toml",
+            "anchor/programs/game/Cargo.toml",
+            "programs/other/Cargo.toml",
+        ];
+        let manifest = "[workspace]\nmembers = [\"programs/*\"]\n";
+        assert_eq!(
+            paths(&detect_members("anchor/Cargo.toml", manifest, &tree)),
+            [("anchor/programs/game", "cargo")]
+        );
+    }
+
+    #[test]
+    fn hits_go_to_the_innermost_member() {
+        let member = |path: &str| WorkspaceMember {
+            path: path.to_string(),
+            manifest: "cargo",
+        };
+        let members = [
+            member("crates/sdk"),
+            member("crates/sdk/core"),
+            member("app"),
+        ];
+        let owner = |path| member_for_path(path, &members).map(|m| m.path.as_str());
+
+        assert_eq!(owner("crates/sdk/core/src/lib.rs"), Some("crates/sdk/core"));
+        assert_eq!(owner("crates/sdk/src/lib.rs"), Some("crates/sdk"));
+        assert_eq!(owner("app/src/main.ts"), Some("app"));
+        // A sibling sharing a name prefix is not inside the member.
+        assert_eq!(owner("application/src/main.ts"), None);
+        assert_eq!(owner("Cargo.toml"), None);
+    }
+
+    #[test]
+    fn globs_match_whole_segments() {
+        assert!(glob_match("programs/*", "programs/counter"));
+        assert!(!glob_match("programs/*", "programs/counter/sub"));
+        assert!(glob_match("crates/**", "crates/a/b"));
+        assert!(glob_match("**/core", "crates/sdk/core"));
+        assert!(glob_match("pkg-?", "pkg-a"));
+        assert!(glob_match("*-sdk", "magic-sdk"));
+        assert!(!glob_match("*-sdk", "magic-sdk-old"));
+    }
+}
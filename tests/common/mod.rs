@@ -0,0 +1,85 @@
+//! Git fixture repos built in a temp dir, for scanning without a network.
+
+use gix::actor::SignatureRef;
+use gix::object::tree::EntryKind;
+use std::path::Path;
+
+pub struct Author {
+    pub name: &'static str,
+    pub email: &'static str,
+}
+
+pub const ANN: Author = Author {
+    name: "Ann",
+    email: "ann@example.com",
+};
+pub const BOB: Author = Author {
+    name: "Bob",
+    email: "bob@example.com",
+};
+
+/// A bare repo at `dir` whose `main` branch gets one commit per call to
+/// `commit`.
+pub struct Fixture {
+    pub repo: gix::Repository,
+    tree: gix::ObjectId,
+    head: Option<gix::ObjectId>,
+    time: u64,
+}
+
+impl Fixture {
+    pub fn init(dir: &Path) -> Self {
+        let repo = gix::init_bare(dir).expect("init fixture repo");
+        let tree = gix::ObjectId::empty_tree(repo.object_hash());
+        Self {
+            repo,
+            tree,
+            head: None,
+            time: 1_700_000_000,
+        }
+    }
+
+    /// Writes `files` (path, content) over the previous commit's tree and
+    /// commits them on `main` as `author`; returns the commit id.
+    pub fn commit(&mut self, author: &Author, files: &[(&str, &str)]) -> String {
+        let mut editor = self.repo.edit_tree(self.tree).expect("edit tree");
+        for (path, content) in files {
+            let blob = self
+                .repo
+                .write_blob(content.as_bytes())
+                .expect("write blob");
+            editor
+                .upsert(*path, EntryKind::Blob, blob)
+                .expect("add file");
+        }
+        self.tree = editor.write().expect("write tree").detach();
+
+        self.time += 60;
+        let time = format!("{} +0000", self.time);
+        let signature = SignatureRef {
+            name: author.name.into(),
+            email: author.email.into(),
+            time: &time,
+        };
+        let id = self
+            .repo
+            .commit_as(
+                signature,
+                signature,
+                "refs/heads/main",
+                "fixture commit",
+                self.tree,
+                self.head,
+            )
+            .expect("commit")
+            .detach();
+        self.head = Some(id);
+        id.to_string()
+    }
+
+    /// Points `HEAD` at `main`, which `init_bare` leaves to the git default.
+    pub fn set_head_main(&self) {
+        std::fs::write(self.repo.path().join("HEAD"), "ref: refs/heads/main\n")
+            .expect("write HEAD");
+    }
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::{Fixture, ANN, BOB};
+use integration_validation::classify::default_path_rules;
+use integration_validation::forge::{classify_forge_url, Forge, ForgeKind, ForgeUrlType};
+use integration_validation::github::{process_repo, scan_forge_repo};
+use integration_validation::local::{local_repo, LocalForge};
+use integration_validation::registry::normalize_repository;
+use integration_validation::types::{PathCategory, RepoStatus, ScanOverrides};
+use reqwest::Client;
+
+const KEYWORDS: [&str; 3] = [
+    "ephemeral-rollups-sdk",
+    "#[delegate]",
+    "MagicIntentBundleBuilder",
+];
+const EXTENSIONS: [&str; 3] = [".toml", ".rs", ".ts"];
+
+async fn scan(forge: &LocalForge, git_ref: &str) -> integration_validation::types::RepoScan {
+    process_repo(
+        &Client::new(),
+        forge,
+        "fixtures",
+        "team",
+        git_ref,
+        None,
+        &KEYWORDS,
+        &EXTENSIONS,
+        254,
+        &default_path_rules(),
+        false,
+    )
+    .await
+    .expect("scan fixture")
+}
+
+#[tokio::test]
+async fn scans_a_bare_repo_at_head_and_at_an_older_commit() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut fixture = Fixture::init(dir.path());
+    let first = fixture.commit(
+        &ANN,
+        &[
+            (
+                "programs/game/Cargo.toml",
+                "ephemeral-rollups-sdk = \"0.2\"",
+            ),
+            ("README.md", "ephemeral-rollups-sdk"),
+        ],
+    );
+    fixture.commit(
+        &BOB,
+        &[
+            ("programs/game/src/lib.rs", "#[delegate]\npub struct Game;"),
+            ("tests/game.ts", "new MagicIntentBundleBuilder()"),
+            ("node_modules/sdk/index.ts", "ephemeral-rollups-sdk"),
+        ],
+    );
+    fixture.set_head_main();
+    let forge = LocalForge::open(dir.path());
+
+    let head = scan(&forge, "HEAD").await;
+    assert_eq!(head.files_processed, 3);
+    assert_eq!(head.keyword_counts["ephemeral-rollups-sdk"].count, 1);
+    assert_eq!(head.keyword_counts["#[delegate]"].count, 1);
+    // Mixed-case keywords match, and hits are categorized by path.
+    let builder = &head.keyword_counts["MagicIntentBundleBuilder"];
+    assert_eq!(builder.categories.get(&PathCategory::Test), Some(&1));
+
+    let old = scan(&forge, &first).await;
+    assert_eq!(old.files_processed, 1);
+    assert_eq!(old.keyword_counts.len(), 1);
+}
+
+#[tokio::test]
+async fn reads_head_commit_and_contributors_from_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut fixture = Fixture::init(dir.path());
+    fixture.commit(&ANN, &[("a.rs", "1")]);
+    fixture.commit(&ANN, &[("b.rs", "2")]);
+    let head = fixture.commit(&BOB, &[("c.rs", "3")]);
+    fixture.set_head_main();
+    let forge = LocalForge::open(dir.path());
+    let client = Client::new();
+
+    let info = forge.repo_info(&client, "fixtures", "team").await.unwrap();
+    assert_eq!(info.default_branch, "main");
+    assert!(!info.empty);
+
+    let (sha, _, email, name) = forge
+        .last_commit(&client, "fixtures", "team", "main")
+        .await
+        .unwrap();
+    assert_eq!(
+        (sha, email.as_str(), name.as_str()),
+        (head, BOB.email, BOB.name)
+    );
+
+    let contributors = forge
+        .contributors(&client, "fixtures", "team", "main")
+        .await
+        .unwrap();
+    let commits: Vec<(&str, usize)> = contributors
+        .iter()
+        .map(|c| (c.name.as_str(), c.commits))
+        .collect();
+    assert_eq!(commits, [("Ann", 2), ("Bob", 1)]);
+}
+
+#[tokio::test]
+async fn walks_a_plain_directory_skipping_build_output() {
+    let dir = tempfile::tempdir().unwrap();
+    for (path, content) in [
+        ("src/lib.rs", "#[delegate]"),
+        ("target/debug/build.rs", "#[delegate]"),
+        ("node_modules/x/index.ts", "#[delegate]"),
+    ] {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    let forge = LocalForge::open(dir.path());
+
+    let scan = scan(&forge, "").await;
+    assert_eq!(scan.files_processed, 1);
+    assert_eq!(scan.keyword_counts["#[delegate]"].count, 1);
+}
+
+#[tokio::test]
+async fn scans_a_configured_local_path_end_to_end() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo_dir = dir.path().join("team-a.git");
+    let mut fixture = Fixture::init(&repo_dir);
+    let sha = fixture.commit(&ANN, &[("Cargo.toml", "ephemeral-rollups-sdk = \"0.2\"")]);
+    fixture.set_head_main();
+
+    let target = local_repo(&format!("{}#main", repo_dir.display())).unwrap();
+    assert_eq!(target.kind, ForgeKind::Local);
+    assert_eq!(target.repo_name, "team-a.git");
+    assert_eq!(target.git_ref.as_deref(), Some("main"));
+
+    let (data, status, error) = scan_forge_repo(
+        &Client::new(),
+        &target,
+        "",
+        &KEYWORDS,
+        &EXTENSIONS,
+        254,
+        "Local",
+        &default_path_rules(),
+        false,
+        &ScanOverrides::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(error, None);
+    assert_eq!(status, Some(RepoStatus::Active));
+    assert_eq!(data.forge, Some(ForgeKind::Local));
+    assert_eq!(data.commit_sha, sha);
+    assert_eq!(data.keyword_matches, "1");
+    assert_eq!(data.team_size, Some(1));
+
+    assert!(local_repo(&dir.path().join("missing").display().to_string()).is_none());
+}
+
+#[test]
+fn file_urls_from_cells_and_registries_are_not_repos() {
+    for url in [
+        "file:///etc",
+        "file:///srv/subs/team-a.git#v1.0",
+        "file://host/x/y",
+    ] {
+        assert!(
+            matches!(classify_forge_url(url), ForgeUrlType::Invalid),
+            "{}",
+            url
+        );
+        assert_eq!(normalize_repository(url), None);
+        assert_eq!(normalize_repository(&format!("git+{}", url)), None);
+    }
+}
+
+#[tokio::test]
+async fn reports_a_missing_scan_branch_instead_of_an_empty_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut fixture = Fixture::init(dir.path());
+    fixture.commit(&ANN, &[("Cargo.toml", "ephemeral-rollups-sdk = \"0.2\"")]);
+    fixture.set_head_main();
+    let overrides = ScanOverrides {
+        branch: Some("release".to_string()),
+        ..Default::default()
+    };
+
+    let (_, status, error) = scan_forge_repo(
+        &Client::new(),
+        &local_repo(&dir.path().display().to_string()).unwrap(),
+        "",
+        &KEYWORDS,
+        &EXTENSIONS,
+        254,
+        "Local",
+        &default_path_rules(),
+        false,
+        &overrides,
+    )
+    .await
+    .unwrap();
+    assert_eq!(status, Some(RepoStatus::Active));
+    assert_eq!(error.as_deref(), Some("branch 'release' not found"));
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::{Fixture, ANN, BOB};
+use integration_validation::forge::Forge;
+use integration_validation::local::LocalForge;
+use integration_validation::mirror::{with_mirror, GitCache};
+use reqwest::Client;
+use std::path::Path;
+
+fn file_url(path: &Path) -> String {
+    url::Url::from_file_path(path).unwrap().to_string()
+}
+
+async fn head_of(path: &Path) -> String {
+    commit_at(path, "main").await.expect("mirror has main")
+}
+
+async fn commit_at(path: &Path, git_ref: &str) -> Option<String> {
+    LocalForge::open(path)
+        .last_commit(&Client::new(), "fixtures", "team", git_ref)
+        .await
+        .map(|commit| commit.0)
+}
+
+#[tokio::test]
+async fn clones_then_fetches_a_file_remote() {
+    let remote = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut fixture = Fixture::init(remote.path());
+    let first = fixture.commit(&ANN, &[("Cargo.toml", "ephemeral-rollups-sdk")]);
+    fixture.set_head_main();
+    let cache = GitCache::new(cache_dir.path());
+    let url = file_url(remote.path());
+
+    let mirror = cache
+        .sync(&url, "local", "fixtures", "team", None)
+        .await
+        .unwrap();
+    assert_eq!(mirror, cache_dir.path().join("local/fixtures/team.git"));
+    assert_eq!(head_of(&mirror).await, first);
+
+    let second = fixture.commit(&BOB, &[("src/lib.rs", "#[delegate]")]);
+    let fetched = cache
+        .sync(&url, "local", "fixtures", "team", None)
+        .await
+        .unwrap();
+    assert_eq!(fetched, mirror);
+    assert_eq!(head_of(&mirror).await, second);
+}
+
+#[tokio::test]
+async fn follows_the_remote_default_branch_and_prunes_deleted_branches() {
+    let remote = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut fixture = Fixture::init(remote.path());
+    let first = fixture.commit(&ANN, &[("Cargo.toml", "ephemeral-rollups-sdk")]);
+    let feature = remote.path().join("refs/heads/feature");
+    std::fs::write(&feature, format!("{}\n", first)).unwrap();
+    let second = fixture.commit(&BOB, &[("src/lib.rs", "#[delegate]")]);
+    std::fs::write(remote.path().join("HEAD"), "ref: refs/heads/feature\n").unwrap();
+    let cache = GitCache::new(cache_dir.path());
+    let url = file_url(remote.path());
+
+    let mirror = cache
+        .sync(&url, "local", "fixtures", "team", None)
+        .await
+        .unwrap();
+    assert_eq!(commit_at(&mirror, "").await, Some(first.clone()));
+    assert_eq!(head_of(&mirror).await, second);
+
+    fixture.set_head_main();
+    std::fs::remove_file(&feature).unwrap();
+    cache
+        .sync(&url, "local", "fixtures", "team", None)
+        .await
+        .unwrap();
+    assert_eq!(commit_at(&mirror, "feature").await, None);
+    assert_eq!(commit_at(&mirror, "").await, Some(second));
+}
+
+#[tokio::test]
+async fn scans_files_and_history_through_the_mirror() {
+    let remote = tempfile::tempdir().unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut fixture = Fixture::init(remote.path());
+    fixture.commit(&ANN, &[("Cargo.toml", "ephemeral-rollups-sdk")]);
+    fixture.commit(&BOB, &[("src/lib.rs", "#[delegate]")]);
+    fixture.set_head_main();
+    let client = Client::new();
+
+    let forge = with_mirror(
+        Box::new(LocalForge::open(remote.path())),
+        &GitCache::new(cache_dir.path()),
+        "local",
+        "fixtures",
+        "team",
+    )
+    .await;
+    assert!(cache_dir
+        .path()
+        .join("local/fixtures/team.git/HEAD")
+        .is_file());
+
+    let tree = forge
+        .list_tree(&client, "fixtures", "team", "main")
+        .await
+        .unwrap();
+    assert_eq!(tree, ["Cargo.toml", "src/lib.rs"]);
+    let file = forge
+        .fetch_file(&client, "fixtures", "team", "main", "src/lib.rs")
+        .await;
+    assert_eq!(file.as_deref(), Some("#[delegate]"));
+    let contributors = forge
+        .contributors(&client, "fixtures", "team", "main")
+        .await
+        .unwrap();
+    assert_eq!(contributors.len(), 2);
+}
+
+#[tokio::test]
+async fn rejects_unsafe_paths_and_leaves_nothing_behind_on_failure() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = GitCache::new(cache_dir.path());
+    let missing = file_url(&cache_dir.path().join("no-such-remote"));
+
+    assert!(cache
+        .sync(&missing, "local", "..", "team", None)
+        .await
+        .is_err());
+    assert!(cache
+        .sync(&missing, "local", "fixtures", ".git", None)
+        .await
+        .is_err());
+
+    assert!(cache
+        .sync(&missing, "local", "fixtures", "team", None)
+        .await
+        .is_err());
+    let fixtures = cache_dir.path().join("local/fixtures");
+    assert_eq!(std::fs::read_dir(fixtures).unwrap().count(), 0);
+}